@@ -46,11 +46,22 @@
 static uint16_t ws2812_gpio_set_bits = 0;
 static uint16_t dma_buffer[DMA_BUFFER_SIZE];
 
-/* DEBUG COUNTERS - Check these in your debugger/Rust code */
-volatile uint32_t debug_dma_irq_count = 0;
-volatile uint32_t debug_tim_irq_count = 0;
+volatile struct ws2812_stats ws2812_stats;
+static uint32_t frame_start_cycles;
+
+/* Set when an underrun or DMA error cut the frame short; the reset that
+ * follows then sends it again, once */
+static int frame_aborted = 0;
+static int retries_left = 0;
+
+/* Set from ws2812_refresh until the reset after the frame, retry included,
+ * has ended; the DMA reads the framebuffers all that time */
+static volatile bool frame_busy = false;
+
+/* Channels the current frame goes to, bit n for channel n; the others stay
+ * low */
+static uint8_t refresh_mask = 0;
 
-int hal_dma_error_flag = 0;
 int framebuffer_position = 0;
 int channels_max_length = 0;
 
@@ -244,9 +255,93 @@ static void fill_dma_buffer(uint16_t *dest, int pos, const struct led_channel_in
     dest[7] = cur7;
 }
 
+/* Stream 1 writes the data bits, so it must not have read from a half before
+ * its refill is done. `filled` is the half just written (0 or 1). `next` is the
+ * item the stream reads next; right at a boundary it can't tell which side it
+ * is on and counts as in time. */
+static int refill_in_time(int filled)
+{
+    uint32_t next = (DMA_BUFFER_SIZE - DMA2_Stream1->NDTR) % DMA_BUFFER_SIZE;
+    uint32_t start = filled ? DMA_BUFFER_FILL_SIZE : 0;
+
+    return !(next > start && next < start + DMA_BUFFER_FILL_SIZE);
+}
+
+/* Count and clear transfer and direct mode errors of the three streams.
+ * Returns the number of streams that had one. */
+static int collect_dma_errors(void)
+{
+    uint32_t lisr = DMA2->LISR;
+    uint32_t hisr = DMA2->HISR;
+    int errors = 0;
+
+    if (lisr & (DMA_LISR_TEIF1 | DMA_LISR_DMEIF1)) {
+        errors++;
+    }
+    if (lisr & (DMA_LISR_TEIF2 | DMA_LISR_DMEIF2)) {
+        errors++;
+    }
+    if (hisr & (DMA_HISR_TEIF5 | DMA_HISR_DMEIF5)) {
+        errors++;
+    }
+    DMA2->LIFCR = DMA_LIFCR_CTEIF1 | DMA_LIFCR_CDMEIF1 | DMA_LIFCR_CTEIF2 | DMA_LIFCR_CDMEIF2;
+    DMA2->HIFCR = DMA_HIFCR_CTEIF5 | DMA_HIFCR_CDMEIF5;
+    ws2812_stats.dma_errors += errors;
+    return errors;
+}
+
+/* Stop the bit stream, drive all lines low and let TIM1 time the reset; its
+ * update interrupt ends the frame */
+static void start_reset(void)
+{
+    // Stop timer and disable DMA requests
+    TIM1->CR1 &= ~TIM_CR1_CEN; // Disable timer
+    TIM1->DIER &= ~(TIM_DIER_UDE | TIM_DIER_CC1DE | TIM_DIER_CC2DE); // Disable DMA requests
+
+    // Disable PWM outputs so the pin actually stays LOW
+    TIM1->CCER &= ~(TIM_CCER_CC1E | TIM_CCER_CC2E);
+
+    // Force all GPIO low immediately
+    GPIOB->BSRR = (uint32_t)ws2812_gpio_set_bits << 16;
+
+    // Clear interrupt flags before enabling UIE to prevent premature trigger
+    TIM1->CR1 &= ~(TIM_CR1_UDIS | TIM_CR1_URS); // Enable update events (clear UDIS) and allow any source to trigger updates (clear URS)
+    TIM1->ARR = RESET_TIMER_PERIOD - 1;         // Auto-Reload Register to define timer period (subtract 1 because counter is 0-based)
+    // Generate Update Event to load ARR, then CLEAR the flag it generates
+    TIM1->EGR = TIM_EGR_UG;     // Manually trigger Update Event to immediately load ARR into the shadow register
+    TIM1->SR = 0;               // Clear all status flags, including UIF set by the Update Event above
+    TIM1->DIER |= TIM_DIER_UIE; // Enable Update Interrupt (will fire when counter reaches ARR and reloads)
+    TIM1->CR1 |= TIM_CR1_CEN;   // Start the timer by setting Counter Enable bit
+}
+
+/* Give up on the bits sent so far. The strips latch what they got during the
+ * reset, and the frame goes out again after it if it has a retry left. */
+static void abort_frame(void)
+{
+    DMA2->LIFCR = DMA_LIFCR_CHTIF2 | DMA_LIFCR_CTCIF2; // Nothing more to refill
+    frame_aborted = 1;
+    start_reset();
+}
+
 /* IRQ Handlers */
 void DMA2_Stream2_Handler(void)
 {
+    int sending = framebuffer_position < channels_max_length;
+
+    // A failed stream stops, the frame can't finish
+    if (collect_dma_errors()) {
+        abort_frame();
+        return;
+    }
+
+    // Both halves pending means one of them went out without a refill
+    if ((DMA2->LISR & (DMA_LISR_HTIF2 | DMA_LISR_TCIF2)) == (DMA_LISR_HTIF2 | DMA_LISR_TCIF2)
+        && sending) {
+        ws2812_stats.underruns++;
+        abort_frame();
+        return;
+    }
+
     // Check half transfer
     if (DMA2->LISR & DMA_LISR_HTIF2) {   // Half transfer complete
         DMA2->LIFCR = DMA_LIFCR_CHTIF2;  // Clear half transfer complete flag
@@ -258,6 +353,11 @@ void DMA2_Stream2_Handler(void)
                 fill_dma_buffer(dma_buffer + i, framebuffer_position, led_channels);
                 framebuffer_position++;
             }
+            if (!refill_in_time(0)) {
+                ws2812_stats.underruns++;
+                abort_frame();
+                return;
+            }
         }
     }
     
@@ -266,66 +366,27 @@ void DMA2_Stream2_Handler(void)
         DMA2->LIFCR = DMA_LIFCR_CTCIF2; // Clear transfer complete flag
         
         if (framebuffer_position >= channels_max_length) {
-            // Stop timer and disable DMA requests
-            TIM1->CR1 &= ~TIM_CR1_CEN; // Disable timer
-            TIM1->DIER &= ~(TIM_DIER_UDE | TIM_DIER_CC1DE | TIM_DIER_CC2DE); // Disable DMA requests
-            
-            // Disable PWM outputs so the pin actually stays LOW
-            TIM1->CCER &= ~(TIM_CCER_CC1E | TIM_CCER_CC2E);
-            
-            // Force all GPIO low immediately
-            GPIOB->BSRR = (uint32_t)ws2812_gpio_set_bits << 16;
-
-            // Clear interrupt flags before enabling UIE to prevent premature trigger
-            TIM1->CR1 &= ~(TIM_CR1_UDIS | TIM_CR1_URS); // Enable update events (clear UDIS) and allow any source to trigger updates (clear URS)
-            TIM1->ARR = RESET_TIMER_PERIOD - 1;         // Auto-Reload Register to define timer period (subtract 1 because counter is 0-based)
-            // Generate Update Event to load ARR, then CLEAR the flag it generates
-            TIM1->EGR = TIM_EGR_UG;     // Manually trigger Update Event to immediately load ARR into the shadow register
-            TIM1->SR = 0;               // Clear all status flags, including UIF set by the Update Event above
-            TIM1->DIER |= TIM_DIER_UIE; // Enable Update Interrupt (will fire when counter reaches ARR and reloads)
-            TIM1->CR1 |= TIM_CR1_CEN;   // Start the timer by setting Counter Enable bit
-            
+            start_reset();
         } else {
             for (int i = 0; i < DMA_BUFFER_FILL_SIZE; i += 8) {
                 fill_dma_buffer(dma_buffer + DMA_BUFFER_FILL_SIZE + i, framebuffer_position, led_channels);
                 framebuffer_position++;
             }
+            if (!refill_in_time(1)) {
+                ws2812_stats.underruns++;
+                abort_frame();
+                return;
+            }
         }
     }
-    
-    // Check for errors
-    if (DMA2->LISR & DMA_LISR_TEIF2) { // Transfer error
-        DMA2->LIFCR = DMA_LIFCR_CTEIF2; // Clear transfer error flag
-        hal_dma_error_flag++;
-    }
     // DBG_PB9_TOGGLE();
 }
 
-
-void TIM1_UP_TIM10_Handler(void)
+/* Set up the DMA streams for led_channels and start clocking bits out */
+static void start_frame(void)
 {
-    if (TIM1->SR & TIM_SR_UIF) { // Update interrupt flag
-        TIM1->SR &= ~TIM_SR_UIF; // Clear update interrupt flag
-        
-        // Reset period finished! 
-        TIM1->CR1 &= ~TIM_CR1_CEN; // Stop timer
-        TIM1->DIER &= ~TIM_DIER_UIE; // Disable interrupt
-        
-        // Re-enable PWM outputs for the next refresh call
-        TIM1->CCER |= (TIM_CCER_CC1E | TIM_CCER_CC2E); // Enable channel 1 and 2 outputs
-    }
-}
-
-void ws2812_refresh(const struct led_channel_info *channels)
-{
-    DBG_PB9_TOGGLE();
-    TIM1->EGR |= TIM_EGR_UG; // Generate update event to load registers
     uint32_t i;
 
-    for (i = 0; i < WS2812_NUM_CHANNELS; i++) {
-        led_channels[i] = channels[i];
-    }
-
     TIM1->CR1 &= ~TIM_CR1_CEN; // Disable timer
     
     // Disable DMA streams
@@ -346,10 +407,13 @@ void ws2812_refresh(const struct led_channel_info *channels)
     }
 
     for (i = 0; i < WS2812_NUM_CHANNELS; i++) {
-        if (channels[i].length_in_bytes > channels_max_length) {
-            channels_max_length = channels[i].length_in_bytes;
+        if (!(refresh_mask & (1 << i))) {
+            continue;
         }
-        if (channels[i].length_in_bytes != 0) {
+        if (led_channels[i].length_in_bytes > channels_max_length) {
+            channels_max_length = led_channels[i].length_in_bytes;
+        }
+        if (led_channels[i].length_in_bytes != 0) {
             ws2812_gpio_set_bits |= (1 << ws2812_channel_gpio_map[i]);
         }
     }
@@ -361,14 +425,17 @@ void ws2812_refresh(const struct led_channel_info *channels)
 #endif
 
     // Clear DMA flags
-    DMA2->LIFCR = DMA_LIFCR_CTEIF1 | DMA_LIFCR_CHTIF1 | DMA_LIFCR_CTCIF1; // Stream 1
-    DMA2->LIFCR = DMA_LIFCR_CTEIF2 | DMA_LIFCR_CHTIF2 | DMA_LIFCR_CTCIF2; // Stream 2
-    DMA2->HIFCR = DMA_HIFCR_CTEIF5 | DMA_HIFCR_CHTIF5 | DMA_HIFCR_CTCIF5; // Stream 5
+    DMA2->LIFCR = DMA_LIFCR_CTEIF1 | DMA_LIFCR_CHTIF1 | DMA_LIFCR_CTCIF1 | DMA_LIFCR_CDMEIF1; // Stream 1
+    DMA2->LIFCR = DMA_LIFCR_CTEIF2 | DMA_LIFCR_CHTIF2 | DMA_LIFCR_CTCIF2 | DMA_LIFCR_CDMEIF2; // Stream 2
+    DMA2->HIFCR = DMA_HIFCR_CTEIF5 | DMA_HIFCR_CHTIF5 | DMA_HIFCR_CTCIF5 | DMA_HIFCR_CDMEIF5; // Stream 5
 
     // Clear timer flags and disable update interrupt
     TIM1->SR = 0; // Clear all status flags
     TIM1->DIER &= ~TIM_DIER_UIE; // Disable update interrupt
 
+    // PWM outputs are off while a reset is timed; a new frame may cut it short
+    TIM1->CCER |= (TIM_CCER_CC1E | TIM_CCER_CC2E);
+
     // Set DMA transfer counts
     DMA2_Stream1->NDTR = DMA_BUFFER_SIZE; // Stream 1
     DMA2_Stream2->NDTR = DMA_BUFFER_SIZE; // Stream 2
@@ -385,12 +452,73 @@ void ws2812_refresh(const struct led_channel_info *channels)
     TIM1->ARR = WS2812_TIMER_PERIOD; // Auto-Reload Register to define timer period
     TIM1->CNT = TIM1->ARR; // Start timer (potentially do ARR-10 just before the overflow to trigger DMA immediately???)
     TIM1->CR1 |= TIM_CR1_CEN; // Start the timer by setting Counter Enable bit
+}
+
+void TIM1_UP_TIM10_Handler(void)
+{
+    if (TIM1->SR & TIM_SR_UIF) { // Update interrupt flag
+        TIM1->SR &= ~TIM_SR_UIF; // Clear update interrupt flag
+        
+        // Reset period finished! 
+        TIM1->CR1 &= ~TIM_CR1_CEN; // Stop timer
+        TIM1->DIER &= ~TIM_DIER_UIE; // Disable interrupt
+        
+        // Re-enable PWM outputs for the next refresh call
+        TIM1->CCER |= (TIM_CCER_CC1E | TIM_CCER_CC2E); // Enable channel 1 and 2 outputs
+
+        if (!frame_aborted) {
+            ws2812_stats.frames_sent++;
+            ws2812_stats.last_frame_cycles = DWT->CYCCNT - frame_start_cycles;
+            frame_busy = false;
+        } else if (retries_left > 0) {
+            frame_aborted = 0;
+            retries_left--;
+            start_frame();
+        } else {
+            frame_aborted = 0;
+            ws2812_stats.frames_dropped++;
+            frame_busy = false;
+        }
+    }
+}
+
+bool ws2812_busy(void)
+{
+    return frame_busy;
+}
+
+bool ws2812_refresh(const struct led_channel_info *channels, uint8_t mask)
+{
+    // Restarting the DMA mid-frame would cut the frame in flight short
+    if (frame_busy) {
+        return false;
+    }
+    frame_busy = true;
 
     DBG_PB9_TOGGLE();
+    frame_start_cycles = DWT->CYCCNT;
+    TIM1->EGR |= TIM_EGR_UG; // Generate update event to load registers
+    uint32_t i;
+
+    for (i = 0; i < WS2812_NUM_CHANNELS; i++) {
+        led_channels[i] = channels[i];
+    }
+
+    refresh_mask = mask;
+    frame_aborted = 0;
+    retries_left = 1;
+    start_frame();
+
+    DBG_PB9_TOGGLE();
+    return true;
 }
 
 void ws2812_init(void)
 {
+    // Cycle counter for the frame timing in ws2812_stats
+    CoreDebug->DEMCR |= CoreDebug_DEMCR_TRCENA_Msk;
+    DWT->CTRL |= DWT_CTRL_CYCCNTENA_Msk;
+
     ws2812_gpio_init();
     ws2812_timer1_init();
     ws2812_dma_init();
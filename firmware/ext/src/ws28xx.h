@@ -12,7 +12,23 @@ struct led_channel_info {
 #define WS2812_NUM_CHANNELS 8
 extern struct led_channel_info led_channels[WS2812_NUM_CHANNELS];
 
+/* Counters since boot, read by the Rust side */
+struct ws2812_stats {
+    uint32_t frames_sent;       /* Frames clocked out, reset included */
+    uint32_t dma_errors;        /* Transfer and direct mode errors on the three streams */
+    uint32_t underruns;         /* Refills that finished after the DMA had reached them */
+    uint32_t last_frame_cycles; /* CPU cycles from ws2812_refresh to the end of the reset */
+    uint32_t frames_dropped;    /* Frames aborted twice, the strips kept partial data */
+};
+extern volatile struct ws2812_stats ws2812_stats;
+
 void ws2812_init(void);
-void ws2812_refresh(const struct led_channel_info *channels);
+/* Send the channels in `mask` (bit n for channel n); the others stay low and
+ * their lengths don't count towards the frame time. Returns false and sends
+ * nothing while the previous frame is still going out. */
+bool ws2812_refresh(const struct led_channel_info *channels, uint8_t mask);
+/* Whether a frame, its retry or its reset is still going out; the
+ * framebuffers must not change until it isn't */
+bool ws2812_busy(void);
 void TIM1_UP_TIM10_Handler(void);
 void DMA2_Stream2_Handler(void);
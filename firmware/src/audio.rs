@@ -0,0 +1,56 @@
+//! Microphone input for audio-reactive effects.
+//!
+//! An analog microphone module (MAX4466/MAX9814 style, output biased at
+//! VDD/2) on PA5 is sampled continuously by ADC1 into a DMA2 stream 0 ring
+//! buffer. Each block goes through `led_effects::audio::AudioAnalyzer` and the
+//! result is published to [`AUDIO`], where effects pick it up through
+//! `AudioLevel`.
+
+use defmt::{info, warn};
+use embassy_stm32::Peri;
+use embassy_stm32::adc::{Adc, SampleTime, Sequence};
+use embassy_stm32::peripherals::{ADC1, DMA2_CH0, PA5};
+use led_effects::audio::{AudioAnalyzer, AudioFeed};
+
+/// Latest microphone levels, read by the effects
+pub static AUDIO: AudioFeed = AudioFeed::new();
+
+/// Samples per analysis block: ~24 ms of audio and ~42 Hz FFT bins
+const BLOCK: usize = 1024;
+
+/// ADC clock is PCLK2 / 4 = 21 MHz and a conversion takes 480 + 12 cycles
+const SAMPLE_RATE: u32 = 21_000_000 / 492;
+
+#[embassy_executor::task]
+pub async fn microphone(
+    adc: Peri<'static, ADC1>,
+    dma: Peri<'static, DMA2_CH0>,
+    mut pin: Peri<'static, PA5>,
+) {
+    // Two blocks of headroom so a slow frame doesn't overrun the DMA
+    let mut dma_buf = [0u16; 2 * BLOCK];
+    let mut adc = Adc::new(adc).into_ring_buffered(dma, &mut dma_buf);
+    adc.set_sample_sequence(Sequence::One, &mut pin, SampleTime::CYCLES480);
+
+    let mut analyzer = AudioAnalyzer::<BLOCK>::new(SAMPLE_RATE);
+    let mut raw = [0u16; BLOCK];
+    let mut samples = [0i16; BLOCK];
+
+    info!("Microphone task started at {} Hz", SAMPLE_RATE);
+
+    loop {
+        match adc.read(&mut raw).await {
+            Ok(_) => {
+                // 12-bit unsigned → 16-bit signed; the analyzer removes the bias
+                for (sample, &value) in samples.iter_mut().zip(&raw) {
+                    *sample = ((value as i32 - 2048) << 4) as i16;
+                }
+                AUDIO.publish(&analyzer.analyze(&samples));
+            }
+            Err(_) => {
+                // The next read restarts the ring buffer from scratch
+                warn!("ADC overrun, restarting microphone sampling");
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! User button (blue, PA0) → gestures → effect selection.
+//!
+//! The EXTI edge wakes the task, `led_control::Button` debounces and turns
+//! the edges into gestures, and [`SELECTOR`] applies them. The render loop
+//! reads the selection from the same static.
+
+use defmt::{Debug2Format, info};
+use embassy_stm32::Peri;
+use embassy_stm32::exti::ExtiInput;
+use embassy_stm32::gpio::Pull;
+use embassy_stm32::peripherals::{EXTI0, PA0};
+use embassy_time::{Instant, with_deadline};
+use led_control::{Button, ButtonConfig, EffectSelector};
+
+/// Current effect, brightness and power state
+pub static SELECTOR: EffectSelector = EffectSelector::new(crate::EFFECT_COUNT);
+
+#[embassy_executor::task]
+pub async fn button(pin: Peri<'static, PA0>, ch: Peri<'static, EXTI0>) {
+    // The Discovery board has an external pull-down; pressed reads high
+    let mut input = ExtiInput::new(pin, ch, Pull::None);
+    let mut button = Button::new(ButtonConfig::DEFAULT);
+
+    info!("Button task started");
+
+    loop {
+        let now = Instant::now().as_millis();
+        if let Some(gesture) = button.update(input.is_high(), now) {
+            let action = SELECTOR.handle(gesture);
+            info!(
+                "Button {} -> {}",
+                Debug2Format(&gesture),
+                Debug2Format(&action)
+            );
+        }
+
+        // Sleep until the next edge, or until a gesture times out
+        match button.next_deadline() {
+            Some(deadline) => {
+                let _ =
+                    with_deadline(Instant::from_millis(deadline), input.wait_for_any_edge()).await;
+            }
+            None => input.wait_for_any_edge().await,
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Serial command console on USART1 (PA9 TX / PA10 RX, the ST-LINK virtual
+//! COM port) at 115200 8N1.
+//!
+//! Lines are parsed by `led_control::console`; see there for the commands.
+//! Replies are formatted into a fixed buffer, so nothing is allocated.
+
+use core::fmt::Write as _;
+use core::sync::atomic::Ordering;
+
+use defmt::{info, unwrap, warn};
+use embassy_stm32::Peri;
+use embassy_stm32::peripherals::{PA9, PA10, USART1};
+use embassy_stm32::usart::{BufferedUart, Config};
+use embedded_io_async::{Read, Write};
+use heapless::String;
+use led_control::settings::CHANNELS;
+use led_control::{Action, ChannelConfig, Command, Input, LineBuffer, parse};
+use led_effects::SharedParam;
+
+use crate::button::SELECTOR;
+use crate::{EFFECT_NAMES, FIREWORKS, FRAME_RATE, Irqs, NUM_LEDS, driver, scheduler, settings};
+
+/// Effect parameters adjustable with `param <name> <value>`
+pub static HUE: SharedParam = SharedParam::new(21); // Orange
+pub static SATURATION: SharedParam = SharedParam::new(255);
+
+const PARAMS: [(&str, &SharedParam); 2] = [("hue", &HUE), ("saturation", &SATURATION)];
+
+const HELP: &str = "commands: list, select <n>, brightness <0-255>, \
+                    param <name> <value>, stats, fps <1-1000>, stream, \
+                    channel <n> <pixels> <universe> <address>, fire\r\n";
+
+#[embassy_executor::task]
+pub async fn console(
+    usart: Peri<'static, USART1>,
+    tx: Peri<'static, PA9>,
+    rx: Peri<'static, PA10>,
+) {
+    let mut tx_buf = [0u8; 256];
+    let mut rx_buf = [0u8; 64];
+    let mut uart = unwrap!(BufferedUart::new(
+        usart,
+        rx,
+        tx,
+        &mut tx_buf,
+        &mut rx_buf,
+        Irqs,
+        Config::default(),
+    ));
+
+    let mut line = LineBuffer::<64>::new();
+    let mut bytes = [0u8; 16];
+    let mut reply: String<512> = String::new();
+
+    info!("Console task started");
+    let _ = uart.write_all(b"\r\nled console, type 'help'\r\n> ").await;
+
+    loop {
+        let count = match uart.read(&mut bytes).await {
+            Ok(count) => count,
+            Err(e) => {
+                warn!("Console read error: {}", e);
+                continue;
+            }
+        };
+
+        for &byte in &bytes[..count] {
+            // Echo, so the terminal shows what is typed
+            let _ = match byte {
+                b'\r' | b'\n' => uart.write_all(b"\r\n").await,
+                0x08 | 0x7F => uart.write_all(b"\x08 \x08").await,
+                _ => uart.write_all(&[byte]).await,
+            };
+
+            reply.clear();
+            match line.push(byte) {
+                Input::Pending => continue,
+                Input::Line(text) => run(text, &mut reply).await,
+                Input::Overflow => {
+                    let _ = reply.push_str("error: line too long\r\n");
+                }
+                Input::Invalid => {
+                    let _ = reply.push_str("error: not UTF-8\r\n");
+                }
+            }
+            let _ = reply.push_str("> ");
+            let _ = uart.write_all(reply.as_bytes()).await;
+        }
+    }
+}
+
+/// Execute one line, writing the reply into `out`. Shared with the USB port.
+pub async fn run(line: &str, out: &mut String<512>) {
+    // A full reply buffer only truncates the reply
+    let _ = match parse(line) {
+        Ok(Command::List) => list(out),
+        Ok(Command::Select(effect)) => {
+            if SELECTOR.select(effect) {
+                write!(out, "selected {}\r\n", EFFECT_NAMES[effect])
+            } else {
+                write!(out, "error: no effect {effect}, try 'list'\r\n")
+            }
+        }
+        Ok(Command::Brightness(level)) => {
+            SELECTOR.apply(Action::Brightness(level));
+            write!(out, "brightness {level}\r\n")
+        }
+        Ok(Command::Param { name, value }) => {
+            match PARAMS.iter().find(|(param, _)| *param == name) {
+                Some((_, param)) if (0..=255).contains(&value) => {
+                    param.set(value as u8);
+                    write!(out, "{name} = {value}\r\n")
+                }
+                Some(_) => write!(out, "error: {name} takes 0-255\r\n"),
+                None => write!(
+                    out,
+                    "error: no parameter '{name}', have hue, saturation\r\n"
+                ),
+            }
+        }
+        Ok(Command::Stats) => stats(out).await,
+        Ok(Command::Fps(fps)) => match driver::with_driver(|driver| driver.check_fps(fps)).await {
+            Err(error) => write!(out, "error: {error}\r\n"),
+            Ok(()) => {
+                FRAME_RATE.store(fps, Ordering::Relaxed);
+                write!(out, "fps {fps}\r\n")
+            }
+        },
+        Ok(Command::Stream) => out.write_str("error: streaming needs the USB port\r\n"),
+        Ok(Command::Fire) => {
+            FIREWORKS.fire();
+            out.write_str("fired\r\n")
+        }
+        Ok(Command::Channel {
+            channel,
+            pixels,
+            universe,
+            address,
+        }) => {
+            if channel >= CHANNELS {
+                write!(out, "error: channels are 0-{}\r\n", CHANNELS - 1)
+            } else if pixels as usize > NUM_LEDS {
+                write!(out, "error: at most {NUM_LEDS} pixels\r\n")
+            } else {
+                settings::set_channel(
+                    channel,
+                    ChannelConfig {
+                        pixels,
+                        universe,
+                        address,
+                    },
+                );
+                write!(
+                    out,
+                    "channel {channel}: {pixels} pixels at {universe}/{address}, \
+                     applies after a reset\r\n"
+                )
+                .and_then(|()| layout_rate(out))
+            }
+        }
+        Ok(Command::Help) => out.write_str(HELP),
+        Err(error) => write!(out, "error: {error}\r\n"),
+    };
+}
+
+async fn stats(out: &mut String<512>) -> core::fmt::Result {
+    let frames = scheduler::stats();
+    write!(
+        out,
+        "frames {} render {} us (max {}) fps {}/{} overruns {} skipped {} \
+         effect {} brightness {} {}\r\n",
+        frames.frames,
+        frames.last_render_us,
+        frames.max_render_us,
+        frames.fps,
+        FRAME_RATE.load(Ordering::Relaxed),
+        frames.overruns,
+        frames.dropped,
+        SELECTOR.effect(),
+        SELECTOR.brightness(),
+        if SELECTOR.is_on() { "on" } else { "off" },
+    )?;
+    let driver = driver::with_driver(|driver| driver.stats()).await;
+    write!(
+        out,
+        "driver: sent {} at {:.1} fps, last {} us, dma errors {} underruns {} dropped {}\r\n",
+        driver.frames_sent,
+        driver.fps,
+        driver.last_frame_us,
+        driver.dma_errors,
+        driver.underruns,
+        driver.frames_dropped,
+    )
+}
+
+/// Warn if the saved layout can't be sent at the frame rate
+fn layout_rate(out: &mut String<512>) -> core::fmt::Result {
+    let longest = settings::channels()
+        .iter()
+        .map(|channel| channel.pixels as usize)
+        .max()
+        .unwrap_or(0);
+    let fps = FRAME_RATE.load(Ordering::Relaxed);
+    let max_fps = driver::LedDriver::max_fps_for(longest);
+    if fps > max_fps {
+        write!(
+            out,
+            "warning: that layout runs at most {max_fps} fps, not {fps}\r\n"
+        )?;
+    }
+    Ok(())
+}
+
+fn list(out: &mut String<512>) -> core::fmt::Result {
+    let current = SELECTOR.effect();
+    for (index, name) in EFFECT_NAMES.iter().enumerate() {
+        let marker = if index == current { '*' } else { ' ' };
+        write!(out, "{marker}{index}: {name}\r\n")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,162 @@
+//! DMX512 input on UART5 RX (PD2) at 250 kbaud, through an RS-485
+//! transceiver (MAX485 or similar with DE and /RE tied low).
+//!
+//! The UART interrupt feeds every character to `led_dmx::dmx512::Decoder`,
+//! which swaps its buffers when a frame completes. The interrupt is our own
+//! rather than the embassy driver's: breaks arrive as framing errors, and
+//! the embassy readers restart reception on errors, losing the slots still
+//! buffered. The [`dmx512`] task copies each new frame out and publishes it
+//! to [`DMX`], where any effect parameter can follow a slot through
+//! `DmxSlot`, then applies [`ROLE`] to it. Publishing stores 512 atomics, too
+//! long for the interrupt with the LED refills waiting behind it.
+
+use core::cell::RefCell;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use critical_section::Mutex;
+use defmt::{info, unwrap, warn};
+use embassy_stm32::Peri;
+use embassy_stm32::interrupt::typelevel::{Handler, Interrupt, UART5};
+use embassy_stm32::pac;
+use embassy_stm32::peripherals::{self, PD2};
+use embassy_stm32::usart::{Config, StopBits, UartRx};
+use embassy_time::{Instant, Timer};
+use led_control::Action;
+use led_dmx::Mapping;
+use led_dmx::dmx512::{BAUD_RATE, Decoder, Event, MAX_SLOTS};
+use led_effects::DmxUniverse;
+
+use crate::button::SELECTOR;
+use crate::{EFFECT_COUNT, driver};
+
+/// Latest frame
+pub static DMX: DmxUniverse = DmxUniverse::new();
+
+static DECODER: Mutex<RefCell<Decoder>> = Mutex::new(RefCell::new(Decoder::new()));
+
+/// What the universe drives
+#[allow(dead_code)] // Only the variant in ROLE is built
+enum Role {
+    /// `dimmer` sets the brightness and `effect` picks the effect, with the
+    /// 0-255 range split evenly between the effects
+    Control { dimmer: u16, effect: u16 },
+    /// Pixels straight from the slots, laid out as universe [`UNIVERSE`]
+    Pixels(Mapping<8>),
+}
+
+/// Master dimmer and effect select. For pixels use e.g.
+/// `Role::Pixels(Mapping::new([Some(ChannelMap { universe: UNIVERSE,
+/// address: 1, pixels: NUM_LEDS as u16 }), None, ...]))`
+const ROLE: Role = Role::Control {
+    dimmer: 1,
+    effect: 2,
+};
+
+/// Universe number for `Role::Pixels` mappings
+const UNIVERSE: u16 = 1;
+
+/// A line without frames for this long gives the strips back
+const TIMEOUT_MS: u32 = 1000;
+
+/// Uptime in ms of the last frame, 0 = never
+static LAST_FRAME_MS: AtomicU32 = AtomicU32::new(0);
+
+/// Whether DMX pixel data is driving the strips
+pub fn is_driving() -> bool {
+    let last = LAST_FRAME_MS.load(Ordering::Relaxed);
+    matches!(ROLE, Role::Pixels(_))
+        && last != 0
+        && (Instant::now().as_millis() as u32).wrapping_sub(last) < TIMEOUT_MS
+}
+
+pub struct Uart5Handler;
+impl Handler<UART5> for Uart5Handler {
+    unsafe fn on_interrupt() {
+        let regs = pac::UART5;
+        let sr = regs.sr().read();
+        if !sr.rxne() && !sr.ore() {
+            return;
+        }
+
+        // Reading DR after SR clears RXNE and the error flags
+        let data = regs.dr().read().dr() as u8;
+        let event = if sr.fe() {
+            if data == 0 {
+                Event::Break
+            } else {
+                Event::Error
+            }
+        } else if sr.ne() || sr.ore() {
+            Event::Error
+        } else {
+            Event::Byte(data)
+        };
+
+        critical_section::with(|cs| {
+            DECODER.borrow_ref_mut(cs).push(event);
+        });
+    }
+}
+
+#[embassy_executor::task]
+pub async fn dmx512(uart: Peri<'static, peripherals::UART5>, rx: Peri<'static, PD2>) {
+    let mut config = Config::default();
+    config.baudrate = BAUD_RATE;
+    config.stop_bits = StopBits::STOP2;
+    // Held for the clock and pin setup; the interrupt reads the data
+    let _rx = unwrap!(UartRx::new_blocking(uart, rx, config));
+    pac::UART5.cr1().modify(|w| w.set_rxneie(true));
+    UART5::unpend();
+    unsafe { UART5::enable() };
+
+    info!("DMX512 input started");
+    let mut seen = 0;
+    let mut frame = [0; MAX_SLOTS];
+
+    loop {
+        // DMX refreshes at 44 Hz at most
+        Timer::after_millis(10).await;
+        // Copied under the decoder's lock, so the frame can't swap mid-copy;
+        // the rest runs with interrupts on
+        let len = critical_section::with(|cs| {
+            let decoder = DECODER.borrow_ref(cs);
+            if decoder.frames() == seen {
+                return None;
+            }
+            seen = decoder.frames();
+            let slots = decoder.frame();
+            frame[..slots.len()].copy_from_slice(slots);
+            Some(slots.len())
+        });
+        let Some(len) = len else {
+            continue;
+        };
+        let frame = &frame[..len];
+        DMX.publish(frame);
+        LAST_FRAME_MS.store(
+            (Instant::now().as_millis() as u32).max(1),
+            Ordering::Relaxed,
+        );
+
+        match &ROLE {
+            Role::Control { dimmer, effect } => {
+                SELECTOR.apply(Action::Brightness(DMX.slot(*dimmer)));
+                let selected = DMX.slot(*effect) as usize * EFFECT_COUNT / 256;
+                if selected != SELECTOR.effect() {
+                    SELECTOR.select(selected);
+                }
+            }
+            Role::Pixels(mapping) => {
+                for span in mapping.spans(UNIVERSE, frame) {
+                    driver::with_channel(span.channel, |channel| {
+                        channel.write_rgb(span.pixel, span.data)
+                    })
+                    .await;
+                }
+                if let Err(error) = driver::refresh().await {
+                    warn!("Frame lost: {}", error);
+                }
+            }
+        }
+    }
+}
@@ -1,24 +1,111 @@
-use super::ffi::{LedChannelInfo, WS2812_NUM_CHANNELS, led_channels, ws2812_init, ws2812_refresh};
+use super::ffi::{
+    LedChannelInfo, TIM1_UP_TIM10_Handler, WS2812_NUM_CHANNELS, led_channels, ws2812_busy,
+    ws2812_init, ws2812_refresh, ws2812_stats,
+};
 use core::ptr::{addr_of, addr_of_mut};
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::mutex::Mutex;
+use embassy_sync::signal::Signal;
+use embassy_time::{Duration, Instant};
 use led_effects::Pixel;
+use static_cell::ConstStaticCell;
+
+/// Core clock set up in `init`, for the cycle counts of the C driver
+const CPU_MHZ: u32 = 168;
+
+/// Window the achieved frame rate is measured over
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// WS2812 bit time, 800 kHz
+const BIT_NS: u32 = 1250;
+/// Bytes `ws2812_refresh` sends past the longest channel: the prefilled DMA
+/// buffer and the padding for channels of different lengths
+const TRAILING_BYTES: u32 = 5;
+/// Low time latching a frame, `RESET_TIMER_PERIOD` in the C driver
+const RESET_US: u32 = 300;
+
+/// Every channel, for [`refresh_channels`]
+pub const ALL_CHANNELS: u8 = 0xFF;
+
+/// Pixel memory for one channel: the pixels tasks render into, and the copy
+/// of them the DMA sends from, which only [`refresh`] writes, once the frame
+/// before is out
+pub struct ChannelRam<const N: usize> {
+    pixels: [Pixel; N],
+    frame: [Pixel; N],
+}
+
+impl<const N: usize> ChannelRam<N> {
+    pub const fn new() -> Self {
+        Self {
+            pixels: [Pixel::BLACK; N],
+            frame: [Pixel::BLACK; N],
+        }
+    }
+}
+
+impl<const N: usize> Default for ChannelRam<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pixel memory for one channel, handed out once by its static cell.
+/// Registering it with [`init_channel`] consumes it, so a buffer can't end
+/// up on two channels.
+pub struct ChannelBuffer<const N: usize> {
+    ram: &'static mut ChannelRam<N>,
+    len: usize,
+}
+
+impl<const N: usize> ChannelBuffer<N> {
+    /// Take the cell's buffer; panics if it was taken before
+    pub fn take(cell: &'static ConstStaticCell<ChannelRam<N>>) -> Self {
+        Self {
+            ram: cell.take(),
+            len: N,
+        }
+    }
+
+    /// Use only the first `len` pixels, for a strip shorter than the buffer
+    pub fn with_len(mut self, len: usize) -> Self {
+        self.len = len.min(N);
+        self
+    }
+}
+
+/// A channel can't be set up, from [`init_channel`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, defmt::Format)]
+pub enum ChannelError {
+    /// Past [`WS2812_NUM_CHANNELS`]
+    NoSuchChannel,
+    /// The channel already has a buffer
+    InUse,
+}
 
 pub struct LedChannel {
     buffer: &'static mut [Pixel],
-    channel_number: u8,
+    /// What the DMA sends, copied from `buffer` by [`refresh`]; the last
+    /// frame sent, to tell whether `buffer` changed since
+    frame: &'static mut [Pixel],
+    /// The strip may not show `frame`: it was never sent, or lost on the way
+    dirty: bool,
 }
 
 impl LedChannel {
-    /// SAFETY: The buffer must truly be 'static and not used elsewhere.
-    unsafe fn new(channel_number: u8, buffer: &'static mut [Pixel]) -> Self {
-        let num_pixels = buffer.len();
+    fn new(channel_number: u8, buffer: &'static mut [Pixel], frame: &'static mut [Pixel]) -> Self {
+        let num_pixels = frame.len();
 
+        // SAFETY: the C driver reads the table only while sending, from
+        // `refresh` on, which `init_channel` holds off; the frame buffer
+        // lives forever and is ours alone
         unsafe {
             // Use addr_of_mut! to access the global array safely
             let base_ptr = addr_of_mut!(led_channels);
 
             // Write the info directly to the specific index
             (*base_ptr)[channel_number as usize] = LedChannelInfo {
-                framebuffer: buffer.as_ptr() as *const u8,
+                framebuffer: frame.as_ptr() as *const u8,
                 length_in_bytes: (num_pixels as u32) * 3,
                 channel_number,
             };
@@ -26,35 +113,134 @@ impl LedChannel {
 
         Self {
             buffer,
-            channel_number,
+            frame,
+            // Whatever the strip shows now, the first refresh sets it
+            dirty: true,
         }
     }
 
+    /// The pixels, for writing. The next refresh sends them if they differ
+    /// from the last frame sent.
     #[inline]
     pub fn buffer_mut(&mut self) -> &mut [Pixel] {
         self.buffer
     }
 
+    /// Turn all pixels off
     #[inline]
     pub fn clear(&mut self) {
         self.buffer.fill(Pixel::BLACK);
     }
+
+    /// Whether the next refresh sends this channel: the pixels changed
+    /// since the last frame sent, or that frame may not have arrived
+    #[inline]
+    pub fn is_dirty(&self) -> bool {
+        self.dirty || self.buffer != self.frame
+    }
+
+    /// Hand the pixels to the DMA for the next frame if they need sending;
+    /// only while the C driver is idle
+    fn stage(&mut self) -> bool {
+        if !self.is_dirty() {
+            return false;
+        }
+        self.frame.copy_from_slice(self.buffer);
+        self.dirty = false;
+        true
+    }
+
+    /// Copy RGB bytes, three per pixel, starting at pixel `first`. Returns
+    /// `false` and leaves the buffer alone if they run past the end.
+    pub fn write_rgb(&mut self, first: usize, rgb: &[u8]) -> bool {
+        let (pixels, _) = rgb.as_chunks::<3>();
+        let Some(target) = self.buffer.get_mut(first..first + pixels.len()) else {
+            return false;
+        };
+        for (pixel, &[r, g, b]) in target.iter_mut().zip(pixels) {
+            *pixel = Pixel::new(r, g, b);
+        }
+        true
+    }
 }
 
 // ============================================================================
 // DRIVER MANAGER
 // ============================================================================
 
+/// Counters since boot and the current frame rate, from [`LedDriver::stats`]
+#[derive(Clone, Copy, Debug, defmt::Format)]
+pub struct DriverStats {
+    /// Frames clocked out to the strips, reset included
+    pub frames_sent: u32,
+    /// DMA transfer and direct mode errors
+    pub dma_errors: u32,
+    /// Buffer refills that finished after the DMA had reached them, so
+    /// stale bits went out
+    pub underruns: u32,
+    /// Wire time of the last frame, from `refresh` to the end of the reset
+    pub last_frame_us: u32,
+    /// Frames given up after the retry, see [`RefreshError`]
+    pub frames_dropped: u32,
+    /// Frames sent per second, measured over about a second
+    pub fps: f32,
+}
+
+/// A frame sent since the previous [`refresh`] was lost. The DMA runs on
+/// after `refresh` returns, so a frame's error shows up on the call after it.
+///
+/// On an underrun or a DMA error the driver stops the frame, holds the lines
+/// low for a reset and sends the frame again; these are the frames where
+/// that failed too. The strips keep what they received of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, defmt::Format)]
+pub enum RefreshError {
+    /// The buffer refill came too late
+    Underrun,
+    /// A DMA stream failed
+    Dma,
+}
+
+/// The strips can't be sent at the requested rate, from
+/// [`LedDriver::check_fps`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, defmt::Format)]
+pub struct FrameRateError {
+    /// Wire time of a frame, reset included
+    pub frame_us: u32,
+    pub max_fps: u16,
+}
+
+impl core::fmt::Display for FrameRateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "frames take {} us to send, at most {} fps",
+            self.frame_us, self.max_fps
+        )
+    }
+}
+
 pub struct LedDriver {
-    channels: [Option<LedChannel>; WS2812_NUM_CHANNELS],
+    /// Pixels of each channel set up, 0 for none
+    lengths: [usize; WS2812_NUM_CHANNELS],
     initialized: bool,
+    /// `frames_dropped` and `dma_errors` at the previous refresh
+    seen: (u32, u32),
+    /// Channels of the last frame sent, sent again if it is lost
+    sent: u8,
+    /// Start of the frame rate window and `frames_sent` at that time
+    window: (Instant, u32),
+    fps: f32,
 }
 
 impl LedDriver {
     pub const fn new() -> Self {
         Self {
-            channels: [None, None, None, None, None, None, None, None],
+            lengths: [0; WS2812_NUM_CHANNELS],
             initialized: false,
+            seen: (0, 0),
+            sent: 0,
+            window: (Instant::from_ticks(0), 0),
+            fps: 0.0,
         }
     }
 
@@ -67,27 +253,116 @@ impl LedDriver {
         }
     }
 
-    /// Now accepts the static buffer as an argument
-    pub fn init_channel(&mut self, channel_number: u8, buffer: &'static mut [Pixel]) {
-        if (channel_number as usize) < WS2812_NUM_CHANNELS {
-            unsafe {
-                self.channels[channel_number as usize] =
-                    Some(LedChannel::new(channel_number, buffer));
+    /// Wire time of a frame whose longest channel has `pixels` pixels, from
+    /// `refresh` to the end of the reset. All channels are sent in parallel,
+    /// so only the longest counts.
+    pub const fn frame_time_us(pixels: usize) -> u32 {
+        let bytes = pixels as u32 * 3 + TRAILING_BYTES;
+        bytes * 8 * BIT_NS / 1000 + RESET_US
+    }
+
+    /// Fastest frame rate for a longest channel of `pixels` pixels; 8×1000
+    /// pixels make about 32 fps
+    pub const fn max_fps_for(pixels: usize) -> u16 {
+        let fps = 1_000_000 / Self::frame_time_us(pixels);
+        if fps > u16::MAX as u32 {
+            u16::MAX
+        } else {
+            fps as u16
+        }
+    }
+
+    /// Pixels on the longest channel
+    pub fn max_pixels(&self) -> usize {
+        self.lengths.iter().copied().max().unwrap_or(0)
+    }
+
+    /// [`max_fps_for`](Self::max_fps_for) the channels set up now
+    pub fn max_fps(&self) -> u16 {
+        Self::max_fps_for(self.max_pixels())
+    }
+
+    /// Whether the channels set up now can be sent `fps` times a second
+    pub fn check_fps(&self, fps: u16) -> Result<(), FrameRateError> {
+        let max_fps = self.max_fps();
+        if fps <= max_fps {
+            Ok(())
+        } else {
+            Err(FrameRateError {
+                frame_us: Self::frame_time_us(self.max_pixels()),
+                max_fps,
+            })
+        }
+    }
+
+    /// Check for frames lost since the previous refresh. Only while the C
+    /// driver is idle.
+    fn take_lost(&mut self) -> Result<(), RefreshError> {
+        let raw = unsafe { addr_of!(ws2812_stats).read_volatile() };
+        let (dropped, dma_errors) =
+            core::mem::replace(&mut self.seen, (raw.frames_dropped, raw.dma_errors));
+
+        if raw.frames_dropped == dropped {
+            Ok(())
+        } else if raw.dma_errors != dma_errors {
+            Err(RefreshError::Dma)
+        } else {
+            Err(RefreshError::Underrun)
+        }
+    }
+
+    /// Start sending `channels`, staged already; only while the C driver
+    /// is idle. Returns whether they went out, nothing to send included.
+    fn start(&mut self, channels: u8) -> bool {
+        let mut started = true;
+        if channels != 0 {
+            // The C driver restarts the DMA and timer that its interrupts
+            // use, so the handoff itself keeps them off
+            started = critical_section::with(|_| unsafe {
+                // Use addr_of! to get the pointer to the array head
+                // without creating a shared reference.
+                let channels_ptr = addr_of!(led_channels) as *const LedChannelInfo;
+                ws2812_refresh(channels_ptr, channels)
+            });
+            if started {
+                self.sent = channels;
             }
         }
+        self.measure_rate();
+        started
     }
 
-    pub fn channel_mut(&mut self, channel_number: u8) -> Option<&mut LedChannel> {
-        self.channels.get_mut(channel_number as usize)?.as_mut()
+    pub fn stats(&self) -> DriverStats {
+        // The interrupts update the counters; read them all at one point
+        let raw = critical_section::with(|_| unsafe { addr_of!(ws2812_stats).read_volatile() });
+        // Nothing refreshed for a whole window
+        let idle = self.window.0.elapsed() > RATE_WINDOW * 2;
+        DriverStats {
+            frames_sent: raw.frames_sent,
+            dma_errors: raw.dma_errors,
+            underruns: raw.underruns,
+            last_frame_us: raw.last_frame_cycles / CPU_MHZ,
+            frames_dropped: raw.frames_dropped,
+            fps: if idle { 0.0 } else { self.fps },
+        }
     }
 
-    pub fn refresh(&self) {
-        unsafe {
-            // Use addr_of! to get the pointer to the array head
-            // without creating a shared reference.
-            let channels_ptr = addr_of!(led_channels) as *const LedChannelInfo;
-            ws2812_refresh(channels_ptr);
+    /// Frames sent per second, over windows of at least [`RATE_WINDOW`]
+    fn measure_rate(&mut self) {
+        let now = Instant::now();
+        let (start, frames_then) = self.window;
+        let elapsed = now.duration_since(start);
+        if elapsed < RATE_WINDOW {
+            return;
         }
+        let frames = unsafe { addr_of!(ws2812_stats.frames_sent).read_volatile() };
+        // After an idle stretch the measurement starts over
+        self.fps = if elapsed < RATE_WINDOW * 2 {
+            frames.wrapping_sub(frames_then) as f32 * 1_000_000.0 / elapsed.as_micros() as f32
+        } else {
+            0.0
+        };
+        self.window = (now, frames);
     }
 }
 
@@ -95,27 +370,144 @@ impl LedDriver {
 // GLOBAL STATIC DRIVER
 // ============================================================================
 
-use core::cell::RefCell;
-use critical_section::Mutex;
+// Async mutexes rather than a critical section around the whole closure:
+// interrupts, the DMA refill among them, keep running while a task renders.
+// Each channel has its own, so tasks render different channels at once and
+// only the refresh handoff goes through the driver's.
+
+/// Refresh, statistics and setup
+static LED_DRIVER: Mutex<CriticalSectionRawMutex, LedDriver> = Mutex::new(LedDriver::new());
+
+/// Raised by the timer interrupt when a frame is out, retry and reset
+/// included
+static FRAME_DONE: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+static CHANNELS: [Mutex<CriticalSectionRawMutex, Option<LedChannel>>; WS2812_NUM_CHANNELS] =
+    [const { Mutex::new(None) }; WS2812_NUM_CHANNELS];
+
+pub async fn init_global_driver() {
+    LED_DRIVER.lock().await.init_hardware();
+}
+
+/// The TIM1 update interrupt: runs the C driver's handler and wakes a
+/// [`refresh`] waiting for the frame to finish
+///
+/// # Safety
+///
+/// Only from the TIM1_UP_TIM10 interrupt.
+pub unsafe fn on_timer_interrupt() {
+    unsafe {
+        TIM1_UP_TIM10_Handler();
+        if !ws2812_busy() {
+            FRAME_DONE.signal(());
+        }
+    }
+}
+
+/// Start sending the channels that changed, see [`refresh_channels`]
+pub async fn refresh() -> Result<(), RefreshError> {
+    refresh_channels(ALL_CHANNELS).await
+}
+
+/// Start sending the channels in `mask` (bit n for channel n) that changed
+/// since they were last sent; the other pins stay low and the frame is only
+/// as long as the longest of them. Nothing is sent when none changed.
+///
+/// Waits for the frame before to go out first: the DMA reads each channel's
+/// copy of its pixels until then, while tasks go on rendering into their
+/// own. Reports frames lost since the previous call; the new frame goes out
+/// regardless.
+pub async fn refresh_channels(mask: u8) -> Result<(), RefreshError> {
+    // Only a refresh starts frames, so with the driver held the C driver
+    // stays idle once it is
+    let mut driver = LED_DRIVER.lock().await;
+    // A signal left from an earlier frame ends the wait early; check again
+    while unsafe { ws2812_busy() } {
+        FRAME_DONE.wait().await;
+    }
 
-static LED_DRIVER: Mutex<RefCell<Option<LedDriver>>> = Mutex::new(RefCell::new(None));
+    let result = driver.take_lost();
+    if result.is_err() {
+        // The strips kept part of the lost frame; send those channels again
+        mark_dirty(driver.sent).await;
+    }
+    let mut channels = 0;
+    for (number, slot) in CHANNELS.iter().enumerate() {
+        if mask & (1 << number) != 0
+            && let Some(channel) = slot.lock().await.as_mut()
+            && channel.stage()
+        {
+            channels |= 1 << number;
+        }
+    }
+    if !driver.start(channels) {
+        mark_dirty(channels).await;
+    }
+    result
+}
 
-pub fn init_global_driver() {
-    critical_section::with(|cs| {
-        let mut driver = LedDriver::new();
-        driver.init_hardware();
-        LED_DRIVER.borrow_ref_mut(cs).replace(driver);
-    });
+/// Send `channels` on the next refresh even if they haven't changed
+async fn mark_dirty(channels: u8) {
+    for (number, slot) in CHANNELS.iter().enumerate() {
+        if channels & (1 << number) != 0
+            && let Some(channel) = slot.lock().await.as_mut()
+        {
+            channel.dirty = true;
+        }
+    }
 }
 
-pub fn with_driver<F, R>(f: F) -> Option<R>
+pub async fn with_driver<F, R>(f: F) -> R
 where
     F: FnOnce(&mut LedDriver) -> R,
 {
-    critical_section::with(|cs| {
-        LED_DRIVER
-            .borrow_ref_mut(cs)
-            .as_mut()
-            .map(|driver| f(driver))
-    })
+    f(&mut *LED_DRIVER.lock().await)
+}
+
+/// Run `f` once the frame going out has finished, with no new one starting
+/// until it is done, e.g. a flash erase
+pub async fn while_idle<R>(f: impl AsyncFnOnce() -> R) -> R {
+    // Only a refresh starts frames, and it needs the driver
+    let _driver = LED_DRIVER.lock().await;
+    while unsafe { ws2812_busy() } {
+        FRAME_DONE.wait().await;
+    }
+    f().await
+}
+
+/// Run `f` on a channel; `None` if it isn't set up. Other channels and the
+/// refresh aren't held up meanwhile.
+pub async fn with_channel<F, R>(channel_number: u8, f: F) -> Option<R>
+where
+    F: FnOnce(&mut LedChannel) -> R,
+{
+    let mut channel = CHANNELS.get(channel_number as usize)?.lock().await;
+    channel.as_mut().map(f)
+}
+
+/// Give `channel_number` its pixel buffer. A channel is set up once and
+/// keeps its buffer from then on.
+pub async fn init_channel<const N: usize>(
+    channel_number: u8,
+    buffer: ChannelBuffer<N>,
+) -> Result<(), ChannelError> {
+    let slot = CHANNELS
+        .get(channel_number as usize)
+        .ok_or(ChannelError::NoSuchChannel)?;
+    // No refresh while the C driver's table changes. The driver first, as
+    // `refresh_channels` locks them.
+    let mut driver = LED_DRIVER.lock().await;
+    let mut slot = slot.lock().await;
+    if slot.is_some() {
+        return Err(ChannelError::InUse);
+    }
+
+    let ram = buffer.ram;
+    driver.lengths[channel_number as usize] = buffer.len;
+    *slot = Some(LedChannel::new(
+        channel_number,
+        &mut ram.pixels[..buffer.len],
+        &mut ram.frame[..buffer.len],
+    ));
+    Ok(())
 }
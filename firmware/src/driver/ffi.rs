@@ -5,10 +5,23 @@ pub struct LedChannelInfo {
     pub channel_number: u8,
 }
 
+/// Counters kept by the interrupt handlers, see `ws28xx.h`
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Ws2812Stats {
+    pub frames_sent: u32,
+    pub dma_errors: u32,
+    pub underruns: u32,
+    pub last_frame_cycles: u32,
+    pub frames_dropped: u32,
+}
+
 unsafe extern "C" {
     pub static mut led_channels: [LedChannelInfo; 8];
+    pub static ws2812_stats: Ws2812Stats;
     pub fn ws2812_init();
-    pub fn ws2812_refresh(channels: *const LedChannelInfo);
+    pub fn ws2812_refresh(channels: *const LedChannelInfo, mask: u8) -> bool;
+    pub fn ws2812_busy() -> bool;
     // Interrupt handlers
     pub fn TIM1_UP_TIM10_Handler() -> ();
     pub fn DMA2_Stream2_Handler() -> ();
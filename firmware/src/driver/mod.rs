@@ -1,13 +1,20 @@
 //! # WS2812 LED Driver Module
 //!
-//! This module provides a safe, zero-copy interface to the WS2812 LED hardware driver.
+//! This module provides a safe interface to the WS2812 LED hardware driver.
 //! It wraps the C FFI functions and provides Rust-safe abstractions.
 //!
+//! Each channel is double-buffered. Tasks render into its pixels, the back
+//! buffer; [`refresh`] waits for the frame in flight to go out, then copies
+//! the pixels that changed into the frame buffer the DMA sends from. The
+//! pixels can be rewritten as soon as `refresh` returns, as the frame being
+//! sent is the copy.
+//!
 //! ## Architecture
 //!
 //! - **FFI Layer** ([`ffi`]) - Raw C bindings to hardware driver
 //! - **Channel Layer** ([`channel`]) - Safe wrappers for LED channels
-//! - **Global Driver** - Thread-safe singleton for managing all channels
+//! - **Global Driver** - Async mutexes for the driver and each channel, so
+//!   tasks render without blocking interrupts and only the refresh is shared
 //!
 //! ## Usage
 //!
@@ -17,16 +24,16 @@
 //! use crate::driver;
 //!
 //! // Initialize the global driver (call once at startup)
-//! driver::init_global_driver();
+//! driver::init_global_driver().await;
 //! ```
 //!
 //! ### Setup channels
 //!
 //! ```ignore
-//! // Initialize a channel
-//! driver::with_driver(|driver| {
-//!     driver.init_channel(0);  // Initialize channel 0
-//! });
+//! // Initialize channel 0 with a static buffer of 60 pixels
+//! static RAM: ConstStaticCell<driver::ChannelRam<60>> =
+//!     ConstStaticCell::new(driver::ChannelRam::new());
+//! driver::init_channel(0, driver::ChannelBuffer::take(&RAM)).await?;
 //! ```
 //!
 //! ### Use channels with effects
@@ -36,25 +43,27 @@
 //!
 //! let mut effect = Pulse { /* ... */ };
 //!
-//! driver::with_driver(|driver| {
-//!     if let Some(channel) = driver.channel_mut(0) {
-//!         // Write directly to hardware buffer - zero copy!
-//!         effect.generate(channel.buffer_mut(), now);
-//!     }
-//! });
-//!
-//! // Send data to LEDs via DMA
-//! driver::with_driver(|driver| {
-//!     driver.refresh();
-//! });
+//! driver::with_channel(0, |channel| {
+//!     // Render into the back buffer; the DMA never reads it
+//!     effect.generate(channel.buffer_mut(), now);
+//! })
+//! .await;
+//!
+//! // Copy the changed channels to their frame buffers once the previous
+//! // frame is out and start sending them; errors are about earlier frames
+//! if let Err(error) = driver::refresh().await {
+//!     warn!("Frame lost: {}", error);
+//! }
 //! ```
 
 pub mod channel;
 pub mod ffi;
 
-// Re-export commonly used types and functions
-pub use channel::{LedChannel, LedDriver};
-pub use ffi::{LedChannelInfo, WS2812_NUM_CHANNELS};
+// Re-export what the tasks use
+pub use channel::{ChannelBuffer, ChannelRam, LedDriver};
 
 // Re-export global driver functions
-pub use channel::{init_global_driver, with_driver};
+pub use channel::{
+    init_channel, init_global_driver, on_timer_interrupt, refresh, while_idle, with_channel,
+    with_driver,
+};
@@ -0,0 +1,258 @@
+//! E1.31 (sACN) and Art-Net input over Ethernet, behind the `ethernet`
+//! feature.
+//!
+//! The DISC1 board has no PHY, so this expects an RMII PHY module (LAN8720
+//! or similar) on PA1 REF_CLK, PA2 MDIO, PC1 MDC, PA7 CRS_DV, PC4/PC5 RXD,
+//! PB12/PB13 TXD and PG11 TX_EN. The address comes from DHCP.
+//!
+//! Parsing, source arbitration and the universe layout are in `led-dmx`;
+//! each channel's universe and address come from the saved settings.
+//! While a desk sends data the render loop leaves the strips alone; they go
+//! back to the effects [`SOURCE_TIMEOUT_MS`] after the last packet.
+
+use core::future::pending;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use defmt::{Debug2Format, info, trace, unwrap, warn};
+use embassy_executor::Spawner;
+use embassy_futures::select::{Either3, select3};
+use embassy_net::udp::{PacketMetadata, UdpSocket};
+use embassy_net::{Ipv4Address, Stack, StackResources};
+use embassy_stm32::eth::{Ethernet, GenericPhy, PacketQueue};
+use embassy_stm32::peripherals::{ETH, PA1, PA2, PA7, PB12, PB13, PC1, PC4, PC5, PG11, RNG};
+use embassy_stm32::rng::{self, Rng};
+use embassy_stm32::{Peri, bind_interrupts, eth, peripherals};
+use embassy_time::{Duration, Instant, Timer};
+use led_dmx::{ChannelMap, Mapping, Received, Receiver, SOURCE_TIMEOUT_MS, artnet, e131, parse};
+use static_cell::StaticCell;
+
+use crate::{FRAME_RATE, driver, settings};
+
+bind_interrupts!(struct Irqs {
+    ETH => eth::InterruptHandler;
+    HASH_RNG => rng::InterruptHandler<peripherals::RNG>;
+});
+
+type Device = Ethernet<'static, ETH, GenericPhy>;
+
+/// Locally administered, fixed
+const MAC_ADDRESS: [u8; 6] = [0x02, 0x00, 0x00, 0x42, 0x29, 0x01];
+
+/// Universes tracked at once, one per channel is plenty for short strips
+const UNIVERSES: usize = 8;
+
+/// Uptime in ms when DMX data was last applied, 0 = never
+static LAST_DATA_MS: AtomicU32 = AtomicU32::new(0);
+
+/// Whether a desk is driving the strips
+pub fn is_active() -> bool {
+    let last = LAST_DATA_MS.load(Ordering::Relaxed);
+    last != 0 && (Instant::now().as_millis() as u32).wrapping_sub(last) < SOURCE_TIMEOUT_MS as u32
+}
+
+/// Bring up the MAC and spawn the network and receiver tasks
+#[allow(clippy::too_many_arguments)]
+pub async fn start(
+    spawner: Spawner,
+    eth: Peri<'static, ETH>,
+    rng: Peri<'static, RNG>,
+    ref_clk: Peri<'static, PA1>,
+    mdio: Peri<'static, PA2>,
+    mdc: Peri<'static, PC1>,
+    crs: Peri<'static, PA7>,
+    rx_d0: Peri<'static, PC4>,
+    rx_d1: Peri<'static, PC5>,
+    tx_d0: Peri<'static, PB12>,
+    tx_d1: Peri<'static, PB13>,
+    tx_en: Peri<'static, PG11>,
+) {
+    let mut rng = Rng::new(rng, Irqs);
+    let mut seed = [0; 8];
+    let _ = rng.async_fill_bytes(&mut seed).await;
+
+    static PACKETS: StaticCell<PacketQueue<4, 4>> = StaticCell::new();
+    let device = Ethernet::new(
+        PACKETS.init(PacketQueue::new()),
+        eth,
+        Irqs,
+        ref_clk,
+        mdio,
+        mdc,
+        crs,
+        rx_d0,
+        rx_d1,
+        tx_d0,
+        tx_d1,
+        tx_en,
+        GenericPhy::new_auto(),
+        MAC_ADDRESS,
+    );
+
+    static RESOURCES: StaticCell<StackResources<4>> = StaticCell::new();
+    let (stack, runner) = embassy_net::new(
+        device,
+        embassy_net::Config::dhcpv4(Default::default()),
+        RESOURCES.init(StackResources::new()),
+        u64::from_le_bytes(seed),
+    );
+
+    spawner.spawn(network(runner)).unwrap();
+    spawner.spawn(dmx(stack)).unwrap();
+}
+
+#[embassy_executor::task]
+async fn network(mut runner: embassy_net::Runner<'static, Device>) -> ! {
+    runner.run().await
+}
+
+#[embassy_executor::task]
+async fn dmx(stack: Stack<'static>) {
+    stack.wait_config_up().await;
+    if let Some(config) = stack.config_v4() {
+        info!("Ethernet up at {}", config.address);
+    }
+
+    let mapping = mapping();
+    // sACN is multicast per universe
+    for universe in mapping.universes() {
+        if stack.join_multicast_group(group(universe)).is_err() {
+            warn!("Could not join the group of universe {}", universe);
+        }
+    }
+    // Sync packets go to the group of their own universe
+    let mut sync_group = None;
+
+    let mut sacn_meta = [PacketMetadata::EMPTY; 8];
+    let mut sacn_rx = [0u8; 2048];
+    let mut sacn_tx_meta = [PacketMetadata::EMPTY; 1];
+    let mut sacn_tx = [0u8; 16];
+    let mut sacn = UdpSocket::new(
+        stack,
+        &mut sacn_meta,
+        &mut sacn_rx,
+        &mut sacn_tx_meta,
+        &mut sacn_tx,
+    );
+    unwrap!(sacn.bind(e131::PORT));
+
+    let mut artnet_meta = [PacketMetadata::EMPTY; 8];
+    let mut artnet_rx = [0u8; 2048];
+    let mut artnet_tx_meta = [PacketMetadata::EMPTY; 1];
+    let mut artnet_tx = [0u8; 16];
+    let mut artnet = UdpSocket::new(
+        stack,
+        &mut artnet_meta,
+        &mut artnet_rx,
+        &mut artnet_tx_meta,
+        &mut artnet_tx,
+    );
+    unwrap!(artnet.bind(artnet::PORT));
+
+    let mut receiver = Receiver::<8, UNIVERSES>::new(mapping);
+    let mut sacn_packet = [0u8; 640];
+    let mut artnet_packet = [0u8; 530];
+    // Unsynchronised data is shown at most at the frame rate
+    let mut last_show = Instant::from_ticks(0);
+    let mut dirty = false;
+
+    info!("DMX receiver listening");
+
+    loop {
+        let period =
+            Duration::from_micros(1_000_000 / FRAME_RATE.load(Ordering::Relaxed).max(1) as u64);
+        let flush = async {
+            if dirty {
+                Timer::at(last_show + period).await
+            } else {
+                pending().await
+            }
+        };
+        let event = select3(
+            sacn.recv_from(&mut sacn_packet),
+            artnet.recv_from(&mut artnet_packet),
+            flush,
+        )
+        .await;
+
+        let bytes = match event {
+            Either3::First(Ok((len, _))) => &sacn_packet[..len],
+            Either3::Second(Ok((len, _))) => &artnet_packet[..len],
+            Either3::First(Err(_)) | Either3::Second(Err(_)) => continue,
+            Either3::Third(()) => {
+                show(&mut last_show, &mut dirty).await;
+                continue;
+            }
+        };
+        let Ok(packet) = parse(bytes) else {
+            continue;
+        };
+
+        let now = Instant::now();
+        match receiver.receive(&packet, now.as_millis()) {
+            Received::Data {
+                universe,
+                data,
+                show: show_now,
+            } => {
+                for span in receiver.mapping.spans(universe, data) {
+                    driver::with_channel(span.channel, |channel| {
+                        channel.write_rgb(span.pixel, span.data)
+                    })
+                    .await;
+                }
+                LAST_DATA_MS.store((now.as_millis() as u32).max(1), Ordering::Relaxed);
+
+                if let Some(address) = receiver.sync_address()
+                    && sync_group != Some(address)
+                {
+                    if let Some(old) = sync_group.filter(|old| !receiver.mapping.contains(*old)) {
+                        let _ = stack.leave_multicast_group(group(old));
+                    }
+                    if !receiver.mapping.contains(address)
+                        && stack.join_multicast_group(group(address)).is_err()
+                    {
+                        warn!("Could not join the group of sync address {}", address);
+                    }
+                    sync_group = Some(address);
+                }
+
+                // Held data waits for its sync instead
+                if show_now {
+                    dirty = true;
+                    if now >= last_show + period {
+                        show(&mut last_show, &mut dirty).await;
+                    }
+                }
+            }
+            // Synchronised output follows the desk's timing
+            Received::Show => show(&mut last_show, &mut dirty).await,
+            Received::Ignored(reason) => trace!("DMX packet ignored: {}", Debug2Format(&reason)),
+        }
+    }
+}
+
+fn group(universe: u16) -> Ipv4Address {
+    let [a, b, c, d] = e131::multicast_address(universe);
+    Ipv4Address::new(a, b, c, d)
+}
+
+/// Universe and start address of each channel from the saved layout;
+/// unused channels don't listen. Art-Net port-addresses use the same
+/// numbers.
+fn mapping() -> Mapping<8> {
+    Mapping::new(settings::channels().map(|channel| {
+        (channel.pixels > 0).then_some(ChannelMap {
+            universe: channel.universe,
+            address: channel.address,
+            pixels: channel.pixels,
+        })
+    }))
+}
+
+async fn show(last_show: &mut Instant, dirty: &mut bool) {
+    if let Err(error) = driver::refresh().await {
+        warn!("Frame lost: {}", error);
+    }
+    *last_show = Instant::now();
+    *dirty = false;
+}
@@ -1,25 +1,35 @@
 #![no_std]
 #![no_main]
 
-use defmt::*;
+use defmt::{info, warn};
 use embassy_executor::Spawner;
 use embassy_time::{Instant, Timer};
 use {defmt_rtt as _, panic_probe as _};
 
+mod audio;
+mod button;
+mod console;
+mod dmx512;
 mod driver;
+#[cfg(feature = "ethernet")]
+mod ethernet;
 mod init;
+mod scheduler;
+mod settings;
+mod usb_cdc;
 
 use embassy_stm32::Peri;
 use embassy_stm32::bind_interrupts;
 use embassy_stm32::gpio::{AnyPin, Level, Output, Speed};
 use embassy_stm32::interrupt::typelevel::Handler;
 use embassy_stm32::interrupt::typelevel::{DMA2_STREAM2, TIM1_UP_TIM10};
+use embassy_stm32::{flash, peripherals, usart, usb};
 
 pub struct Tim1UpTim10Handler;
 impl Handler<TIM1_UP_TIM10> for Tim1UpTim10Handler {
     unsafe fn on_interrupt() {
         unsafe {
-            driver::ffi::TIM1_UP_TIM10_Handler();
+            driver::on_timer_interrupt();
         }
     }
 }
@@ -42,13 +52,39 @@ pub unsafe extern "C" fn DMA2_STREAM2_OVERRIDE() {
 
 bind_interrupts!(struct Irqs {
     TIM1_UP_TIM10 => Tim1UpTim10Handler;
+    USART1 => usart::BufferedInterruptHandler<peripherals::USART1>;
+    OTG_HS => usb::InterruptHandler<peripherals::USB_OTG_HS>;
+    UART5 => dmx512::Uart5Handler;
+    FLASH => flash::InterruptHandler;
 });
 
 use init::init_clock;
 
-use led_effects::Pixel;
+use core::sync::atomic::{AtomicU16, Ordering};
+use led_control::settings::CHANNELS;
+use led_effects::Trigger;
+use static_cell::ConstStaticCell;
 const NUM_LEDS: usize = 60;
-static mut CHANNEL_0_RAM: [Pixel; NUM_LEDS] = [Pixel::BLACK; NUM_LEDS];
+/// Effects the button and console cycle through, see `led_effects`
+const EFFECT_NAMES: [&str; 4] = ["chase", "breathe", "fountain", "fireworks"];
+const EFFECT_COUNT: usize = EFFECT_NAMES.len();
+
+/// Render loop frame rate, set from the console; see `scheduler` for the
+/// rate it actually runs at
+static FRAME_RATE: AtomicU16 = AtomicU16::new(40);
+
+/// Sets off a shell of the `fireworks` effect; fired from the console
+static FIREWORKS: Trigger = Trigger::new();
+
+/// Pixel memory of each channel, enough for the longest strip `channel`
+/// accepts
+static CHANNEL_RAM: [ConstStaticCell<driver::ChannelRam<NUM_LEDS>>; CHANNELS] =
+    [const { ConstStaticCell::new(driver::ChannelRam::new()) }; CHANNELS];
+
+const _: () = core::assert!(
+    driver::LedDriver::max_fps_for(NUM_LEDS) >= settings::DEFAULTS.fps,
+    "NUM_LEDS can't be sent at the default frame rate"
+);
 
 #[embassy_executor::main]
 async fn main(_spawner: Spawner) {
@@ -58,66 +94,122 @@ async fn main(_spawner: Spawner) {
     let peripherals = embassy_stm32::init(device_config);
 
     // Initialize the global LED driver
-    driver::init_global_driver();
+    driver::init_global_driver().await;
 
-    // Setup channel 0
-    driver::with_driver(|driver| unsafe {
-        use core::ptr::addr_of_mut;
+    let (store, saved) = settings::load(peripherals.FLASH).await;
 
-        let buf_ptr = addr_of_mut!(CHANNEL_0_RAM);
-        driver.init_channel(0, &mut *buf_ptr);
-        // driver.init_channel(0, &mut CHANNEL_0_RAM);
-    });
+    // Setup the channels the saved layout uses, as long as it says
+    for (number, (config, ram)) in saved.channels.iter().zip(&CHANNEL_RAM).enumerate() {
+        let pixels = (config.pixels as usize).min(NUM_LEDS);
+        if pixels == 0 {
+            continue;
+        }
+        let buffer = driver::ChannelBuffer::take(ram).with_len(pixels);
+        if let Err(error) = driver::init_channel(number as u8, buffer).await {
+            warn!("Channel {} not set up: {}", number, error);
+        }
+    }
+    let fps = FRAME_RATE.load(Ordering::Relaxed);
+    if let Err(error) = driver::with_driver(|driver| driver.check_fps(fps)).await {
+        warn!("{} fps is too fast, {}", fps, error);
+    }
 
     // _spawner.spawn(blink(peripherals.PG13.into())).unwrap();
     _spawner.spawn(blink(peripherals.PG14.into())).unwrap();
     _spawner
         .spawn(led_effects(peripherals.PG13.into()))
         .unwrap();
+    _spawner
+        .spawn(button::button(peripherals.PA0, peripherals.EXTI0))
+        .unwrap();
+    _spawner
+        .spawn(console::console(
+            peripherals.USART1,
+            peripherals.PA9,
+            peripherals.PA10,
+        ))
+        .unwrap();
+    _spawner
+        .spawn(usb_cdc::usb_cdc(
+            peripherals.USB_OTG_HS,
+            peripherals.PB15,
+            peripherals.PB14,
+        ))
+        .unwrap();
+    _spawner
+        .spawn(dmx512::dmx512(peripherals.UART5, peripherals.PD2))
+        .unwrap();
+    _spawner.spawn(settings::settings(store, saved)).unwrap();
+    #[cfg(feature = "ethernet")]
+    ethernet::start(
+        _spawner,
+        peripherals.ETH,
+        peripherals.RNG,
+        peripherals.PA1,
+        peripherals.PA2,
+        peripherals.PC1,
+        peripherals.PA7,
+        peripherals.PC4,
+        peripherals.PC5,
+        peripherals.PB12,
+        peripherals.PB13,
+        peripherals.PG11,
+    )
+    .await;
+    _spawner
+        .spawn(audio::microphone(
+            peripherals.ADC1,
+            peripherals.DMA2_CH0,
+            peripherals.PA5,
+        ))
+        .unwrap();
 
+    // Driver health, also on the console's `stats`
     loop {
-        Timer::after_secs(1).await;
+        Timer::after_secs(10).await;
+        let stats = driver::with_driver(|driver| driver.stats()).await;
+        info!("Driver: {}", stats);
     }
 }
 
 #[embassy_executor::task]
 async fn led_effects(debug_pin: Peri<'static, AnyPin>) {
-    // Get start time
     let mut pg13_debug: Output<'_> = Output::new(debug_pin, Level::High, Speed::Low);
-    let start_time = Instant::now().as_millis();
     let mut effect_start_time = Instant::now(); // Track the start of the current loop
-    let effect_duration_ms: u32 = 5000; // Must match your EffectBuilder duration
 
-    // Create the effect ONCE before the loop
+    // Create the effects ONCE before the loop
     use led_effects::Chase;
     use led_effects::EffectBuilder;
     use led_effects::Generator;
+    use led_effects::rng::Rng;
     use led_effects::{Blur, Trail};
-    use led_effects::{DynamicParam, Fade, RotatingHue, StaticParam, VelocityIntegral};
+    use led_effects::{Boundary, Emitter, ParticlePool, Particles, Sine, SolidColor};
+    use led_effects::{DynamicParam, Fade, RotatingHue, StaticParam, Timestamp, VelocityIntegral};
+    use led_effects::{Retrigger, Triggered};
 
-    let mut effect = EffectBuilder::new(Chase {
-        start_time: 0,
+    let mut chase = EffectBuilder::new(Chase {
+        start_time: Timestamp::ZERO,
         duration: 5000,
 
         position: DynamicParam::<_, u8>::new(
-            VelocityIntegral {
-                start_time: 0,
-                velocity_envelope: Fade {
-                    start_time: 0,
+            VelocityIntegral::new(
+                Timestamp::ZERO,
+                Fade {
+                    start_time: Timestamp::ZERO,
                     duration: 5000,
                     inverted: true,
                 },
-                initial_position: 0,
-                dt_ms: 16,
-                velocity_scale: 20, // Adjust for desired speed
-            },
+                0,  // initial position
+                16, // dt_ms
+                20, // velocity scale, adjust for desired speed
+            ),
             0.0,
             NUM_LEDS as f32,
         ),
 
         width: DynamicParam::<_, u8>::new(
             Fade {
-                start_time: 0,
+                start_time: Timestamp::ZERO,
                 duration: 5000,
                 inverted: false,
             },
@@ -127,7 +219,7 @@ async fn led_effects(debug_pin: Peri<'static, AnyPin>) {
 
         intensity: DynamicParam::<_, u8>::new(
             Fade {
-                start_time: 0,
+                start_time: Timestamp::ZERO,
                 duration: 5000,
                 inverted: true,
             },
@@ -136,16 +228,16 @@ async fn led_effects(debug_pin: Peri<'static, AnyPin>) {
         ),
 
         hue: RotatingHue {
-            start_time: 0,
+            start_time: Timestamp::ZERO,
             degrees_per_ms: 0.05,
         },
 
-        saturation: StaticParam(255),
+        saturation: &console::SATURATION,
     })
     .with_modifier(Trail {
         decay_rate: DynamicParam::<_, u8>::new(
             Fade {
-                start_time: 0,
+                start_time: Timestamp::ZERO,
                 duration: 5000,
                 inverted: false,
             },
@@ -158,34 +250,160 @@ async fn led_effects(debug_pin: Peri<'static, AnyPin>) {
     })
     .build();
 
+    let mut breathe = SolidColor {
+        start_time: Timestamp::ZERO,
+        duration: u32::MAX,
+        intensity: DynamicParam::<_, u8>::new(
+            Sine {
+                start_time: Timestamp::ZERO,
+                period: 4000,
+            },
+            8.0,
+            255.0,
+        ),
+        hue: &console::HUE,
+        saturation: &console::SATURATION,
+    };
+
+    let mut fountain = Particles::<32, _, _> {
+        start_time: Timestamp::ZERO,
+        duration: u32::MAX,
+        emitter: Emitter::Point {
+            position: 0.0,
+            rate: 20,
+        },
+        min_velocity: NUM_LEDS as f32 * 0.8,
+        max_velocity: NUM_LEDS as f32 * 1.2,
+        gravity: -(NUM_LEDS as f32),
+        drag: 0.1,
+        lifetime: 2500,
+        lifetime_jitter: 1000,
+        boundary: Boundary::Bounce { restitution: 0.4 },
+        hue: RotatingHue {
+            start_time: Timestamp::ZERO,
+            degrees_per_ms: 0.02,
+        },
+        hue_spread: 24,
+        hue_shift: 0,
+        saturation: 255,
+        intensity: Fade {
+            start_time: Timestamp::ZERO,
+            duration: 2500,
+            inverted: true,
+        },
+        rng: Rng::new(0x0F0F_0F0F),
+        pool: ParticlePool::new(),
+    };
+
+    // Up to three shells at once, each bursting from its own spot
+    let shell = |position: f32, seed: u32| Particles::<24, _, _> {
+        start_time: Timestamp::ZERO,
+        duration: 0,
+        emitter: Emitter::Burst {
+            position,
+            count: 20,
+        },
+        min_velocity: -(NUM_LEDS as f32) * 0.4,
+        max_velocity: NUM_LEDS as f32 * 0.4,
+        gravity: 0.0,
+        drag: 2.0,
+        lifetime: 900,
+        lifetime_jitter: 400,
+        boundary: Boundary::Kill,
+        hue: RotatingHue {
+            start_time: Timestamp::ZERO,
+            degrees_per_ms: 0.1,
+        },
+        hue_spread: 40,
+        hue_shift: -30,
+        saturation: 220,
+        intensity: Fade {
+            start_time: Timestamp::ZERO,
+            duration: 900,
+            inverted: true,
+        },
+        rng: Rng::new(seed),
+        pool: ParticlePool::new(),
+    };
+    let mut fireworks = Triggered::new(
+        &FIREWORKS,
+        Retrigger::Stack,
+        [
+            shell(NUM_LEDS as f32 * 0.5, 0x1234_5678),
+            shell(NUM_LEDS as f32 * 0.25, 0x2345_6789),
+            shell(NUM_LEDS as f32 * 0.75, 0x3456_789A),
+        ],
+    );
+
+    let mut effects: [&mut dyn Generator; EFFECT_COUNT] =
+        [&mut chase, &mut breathe, &mut fountain, &mut fireworks];
+    let mut current = button::SELECTOR.effect();
+    let mut scheduler = scheduler::FrameScheduler::new();
+
     info!("LED effects task started");
 
     loop {
+        // A USB host or lighting desk owns the strips while it sends frames
+        if external_input() {
+            Timer::after_millis(20).await;
+            continue;
+        }
+        scheduler.next().await;
+
         let now_instant = Instant::now();
-        let mut elapsed = now_instant.duration_since(effect_start_time).as_millis() as u32;
+        let mut elapsed = now_instant.duration_since(effect_start_time).as_millis();
+
+        // A new selection starts from its beginning
+        let selected = button::SELECTOR.effect();
+        if selected != current {
+            current = selected;
+            effect_start_time = now_instant;
+            elapsed = 0;
+            info!("Effect {} selected", current);
+        }
 
-        if elapsed >= effect_duration_ms {
+        // One-shot effects loop once they have finished
+        if !effects[current].is_alive(Timestamp::from_millis(elapsed)) {
             effect_start_time = now_instant;
             elapsed = 0;
             info!("Effect restarting...");
         }
 
-        driver::with_driver(|driver| {
-            if let Some(channel) = driver.channel_mut(0) {
-                channel.clear();
-                // Pass the 'elapsed' which now resets to 0 every 5 seconds
+        let render_start = Instant::now();
+        // Only this channel is held; interrupts run on while it renders
+        driver::with_channel(0, |channel| {
+            channel.clear();
+            if button::SELECTOR.is_on() {
                 pg13_debug.set_high();
-                effect.generate(channel.buffer_mut(), elapsed);
+                effects[current].generate(channel.buffer_mut(), Timestamp::from_millis(elapsed));
                 pg13_debug.set_low();
+
+                let brightness = button::SELECTOR.brightness();
+                if brightness < u8::MAX {
+                    for pixel in channel.buffer_mut() {
+                        *pixel = pixel.scale(brightness);
+                    }
+                }
             }
-        });
+        })
+        .await;
+        let render_us = render_start.elapsed().as_micros() as u32;
 
-        driver::with_driver(|driver| {
-            driver.refresh();
-        });
+        if let Err(error) = driver::refresh().await {
+            warn!("Frame lost: {}", error);
+        }
+        let wire_us = driver::with_driver(|driver| driver.stats().last_frame_us).await;
+        scheduler.frame_done(render_us, wire_us);
+    }
+}
 
-        Timer::after_millis(25).await;
+/// Whether something other than the effects drives the strips
+fn external_input() -> bool {
+    #[cfg(feature = "ethernet")]
+    if ethernet::is_active() {
+        return true;
     }
+    usb_cdc::STREAMING.load(Ordering::Relaxed) || dmx512::is_driving()
 }
 
 #[embassy_executor::task(pool_size = 2)]
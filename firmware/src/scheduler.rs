@@ -0,0 +1,93 @@
+//! Fixed-rate frame ticks for the effects loop.
+//!
+//! [`FrameScheduler`] runs an `embassy_time::Ticker` at `FRAME_RATE` and
+//! accounts each frame with `led_control::FrameBudget`. A frame that runs
+//! into the next tick is an overrun; the ticks it ran into are dropped
+//! rather than rendered back to back. When the longest channel takes longer
+//! on the wire than a period, the rate drops to what the strips can take
+//! and comes back once they can.
+
+use core::cell::Cell;
+use core::sync::atomic::Ordering;
+
+use critical_section::Mutex;
+use defmt::{info, warn};
+use embassy_time::{Duration, Instant, Ticker};
+use led_control::{FrameBudget, FrameStats};
+
+use crate::FRAME_RATE;
+
+/// Latest accounting, reported by `stats`
+static STATS: Mutex<Cell<FrameStats>> = Mutex::new(Cell::new(FrameStats {
+    frames: 0,
+    overruns: 0,
+    dropped: 0,
+    last_render_us: 0,
+    max_render_us: 0,
+    fps: 0,
+}));
+
+pub fn stats() -> FrameStats {
+    critical_section::with(|cs| STATS.borrow(cs).get())
+}
+
+pub struct FrameScheduler {
+    ticker: Ticker,
+    budget: FrameBudget,
+    /// When the current frame's tick fired
+    tick: Instant,
+}
+
+impl FrameScheduler {
+    pub fn new() -> Self {
+        let budget = FrameBudget::new(FRAME_RATE.load(Ordering::Relaxed));
+        Self {
+            ticker: Ticker::every(period(&budget)),
+            budget,
+            tick: Instant::now(),
+        }
+    }
+
+    /// Wait for the next frame; a new `FRAME_RATE` applies from here
+    pub async fn next(&mut self) {
+        let target = FRAME_RATE.load(Ordering::Relaxed).max(1);
+        if target != self.budget.target_fps() {
+            self.budget.set_target(target);
+            self.ticker = Ticker::every(period(&self.budget));
+        }
+        self.ticker.next().await;
+        self.tick = Instant::now();
+    }
+
+    /// Account the frame started by the last [`next`](Self::next), once it
+    /// is refreshed. `wire_us` is how long the driver took to send it.
+    pub fn frame_done(&mut self, render_us: u32, wire_us: u32) {
+        let fps = self.budget.fps();
+        let busy_us = self.tick.elapsed().as_micros() as u32;
+        if self.budget.frame_done(render_us, busy_us, wire_us) > 0 {
+            // Start counting periods from now instead of catching up
+            self.ticker.reset();
+        }
+
+        if self.budget.fps() != fps {
+            if self.budget.fps() < self.budget.target_fps() {
+                warn!(
+                    "Frames take {} us to send, running at {} fps instead of {}",
+                    wire_us,
+                    self.budget.fps(),
+                    self.budget.target_fps()
+                );
+            } else {
+                info!("Back to {} fps", self.budget.fps());
+            }
+            self.ticker = Ticker::every(period(&self.budget));
+        }
+
+        let stats = self.budget.stats();
+        critical_section::with(|cs| STATS.borrow(cs).set(stats));
+    }
+}
+
+fn period(budget: &FrameBudget) -> Duration {
+    Duration::from_micros(budget.period_us() as u64)
+}
@@ -0,0 +1,168 @@
+//! Settings kept in internal flash across resets: effect, brightness, power,
+//! frame rate, colour and the channel layout.
+//!
+//! `led_control::Store` keeps a record log in the four 16 KB sectors at the
+//! start of bank 2 (sectors 12-15, 0x0810_0000), well clear of the program.
+//! The settings load once at boot; the [`settings`] task writes them back
+//! once a change has held for [`SETTLE_MS`], so stepping through the
+//! brightness levels writes one record rather than four. Roughly every 240
+//! saves one takes a sector erase; [`SettingsFlash`] waits for the LED frame
+//! on the wire to finish and holds the next one back until it is done.
+
+use core::cell::Cell;
+use core::sync::atomic::Ordering;
+
+use critical_section::Mutex;
+use defmt::{Display2Format, info, warn};
+use embassy_stm32::Peri;
+use embassy_stm32::flash::{Async, Bank2Region1, Error, Flash};
+use embassy_stm32::peripherals::FLASH;
+use embassy_time::{Instant, Timer};
+use embedded_storage_async::nor_flash::{ErrorType, NorFlash, ReadNorFlash};
+use led_control::settings::CHANNELS;
+use led_control::{Action, ChannelConfig, Settings, Store};
+
+use crate::button::SELECTOR;
+use crate::console::{HUE, SATURATION};
+use crate::{FRAME_RATE, Irqs, NUM_LEDS, driver};
+
+pub type SettingsStore = Store<SettingsFlash, 128>;
+
+type Region = Bank2Region1<'static, Async>;
+
+/// The store's sectors. Erases wait until no LED frame is going out, so the
+/// flash never competes with the WS2812 DMA and its interrupts.
+pub struct SettingsFlash(Region);
+
+impl ErrorType for SettingsFlash {
+    type Error = Error;
+}
+
+impl ReadNorFlash for SettingsFlash {
+    const READ_SIZE: usize = <Region as ReadNorFlash>::READ_SIZE;
+
+    async fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Error> {
+        ReadNorFlash::read(&mut self.0, offset, bytes).await
+    }
+
+    fn capacity(&self) -> usize {
+        ReadNorFlash::capacity(&self.0)
+    }
+}
+
+impl NorFlash for SettingsFlash {
+    const WRITE_SIZE: usize = <Region as NorFlash>::WRITE_SIZE;
+    const ERASE_SIZE: usize = <Region as NorFlash>::ERASE_SIZE;
+
+    async fn erase(&mut self, from: u32, to: u32) -> Result<(), Error> {
+        driver::while_idle(async || NorFlash::erase(&mut self.0, from, to).await).await
+    }
+
+    async fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Error> {
+        NorFlash::write(&mut self.0, offset, bytes).await
+    }
+}
+
+/// Used until something is saved: channel 0 is the strip on PB0, on
+/// universe 1
+pub const DEFAULTS: Settings = {
+    let mut settings = Settings::DEFAULT;
+    settings.channels[0] = ChannelConfig {
+        pixels: NUM_LEDS as u16,
+        universe: 1,
+        address: 1,
+    };
+    settings
+};
+
+/// A change must hold this long before it is written
+const SETTLE_MS: u64 = 2000;
+
+/// Channel layout; changes apply from the next reset
+static LAYOUT: Mutex<Cell<[ChannelConfig; CHANNELS]>> = Mutex::new(Cell::new(DEFAULTS.channels));
+
+pub fn channels() -> [ChannelConfig; CHANNELS] {
+    critical_section::with(|cs| LAYOUT.borrow(cs).get())
+}
+
+pub fn set_channel(channel: usize, config: ChannelConfig) {
+    critical_section::with(|cs| {
+        let layout = LAYOUT.borrow(cs);
+        let mut channels = layout.get();
+        channels[channel] = config;
+        layout.set(channels);
+    });
+}
+
+/// Read the saved settings and apply them
+pub async fn load(flash: Peri<'static, FLASH>) -> (SettingsStore, Settings) {
+    let regions = Flash::new(flash, Irqs).into_regions();
+    let mut store = Store::new(SettingsFlash(regions.bank2_region1));
+    let settings = Settings::load(&mut store, DEFAULTS).await;
+    if settings == DEFAULTS {
+        info!("Using default settings");
+    } else {
+        info!(
+            "Settings loaded: effect {} brightness {} fps {}",
+            settings.effect, settings.brightness, settings.fps
+        );
+    }
+
+    SELECTOR.select(settings.effect as usize);
+    SELECTOR.apply(Action::Brightness(settings.brightness));
+    SELECTOR.apply(if settings.power {
+        Action::PowerOn
+    } else {
+        Action::PowerOff
+    });
+    FRAME_RATE.store(settings.fps, Ordering::Relaxed);
+    HUE.set(settings.hue);
+    SATURATION.set(settings.saturation);
+    critical_section::with(|cs| LAYOUT.borrow(cs).set(settings.channels));
+
+    (store, settings)
+}
+
+/// What would be saved now
+fn current() -> Settings {
+    Settings {
+        effect: SELECTOR.effect() as u8,
+        brightness: SELECTOR.brightness(),
+        power: SELECTOR.is_on(),
+        fps: FRAME_RATE.load(Ordering::Relaxed),
+        hue: HUE.get(),
+        saturation: SATURATION.get(),
+        channels: channels(),
+    }
+}
+
+#[embassy_executor::task]
+pub async fn settings(mut store: SettingsStore, mut saved: Settings) {
+    let mut seen = saved;
+    let mut changed_at = Instant::now();
+
+    loop {
+        Timer::after_millis(250).await;
+        let live = current();
+        if live != seen {
+            seen = live;
+            changed_at = Instant::now();
+            continue;
+        }
+        if live == saved || changed_at.elapsed().as_millis() < SETTLE_MS {
+            continue;
+        }
+
+        match live.save(&mut store).await {
+            Ok(()) => {
+                saved = live;
+                info!("Settings saved");
+            }
+            Err(error) => {
+                warn!("Settings not saved: {}", Display2Format(&error));
+                // Try again after another settle time
+                changed_at = Instant::now();
+            }
+        }
+    }
+}
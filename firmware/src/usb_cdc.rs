@@ -0,0 +1,196 @@
+//! USB CDC-ACM control port on OTG HS in full-speed mode (the CN6 micro-USB
+//! connector, PB14 DM / PB15 DP).
+//!
+//! The port starts in text mode and takes the same commands as the serial
+//! console, without echo. `stream` switches it to binary streaming: the host
+//! pushes pixel data for any of the 8 channels with the `led_protocol`
+//! framing and shows it with a `Show` packet; `GetStats` reads the driver
+//! counters. `Stop` or a disconnect goes back to text mode and hands the
+//! strips back to the effects.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use defmt::{Display2Format, info, unwrap, warn};
+use embassy_futures::join::join;
+use embassy_stm32::Peri;
+use embassy_stm32::peripherals::{PB14, PB15, USB_OTG_HS};
+use embassy_stm32::usb::{self, Driver};
+use embassy_usb::Builder;
+use embassy_usb::class::cdc_acm::{CdcAcmClass, State};
+use embassy_usb::driver::EndpointError;
+use heapless::String;
+use led_control::{Command, Input, LineBuffer, parse};
+use led_protocol::{Decoder, Frame, MAX_REPLY_LEN, Packet, Stats, encode};
+
+use crate::{Irqs, console, driver};
+
+/// Set while the host drives the strips; the render loop leaves them alone
+pub static STREAMING: AtomicBool = AtomicBool::new(false);
+
+const MAX_PACKET_SIZE: u16 = 64;
+/// Largest streamed payload, 340 pixels
+const MAX_PAYLOAD: usize = 1024;
+
+type Class<'d> = CdcAcmClass<'d, Driver<'d, USB_OTG_HS>>;
+
+#[embassy_executor::task]
+pub async fn usb_cdc(
+    otg: Peri<'static, USB_OTG_HS>,
+    dp: Peri<'static, PB15>,
+    dm: Peri<'static, PB14>,
+) {
+    let mut ep_out_buffer = [0u8; 256];
+    let mut usb_config = usb::Config::default();
+    // Bus powered, so VBUS sensing on PB13 adds nothing
+    usb_config.vbus_detection = false;
+    let usb_driver = Driver::new_fs(otg, Irqs, dp, dm, &mut ep_out_buffer, usb_config);
+
+    // Placeholder VID:PID from the embassy examples, not allocated to this
+    // device; swap in a real pair before shipping hardware
+    let mut config = embassy_usb::Config::new(0xc0de, 0xcafe);
+    config.manufacturer = Some("stm32f429-rust-embassy");
+    config.product = Some("WS2812 controller");
+    config.serial_number = Some("00000001");
+    config.max_power = 100;
+    config.max_packet_size_0 = 64;
+
+    let mut config_descriptor = [0; 256];
+    let mut bos_descriptor = [0; 256];
+    let mut control_buf = [0; 64];
+    let mut state = State::new();
+    let mut builder = Builder::new(
+        usb_driver,
+        config,
+        &mut config_descriptor,
+        &mut bos_descriptor,
+        &mut [],
+        &mut control_buf,
+    );
+    let mut class = CdcAcmClass::new(&mut builder, &mut state, MAX_PACKET_SIZE);
+    let mut device = builder.build();
+
+    info!("USB task started");
+    let sessions = async {
+        loop {
+            class.wait_connection().await;
+            info!("USB host connected");
+            let _ = serve(&mut class).await;
+            STREAMING.store(false, Ordering::Relaxed);
+            info!("USB host disconnected");
+        }
+    };
+    join(device.run(), sessions).await;
+}
+
+/// Handle one connection until the host goes away
+async fn serve(class: &mut Class<'_>) -> Result<(), EndpointError> {
+    let mut line = LineBuffer::<64>::new();
+    let mut decoder = Decoder::<MAX_PAYLOAD>::new();
+    let mut reply: String<512> = String::new();
+    let mut bytes = [0u8; MAX_PACKET_SIZE as usize];
+
+    write(class, b"led console, type 'help'\r\n> ").await?;
+
+    loop {
+        let count = class.read_packet(&mut bytes).await?;
+
+        // Check the mode per byte: a packet may hold the end of `stream` and
+        // the first frame, or `Stop` and the next command
+        for &byte in &bytes[..count] {
+            if STREAMING.load(Ordering::Relaxed) {
+                let answer = match decoder.push(byte) {
+                    None => continue,
+                    Some(Ok(Packet { seq, frame })) => match stream(frame).await {
+                        Some(frame) => Packet { seq, frame },
+                        None => continue,
+                    },
+                    Some(Err(error)) => {
+                        warn!("USB stream: {}", Display2Format(&error));
+                        Packet {
+                            seq: 0,
+                            frame: Frame::Nack,
+                        }
+                    }
+                };
+                let mut out = [0u8; MAX_REPLY_LEN];
+                let len = unwrap!(encode(&answer, &mut out).ok());
+                write(class, &out[..len]).await?;
+                continue;
+            }
+
+            reply.clear();
+            match line.push(byte) {
+                Input::Pending => continue,
+                Input::Line(text) if parse(text) == Ok(Command::Stream) => {
+                    decoder.reset();
+                    STREAMING.store(true, Ordering::Relaxed);
+                    info!("USB streaming started");
+                    // The last text until `Stop`
+                    write(class, b"streaming\r\n").await?;
+                    continue;
+                }
+                Input::Line(text) => console::run(text, &mut reply).await,
+                Input::Overflow => {
+                    let _ = reply.push_str("error: line too long\r\n");
+                }
+                Input::Invalid => {
+                    let _ = reply.push_str("error: not UTF-8\r\n");
+                }
+            }
+            let _ = reply.push_str("> ");
+            write(class, reply.as_bytes()).await?;
+        }
+    }
+}
+
+/// Apply one streamed packet. Returns the reply, if it gets one.
+async fn stream(frame: Frame<'_>) -> Option<Frame<'static>> {
+    match frame {
+        Frame::Pixels {
+            channel,
+            offset,
+            data,
+        } => {
+            let written =
+                driver::with_channel(channel, |channel| channel.write_rgb(offset as usize, data))
+                    .await;
+            // Unset channels and data past the end of the strip
+            (written != Some(true)).then_some(Frame::Nack)
+        }
+        Frame::Show => {
+            // Ack the Show; a lost earlier frame is only logged
+            if let Err(error) = driver::refresh().await {
+                warn!("Frame lost: {}", error);
+            }
+            Some(Frame::Ack)
+        }
+        Frame::Stop => {
+            STREAMING.store(false, Ordering::Relaxed);
+            info!("USB streaming stopped");
+            Some(Frame::Ack)
+        }
+        Frame::GetStats => {
+            let stats = driver::with_driver(|driver| driver.stats()).await;
+            Some(Frame::Stats(Stats {
+                frames_sent: stats.frames_sent,
+                dma_errors: stats.dma_errors,
+                underruns: stats.underruns,
+                frame_us: stats.last_frame_us,
+                fps_centi: (stats.fps * 100.0) as u32,
+            }))
+        }
+        // Replies are for the host
+        Frame::Ack | Frame::Nack | Frame::Stats(_) => Some(Frame::Nack),
+    }
+}
+
+/// Write `bytes` in packets, ending with a short one so the host sees the end
+async fn write(class: &mut Class<'_>, bytes: &[u8]) -> Result<(), EndpointError> {
+    for chunk in bytes.chunks(MAX_PACKET_SIZE as usize) {
+        class.write_packet(chunk).await?;
+    }
+    if bytes.len().is_multiple_of(MAX_PACKET_SIZE as usize) {
+        class.write_packet(&[]).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,197 @@
+//! Debounced push button with short, double, long and hold gestures.
+//!
+//! Feed [`Button::update`] the raw pin level on every edge interrupt and
+//! again once [`Button::next_deadline`] has passed; timed gestures (hold,
+//! the end of the double-press window, debounce settling) are reported from
+//! those deadline calls.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gesture {
+    /// Pressed and released once
+    Short,
+    /// Two short presses within `double_gap_ms`
+    Double,
+    /// Released after at least `long_press_ms`
+    Long,
+    /// Still held after `hold_ms`; reported while the button is down
+    Hold,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ButtonConfig {
+    /// The level must be stable this long to count as an edge
+    pub debounce_ms: u32,
+    pub long_press_ms: u32,
+    pub hold_ms: u32,
+    /// Longest gap between two presses of a double press
+    pub double_gap_ms: u32,
+}
+
+impl ButtonConfig {
+    pub const DEFAULT: Self = Self {
+        debounce_ms: 20,
+        long_press_ms: 600,
+        hold_ms: 2000,
+        double_gap_ms: 300,
+    };
+}
+
+impl Default for ButtonConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+pub struct Button {
+    pub config: ButtonConfig,
+    // Debouncer: last raw level and when it last changed
+    raw: bool,
+    raw_since: u64,
+    edge_at: Option<u64>, // First bounce away from the stable level
+    stable: bool,
+    // Gesture tracking, in debounced edge times
+    pressed_at: Option<u64>,
+    released_at: Option<u64>, // First press of a possible double
+    second_press: bool,
+    held: bool, // Hold already reported for this press
+}
+
+impl Button {
+    pub const fn new(config: ButtonConfig) -> Self {
+        Self {
+            config,
+            raw: false,
+            raw_since: 0,
+            edge_at: None,
+            stable: false,
+            pressed_at: None,
+            released_at: None,
+            second_press: false,
+            held: false,
+        }
+    }
+
+    /// Debounced level
+    #[inline(always)]
+    pub fn is_pressed(&self) -> bool {
+        self.stable
+    }
+
+    /// Feed the raw level (`true` = pressed) at `now` ms
+    pub fn update(&mut self, pressed: bool, now: u64) -> Option<Gesture> {
+        // Settle what the pin was doing up to now before taking the new level
+        let settled = self.settle(now);
+
+        if pressed != self.raw {
+            self.edge_at.get_or_insert(now);
+            self.raw = pressed;
+            self.raw_since = now;
+        }
+
+        settled
+            .or_else(|| self.settle(now))
+            .or_else(|| self.timeouts(now))
+    }
+
+    /// Earliest time `update` has something to report without a new edge
+    pub fn next_deadline(&self) -> Option<u64> {
+        let config = &self.config;
+        let debounce =
+            (self.raw != self.stable).then(|| self.raw_since + config.debounce_ms as u64);
+        let hold = match self.pressed_at {
+            Some(at) if self.stable && !self.held => Some(at + config.hold_ms as u64),
+            _ => None,
+        };
+        let double = match self.released_at {
+            // A press still settling may turn this into a double
+            Some(at) if !self.raw && !self.stable => Some(at + config.double_gap_ms as u64 + 1),
+            _ => None,
+        };
+
+        [debounce, hold, double].into_iter().flatten().min()
+    }
+
+    /// Commit the raw level once it has been stable for `debounce_ms`
+    fn settle(&mut self, now: u64) -> Option<Gesture> {
+        if now.saturating_sub(self.raw_since) < self.config.debounce_ms as u64 {
+            return None;
+        }
+
+        // Durations are measured from the first bounce, so the debounce
+        // delay doesn't stretch them
+        let edge = self.edge_at.take().unwrap_or(self.raw_since);
+        if self.raw == self.stable {
+            return None;
+        }
+        self.stable = self.raw;
+
+        if self.stable {
+            self.press(edge)
+        } else {
+            self.release(edge)
+        }
+    }
+
+    fn press(&mut self, at: u64) -> Option<Gesture> {
+        self.pressed_at = Some(at);
+        self.held = false;
+        self.second_press = false;
+
+        let released = self.released_at.take()?;
+        if at.saturating_sub(released) <= self.config.double_gap_ms as u64 {
+            self.second_press = true;
+            None
+        } else {
+            // The window closed without a deadline call, report the first press now
+            Some(Gesture::Short)
+        }
+    }
+
+    fn release(&mut self, at: u64) -> Option<Gesture> {
+        let pressed_at = self.pressed_at.take()?;
+        if self.held {
+            return None;
+        }
+
+        if at.saturating_sub(pressed_at) >= self.config.long_press_ms as u64 {
+            self.second_press = false;
+            Some(Gesture::Long)
+        } else if self.second_press {
+            self.second_press = false;
+            Some(Gesture::Double)
+        } else {
+            // Wait to see whether a second press follows
+            self.released_at = Some(at);
+            None
+        }
+    }
+
+    fn timeouts(&mut self, now: u64) -> Option<Gesture> {
+        if let Some(at) = self.pressed_at
+            && self.stable
+            && !self.held
+            && now.saturating_sub(at) >= self.config.hold_ms as u64
+        {
+            self.held = true;
+            self.second_press = false;
+            return Some(Gesture::Hold);
+        }
+
+        if let Some(at) = self.released_at
+            && !self.raw
+            && !self.stable
+            && now.saturating_sub(at) > self.config.double_gap_ms as u64
+        {
+            self.released_at = None;
+            return Some(Gesture::Short);
+        }
+
+        None
+    }
+}
+
+impl Default for Button {
+    fn default() -> Self {
+        Self::new(ButtonConfig::DEFAULT)
+    }
+}
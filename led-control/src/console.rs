@@ -0,0 +1,208 @@
+//! Line-based serial command protocol.
+//!
+//! [`LineBuffer`] collects bytes into lines and [`parse`] turns a line into a
+//! [`Command`]. Both borrow from fixed buffers, so nothing is allocated.
+//!
+//! ```text
+//! list                  effects and their numbers
+//! select <n>            show effect n
+//! brightness <0-255>
+//! param <name> <value>  set a named effect parameter
+//! stats                 render and driver statistics
+//! fps <1-1000>          target frame rate
+//! stream                switch to binary frame streaming (USB only)
+//! fire                  set off the triggered effects
+//! channel <n> <pixels> <universe> <address>
+//!                       strip length and DMX patch, saved for the next reset
+//! help
+//! ```
+
+use core::str::FromStr;
+
+/// Highest frame rate `fps` accepts
+pub const MAX_FPS: u16 = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command<'a> {
+    List,
+    Select(usize),
+    Brightness(u8),
+    Param {
+        name: &'a str,
+        value: i32,
+    },
+    Stats,
+    Fps(u16),
+    Stream,
+    Fire,
+    Channel {
+        channel: usize,
+        pixels: u16,
+        universe: u16,
+        address: u16,
+    },
+    Help,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError<'a> {
+    Empty,
+    UnknownCommand(&'a str),
+    /// Name of the missing argument
+    MissingArgument(&'static str),
+    InvalidNumber(&'a str),
+    OutOfRange(&'a str),
+    TooManyArguments,
+}
+
+impl core::fmt::Display for ParseError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty command"),
+            Self::UnknownCommand(name) => write!(f, "unknown command '{name}', try 'help'"),
+            Self::MissingArgument(name) => write!(f, "missing <{name}>"),
+            Self::InvalidNumber(text) => write!(f, "'{text}' is not a number"),
+            Self::OutOfRange(text) => write!(f, "{text} is out of range"),
+            Self::TooManyArguments => write!(f, "too many arguments"),
+        }
+    }
+}
+
+/// Parse one line. Words are separated by any whitespace; commands are
+/// case-sensitive.
+pub fn parse(line: &str) -> Result<Command<'_>, ParseError<'_>> {
+    let mut words = line.split_ascii_whitespace();
+    let name = words.next().ok_or(ParseError::Empty)?;
+
+    let command = match name {
+        "list" => Command::List,
+        "select" => Command::Select(number(words.next(), "n")?),
+        "brightness" => Command::Brightness(number(words.next(), "value")?),
+        "param" => Command::Param {
+            name: words.next().ok_or(ParseError::MissingArgument("name"))?,
+            value: number(words.next(), "value")?,
+        },
+        "stats" => Command::Stats,
+        "fps" => {
+            let text = words.next().ok_or(ParseError::MissingArgument("fps"))?;
+            let fps: u16 = number(Some(text), "fps")?;
+            if fps == 0 || fps > MAX_FPS {
+                return Err(ParseError::OutOfRange(text));
+            }
+            Command::Fps(fps)
+        }
+        "stream" => Command::Stream,
+        "fire" => Command::Fire,
+        "channel" => {
+            let channel = number(words.next(), "n")?;
+            let pixels = number(words.next(), "pixels")?;
+            let universe = number(words.next(), "universe")?;
+            let text = words.next().ok_or(ParseError::MissingArgument("address"))?;
+            let address: u16 = number(Some(text), "address")?;
+            if !(1..=512).contains(&address) {
+                return Err(ParseError::OutOfRange(text));
+            }
+            Command::Channel {
+                channel,
+                pixels,
+                universe,
+                address,
+            }
+        }
+        "help" => Command::Help,
+        _ => return Err(ParseError::UnknownCommand(name)),
+    };
+
+    if words.next().is_some() {
+        return Err(ParseError::TooManyArguments);
+    }
+    Ok(command)
+}
+
+fn number<'a, T: FromStr>(word: Option<&'a str>, name: &'static str) -> Result<T, ParseError<'a>> {
+    let text = word.ok_or(ParseError::MissingArgument(name))?;
+    text.parse().map_err(|_| {
+        // Tell "300" for a u8 apart from "abc"
+        if text
+            .trim_start_matches('-')
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+        {
+            ParseError::OutOfRange(text)
+        } else {
+            ParseError::InvalidNumber(text)
+        }
+    })
+}
+
+/// What [`LineBuffer::push`] made of a byte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Input<'a> {
+    /// Still inside a line
+    Pending,
+    /// A complete line, without the terminator
+    Line(&'a str),
+    /// The line was longer than the buffer and has been dropped
+    Overflow,
+    /// The line was not valid UTF-8 and has been dropped
+    Invalid,
+}
+
+/// Collects bytes into lines of at most `N` bytes. Accepts `\n`, `\r` or
+/// `\r\n` endings and handles backspace, as typed in a terminal.
+pub struct LineBuffer<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+    overflowed: bool,
+    last_was_cr: bool,
+}
+
+impl<const N: usize> LineBuffer<N> {
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+            overflowed: false,
+            last_was_cr: false,
+        }
+    }
+
+    pub fn push(&mut self, byte: u8) -> Input<'_> {
+        let after_cr = core::mem::replace(&mut self.last_was_cr, byte == b'\r');
+
+        match byte {
+            // The \n of a \r\n ending was already handled with the \r
+            b'\n' if after_cr => Input::Pending,
+            b'\r' | b'\n' => {
+                let len = core::mem::take(&mut self.len);
+                if core::mem::take(&mut self.overflowed) {
+                    return Input::Overflow;
+                }
+                match core::str::from_utf8(&self.bytes[..len]) {
+                    Ok(line) => Input::Line(line),
+                    Err(_) => Input::Invalid,
+                }
+            }
+            // Backspace and DEL
+            0x08 | 0x7F => {
+                self.len = self.len.saturating_sub(1);
+                Input::Pending
+            }
+            _ => {
+                if self.len < N {
+                    self.bytes[self.len] = byte;
+                    self.len += 1;
+                } else {
+                    self.overflowed = true;
+                }
+                Input::Pending
+            }
+        }
+    }
+}
+
+impl<const N: usize> Default for LineBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
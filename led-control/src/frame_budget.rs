@@ -0,0 +1,108 @@
+//! Frame timing for a fixed-rate render loop: overrun and drop accounting
+//! and the frame rate the strips can actually take.
+//!
+//! The loop reports each frame with [`FrameBudget::frame_done`]: how long
+//! rendering took, how long the frame was busy from its tick on, and how
+//! long the longest channel takes on the wire. A frame busy past its period
+//! is an overrun, and every tick it ran into is dropped. With `auto_lower`
+//! set, a wire time longer than the period lowers the rate until it fits;
+//! the requested rate comes back once it does.
+//!
+//! Times are in µs.
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    pub frames: u32,
+    /// Frames busy for longer than a period
+    pub overruns: u32,
+    /// Ticks skipped because a frame ran into them
+    pub dropped: u32,
+    pub last_render_us: u32,
+    pub max_render_us: u32,
+    /// Rate the loop runs at, below the requested one if lowered
+    pub fps: u16,
+}
+
+pub struct FrameBudget {
+    target_fps: u16,
+    /// Lower the rate when the wire time doesn't fit the period
+    pub auto_lower: bool,
+    stats: FrameStats,
+}
+
+impl FrameBudget {
+    pub const fn new(fps: u16) -> Self {
+        let fps = if fps == 0 { 1 } else { fps };
+        Self {
+            target_fps: fps,
+            auto_lower: true,
+            stats: FrameStats {
+                frames: 0,
+                overruns: 0,
+                dropped: 0,
+                last_render_us: 0,
+                max_render_us: 0,
+                fps,
+            },
+        }
+    }
+
+    #[inline(always)]
+    pub fn target_fps(&self) -> u16 {
+        self.target_fps
+    }
+
+    /// The rate to run at now
+    #[inline(always)]
+    pub fn fps(&self) -> u16 {
+        self.stats.fps
+    }
+
+    #[inline(always)]
+    pub fn period_us(&self) -> u32 {
+        1_000_000 / self.stats.fps as u32
+    }
+
+    #[inline(always)]
+    pub fn stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    /// Ask for a new rate; it applies from the next frame
+    pub fn set_target(&mut self, fps: u16) {
+        self.target_fps = fps.max(1);
+        self.stats.fps = self.target_fps;
+    }
+
+    /// Account a frame. `busy_us` runs from its tick to the end of the
+    /// refresh. Returns how many ticks it ran into, which the loop should
+    /// skip rather than catch up on.
+    pub fn frame_done(&mut self, render_us: u32, busy_us: u32, wire_us: u32) -> u32 {
+        let period = self.period_us();
+        let stats = &mut self.stats;
+        stats.frames = stats.frames.wrapping_add(1);
+        stats.last_render_us = render_us;
+        stats.max_render_us = stats.max_render_us.max(render_us);
+
+        let missed = busy_us / period;
+        if missed > 0 {
+            stats.overruns += 1;
+            stats.dropped += missed;
+        }
+
+        if self.auto_lower {
+            // The fastest the strips can take, and back up to the request
+            // once it fits again
+            let fits = (1_000_000 / wire_us.max(1)).clamp(1, u16::MAX as u32) as u16;
+            stats.fps = self.target_fps.min(fits);
+        }
+        missed
+    }
+}
+
+impl Default for FrameBudget {
+    /// 40 fps, the firmware's default
+    fn default() -> Self {
+        Self::new(40)
+    }
+}
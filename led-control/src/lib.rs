@@ -0,0 +1,20 @@
+//! Input and control logic for the LED firmware, kept free of HAL types so
+//! it can be tested on the host.
+//!
+//! Times are plain milliseconds since boot (`embassy_time::Instant::as_millis`).
+
+#![no_std]
+
+pub mod button;
+pub mod console;
+pub mod frame_budget;
+pub mod selector;
+pub mod settings;
+pub mod store;
+
+pub use button::{Button, ButtonConfig, Gesture};
+pub use console::{Command, Input, LineBuffer, ParseError, parse};
+pub use frame_budget::{FrameBudget, FrameStats};
+pub use selector::{Action, BRIGHTNESS_LEVELS, EffectSelector};
+pub use settings::{ChannelConfig, DecodeError, Settings};
+pub use store::{Store, StoreError};
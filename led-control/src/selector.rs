@@ -0,0 +1,110 @@
+//! Which effect is showing, how bright, and whether the strip is on.
+//!
+//! The state lives in atomics so one `static EffectSelector` can be changed
+//! from the button task and the serial console and read by the render
+//! loop without a lock.
+
+use crate::button::Gesture;
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+
+/// Brightness steps cycled by a long press
+pub const BRIGHTNESS_LEVELS: [u8; 4] = [32, 80, 160, 255];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Next,
+    Previous,
+    Brightness(u8),
+    PowerOn,
+    PowerOff,
+}
+
+pub struct EffectSelector {
+    effect_count: usize,
+    effect: AtomicUsize,
+    brightness: AtomicU8,
+    powered: AtomicBool,
+}
+
+impl EffectSelector {
+    /// Starts on effect 0 at full brightness
+    pub const fn new(effect_count: usize) -> Self {
+        Self {
+            effect_count,
+            effect: AtomicUsize::new(0),
+            brightness: AtomicU8::new(u8::MAX),
+            powered: AtomicBool::new(true),
+        }
+    }
+
+    #[inline(always)]
+    pub fn effect_count(&self) -> usize {
+        self.effect_count
+    }
+
+    #[inline(always)]
+    pub fn effect(&self) -> usize {
+        self.effect.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    pub fn brightness(&self) -> u8 {
+        self.brightness.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    pub fn is_on(&self) -> bool {
+        self.powered.load(Ordering::Relaxed)
+    }
+
+    /// Short = next effect, double = previous, long = next brightness step,
+    /// hold = power off. While off, any gesture turns the strip back on.
+    pub fn handle(&self, gesture: Gesture) -> Action {
+        let action = if !self.is_on() {
+            Action::PowerOn
+        } else {
+            match gesture {
+                Gesture::Short => Action::Next,
+                Gesture::Double => Action::Previous,
+                Gesture::Long => Action::Brightness(self.next_brightness()),
+                Gesture::Hold => Action::PowerOff,
+            }
+        };
+
+        self.apply(action);
+        action
+    }
+
+    pub fn apply(&self, action: Action) {
+        let count = self.effect_count.max(1);
+        match action {
+            Action::Next => self
+                .effect
+                .store((self.effect() + 1) % count, Ordering::Relaxed),
+            Action::Previous => self
+                .effect
+                .store((self.effect() + count - 1) % count, Ordering::Relaxed),
+            Action::Brightness(level) => self.brightness.store(level, Ordering::Relaxed),
+            Action::PowerOn => self.powered.store(true, Ordering::Relaxed),
+            Action::PowerOff => self.powered.store(false, Ordering::Relaxed),
+        }
+    }
+
+    /// Show `effect`; false if there is no such effect
+    pub fn select(&self, effect: usize) -> bool {
+        if effect >= self.effect_count {
+            return false;
+        }
+        self.effect.store(effect, Ordering::Relaxed);
+        true
+    }
+
+    /// The next step above the current brightness, wrapping to the dimmest
+    fn next_brightness(&self) -> u8 {
+        let current = self.brightness();
+        BRIGHTNESS_LEVELS
+            .into_iter()
+            .find(|&level| level > current)
+            .unwrap_or(BRIGHTNESS_LEVELS[0])
+    }
+}
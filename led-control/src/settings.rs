@@ -0,0 +1,178 @@
+//! Settings kept across resets, and their versioned flash encoding.
+//!
+//! ```text
+//! version | effect | brightness | flags | fps u16 | hue | saturation
+//! then per channel: pixels u16 | universe u16 | address u16
+//! ```
+//!
+//! Numbers are little-endian; flags bit 0 is the power state. A record in
+//! an older layout is read field by field and the fields it lacks keep
+//! their defaults; a newer or damaged one is refused, and [`Settings::load`]
+//! falls back to the defaults.
+
+use embedded_storage_async::nor_flash::NorFlash;
+
+use crate::console::MAX_FPS;
+use crate::store::{Store, StoreError};
+
+/// Layout written by [`Settings::encode`]
+pub const VERSION: u8 = 1;
+/// Output channels of the driver
+pub const CHANNELS: usize = 8;
+
+const CHANNEL_LEN: usize = 6;
+const HEADER_LEN: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChannelConfig {
+    /// Strip length, 0 = unused
+    pub pixels: u16,
+    /// E1.31/Art-Net universe and first DMX address (1-512) of the strip
+    pub universe: u16,
+    pub address: u16,
+}
+
+impl ChannelConfig {
+    pub const UNUSED: Self = Self {
+        pixels: 0,
+        universe: 1,
+        address: 1,
+    };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub effect: u8,
+    pub brightness: u8,
+    pub power: bool,
+    pub fps: u16,
+    pub hue: u8,
+    pub saturation: u8,
+    pub channels: [ChannelConfig; CHANNELS],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    TooShort,
+    /// Written by newer firmware
+    UnknownVersion(u8),
+    /// A field is out of range
+    Invalid,
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooShort => write!(f, "settings record too short"),
+            Self::UnknownVersion(version) => write!(f, "unknown settings version {version}"),
+            Self::Invalid => write!(f, "settings out of range"),
+        }
+    }
+}
+
+impl Settings {
+    /// First effect, full brightness, 40 fps, no channels
+    pub const DEFAULT: Self = Self {
+        effect: 0,
+        brightness: u8::MAX,
+        power: true,
+        fps: 40,
+        hue: 21,
+        saturation: u8::MAX,
+        channels: [ChannelConfig::UNUSED; CHANNELS],
+    };
+
+    pub const ENCODED_LEN: usize = HEADER_LEN + CHANNELS * CHANNEL_LEN;
+
+    pub fn encode(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0; Self::ENCODED_LEN];
+        let [fps_lo, fps_hi] = self.fps.to_le_bytes();
+        bytes[..HEADER_LEN].copy_from_slice(&[
+            VERSION,
+            self.effect,
+            self.brightness,
+            self.power as u8,
+            fps_lo,
+            fps_hi,
+            self.hue,
+            self.saturation,
+        ]);
+
+        let (channels, _) = bytes[HEADER_LEN..].as_chunks_mut::<CHANNEL_LEN>();
+        for (out, channel) in channels.iter_mut().zip(&self.channels) {
+            out[0..2].copy_from_slice(&channel.pixels.to_le_bytes());
+            out[2..4].copy_from_slice(&channel.universe.to_le_bytes());
+            out[4..6].copy_from_slice(&channel.address.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Read a record of any known version; fields it lacks come from
+    /// `defaults`
+    pub fn decode(bytes: &[u8], defaults: &Self) -> Result<Self, DecodeError> {
+        let (&version, _) = bytes.split_first().ok_or(DecodeError::TooShort)?;
+        // A new version appends fields: its length goes here and its fields
+        // are read after these
+        let len = match version {
+            1 => Self::ENCODED_LEN,
+            _ => return Err(DecodeError::UnknownVersion(version)),
+        };
+        let bytes = bytes.get(..len).ok_or(DecodeError::TooShort)?;
+
+        let mut settings = Self {
+            effect: bytes[1],
+            brightness: bytes[2],
+            power: bytes[3] & 1 != 0,
+            fps: u16::from_le_bytes([bytes[4], bytes[5]]),
+            hue: bytes[6],
+            saturation: bytes[7],
+            ..*defaults
+        };
+
+        let (channels, _) = bytes[HEADER_LEN..].as_chunks::<CHANNEL_LEN>();
+        for (channel, raw) in settings.channels.iter_mut().zip(channels) {
+            *channel = ChannelConfig {
+                pixels: u16::from_le_bytes([raw[0], raw[1]]),
+                universe: u16::from_le_bytes([raw[2], raw[3]]),
+                address: u16::from_le_bytes([raw[4], raw[5]]),
+            };
+        }
+
+        if settings.fps == 0
+            || settings.fps > MAX_FPS
+            || settings
+                .channels
+                .iter()
+                .any(|channel| !(1..=512).contains(&channel.address))
+        {
+            return Err(DecodeError::Invalid);
+        }
+        Ok(settings)
+    }
+
+    /// The saved settings, or `defaults` if there are none or they can't be
+    /// read
+    pub async fn load<F: NorFlash, const N: usize>(
+        store: &mut Store<F, N>,
+        defaults: Self,
+    ) -> Self {
+        let mut bytes = [0; 256];
+        match store.load(&mut bytes).await {
+            Ok(Some(len)) => Self::decode(&bytes[..len], &defaults).unwrap_or(defaults),
+            _ => defaults,
+        }
+    }
+
+    pub async fn save<F: NorFlash, const N: usize>(
+        &self,
+        store: &mut Store<F, N>,
+    ) -> Result<(), StoreError<F::Error>> {
+        store.save(&self.encode()).await
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
@@ -0,0 +1,251 @@
+//! Append-only record log in NOR flash, spread over several erase pages for
+//! wear leveling.
+//!
+//! Every save appends a record after the previous one; the newest valid
+//! record is the current value. When a page is full the log moves on to
+//! the next page, erasing it first, and wraps around at the end, so each
+//! page is erased once per pass. The newest record is never in the page
+//! being erased, so a reset mid-save loses at most the new value.
+//!
+//! ```text
+//! sequence u32 | length u16 | 0xFFFF | crc32 u32 | payload | 0xFF padding
+//! ```
+//!
+//! The CRC (CRC-32/ISO-HDLC) covers the sequence, the length and the
+//! payload. Records are padded to the flash write size; erased flash reads
+//! as 0xFF, so an all-0xFF header marks the free space of a page.
+//!
+//! Internal flash reads with byte granularity; the store expects
+//! `READ_SIZE` 1. The flash is async, so an erase waits without holding up
+//! the rest of the system.
+
+use crc::{CRC_32_ISO_HDLC, Crc};
+use embedded_storage_async::nor_flash::NorFlash;
+
+const CRC: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+const HEADER_LEN: usize = 12;
+const ERASED: u8 = 0xFF;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoreError<E> {
+    Flash(E),
+    /// The record doesn't fit the write buffer or a page
+    TooLarge,
+}
+
+impl<E: core::fmt::Debug> core::fmt::Display for StoreError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Flash(error) => write!(f, "flash error {error:?}"),
+            Self::TooLarge => write!(f, "record too large"),
+        }
+    }
+}
+
+/// Where a record is
+#[derive(Clone, Copy, Debug)]
+struct Record {
+    sequence: u32,
+    page: u32,
+    offset: u32,
+    len: u16,
+}
+
+/// Record log over all of `flash`, which must span at least two erase
+/// pages. `N` is the largest record including header and padding.
+pub struct Store<F, const N: usize> {
+    flash: F,
+    pages: u32,
+    scanned: bool,
+    /// Newest valid record
+    latest: Option<Record>,
+    /// Page and offset the next record goes to; `None` moves on to a fresh
+    /// page
+    head: Option<(u32, u32)>,
+    buffer: [u8; N],
+}
+
+impl<F: NorFlash, const N: usize> Store<F, N> {
+    pub fn new(flash: F) -> Self {
+        let pages = (flash.capacity() / F::ERASE_SIZE) as u32;
+        assert!(pages >= 2, "the store needs at least two erase pages");
+        Self {
+            flash,
+            pages,
+            scanned: false,
+            latest: None,
+            head: None,
+            buffer: [ERASED; N],
+        }
+    }
+
+    pub fn into_inner(self) -> F {
+        self.flash
+    }
+
+    /// Copy the newest valid record into `out`. Returns its length, or
+    /// `None` if nothing has been saved yet.
+    pub async fn load(&mut self, out: &mut [u8]) -> Result<Option<usize>, StoreError<F::Error>> {
+        self.scan().await?;
+        let Some(record) = self.latest else {
+            return Ok(None);
+        };
+        let len = record.len as usize;
+        let out = out.get_mut(..len).ok_or(StoreError::TooLarge)?;
+        let at = self.address(record.page, record.offset) + HEADER_LEN as u32;
+        self.flash.read(at, out).await.map_err(StoreError::Flash)?;
+        Ok(Some(len))
+    }
+
+    /// Append `payload` as the newest record
+    pub async fn save(&mut self, payload: &[u8]) -> Result<(), StoreError<F::Error>> {
+        self.scan().await?;
+        let size = Self::record_size(payload.len());
+        if size > N || size > F::ERASE_SIZE || payload.len() > u16::MAX as usize {
+            return Err(StoreError::TooLarge);
+        }
+
+        let (page, offset) = match self.head {
+            Some((page, offset)) if offset as usize + size <= F::ERASE_SIZE => (page, offset),
+            _ => {
+                let page = self
+                    .latest
+                    .map_or(0, |latest| (latest.page + 1) % self.pages);
+                let from = self.address(page, 0);
+                self.flash
+                    .erase(from, from + F::ERASE_SIZE as u32)
+                    .await
+                    .map_err(StoreError::Flash)?;
+                (page, 0)
+            }
+        };
+
+        // 0xFFFF_FFFF would read as free space
+        let sequence = self
+            .latest
+            .map_or(1, |latest| latest.sequence.wrapping_add(1).max(1));
+        let len = payload.len() as u16;
+        let record = &mut self.buffer[..size];
+        record.fill(ERASED);
+        record[0..4].copy_from_slice(&sequence.to_le_bytes());
+        record[4..6].copy_from_slice(&len.to_le_bytes());
+        record[HEADER_LEN..HEADER_LEN + payload.len()].copy_from_slice(payload);
+        let crc = checksum(&record[0..6], payload);
+        record[8..12].copy_from_slice(&crc.to_le_bytes());
+
+        let at = self.address(page, offset);
+        self.flash
+            .write(at, &self.buffer[..size])
+            .await
+            .map_err(StoreError::Flash)?;
+
+        self.latest = Some(Record {
+            sequence,
+            page,
+            offset,
+            len,
+        });
+        self.head = Some((page, offset + size as u32));
+        Ok(())
+    }
+
+    /// Find the newest valid record and where the next one goes
+    async fn scan(&mut self) -> Result<(), StoreError<F::Error>> {
+        if self.scanned {
+            return Ok(());
+        }
+
+        let page_size = F::ERASE_SIZE as u32;
+        let mut latest: Option<Record> = None;
+        let mut head = None;
+        for page in 0..self.pages {
+            let mut offset = 0;
+            // First byte past the records, the page size if it is full
+            let end = loop {
+                if offset + HEADER_LEN as u32 > page_size {
+                    break page_size;
+                }
+                let mut header = [0; HEADER_LEN];
+                self.flash
+                    .read(self.address(page, offset), &mut header)
+                    .await
+                    .map_err(StoreError::Flash)?;
+                if header.iter().all(|&b| b == ERASED) {
+                    break offset;
+                }
+
+                let sequence = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+                let len = u16::from_le_bytes([header[4], header[5]]);
+                let crc = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+                let size = Self::record_size(len as usize) as u32;
+                // A torn header; nothing after it can be trusted
+                if offset + size > page_size {
+                    break page_size;
+                }
+
+                let valid = self.verify(page, offset, &header[0..6], len).await? == crc;
+                if valid && latest.is_none_or(|latest| sequence > latest.sequence) {
+                    latest = Some(Record {
+                        sequence,
+                        page,
+                        offset,
+                        len,
+                    });
+                }
+                offset += size;
+            };
+
+            if latest.is_some_and(|latest| latest.page == page) {
+                head = Some((page, end));
+            }
+        }
+
+        self.latest = latest;
+        self.head = head;
+        self.scanned = true;
+        Ok(())
+    }
+
+    /// CRC of a record's header fields and payload, read through the write
+    /// buffer
+    async fn verify(
+        &mut self,
+        page: u32,
+        offset: u32,
+        header: &[u8],
+        len: u16,
+    ) -> Result<u32, StoreError<F::Error>> {
+        let mut digest = CRC.digest();
+        digest.update(header);
+        let mut at = self.address(page, offset) + HEADER_LEN as u32;
+        let mut left = len as usize;
+        while left > 0 {
+            let chunk = &mut self.buffer[..left.min(N)];
+            self.flash
+                .read(at, chunk)
+                .await
+                .map_err(StoreError::Flash)?;
+            digest.update(chunk);
+            at += chunk.len() as u32;
+            left -= chunk.len();
+        }
+        Ok(digest.finalize())
+    }
+
+    fn address(&self, page: u32, offset: u32) -> u32 {
+        page * F::ERASE_SIZE as u32 + offset
+    }
+
+    /// Header and payload, padded to whole write units
+    fn record_size(payload: usize) -> usize {
+        (HEADER_LEN + payload).next_multiple_of(F::WRITE_SIZE.max(4))
+    }
+}
+
+fn checksum(header: &[u8], payload: &[u8]) -> u32 {
+    let mut digest = CRC.digest();
+    digest.update(header);
+    digest.update(payload);
+    digest.finalize()
+}
@@ -0,0 +1,141 @@
+//! Button gestures from scripted pin traces, and the effect selector they drive.
+
+use led_control::{Action, BRIGHTNESS_LEVELS, Button, ButtonConfig, EffectSelector, Gesture};
+
+/// Replay `(time, level)` edges, calling `update` on every edge and at every
+/// deadline like the firmware task does, until `end`
+fn run(edges: &[(u64, bool)], end: u64) -> Vec<(u64, Gesture)> {
+    let mut button = Button::new(ButtonConfig::DEFAULT);
+    let mut gestures = Vec::new();
+    let mut level = false;
+    let mut edges = edges.iter().peekable();
+    let mut now = 0;
+
+    loop {
+        let next_edge = edges.peek().map(|&&(t, _)| t);
+        let next = match (next_edge, button.next_deadline()) {
+            (Some(edge), Some(deadline)) => edge.min(deadline),
+            (Some(edge), None) => edge,
+            (None, Some(deadline)) => deadline,
+            (None, None) => break,
+        };
+        if next > end {
+            break;
+        }
+        now = now.max(next);
+        if next_edge == Some(now) {
+            level = edges.next().unwrap().1;
+        }
+        if let Some(gesture) = button.update(level, now) {
+            gestures.push((now, gesture));
+        }
+    }
+
+    gestures
+}
+
+/// A press from `at` for `length` ms, bouncing for a few ms on both edges
+fn press(at: u64, length: u64) -> Vec<(u64, bool)> {
+    vec![
+        (at, true),
+        (at + 2, false),
+        (at + 4, true),
+        (at + length, false),
+        (at + length + 3, true),
+        (at + length + 5, false),
+    ]
+}
+
+#[test]
+fn short_press_after_the_double_window() {
+    let gestures = run(&press(100, 120), 5000);
+    // Released at 220, the double window closes 300 ms later
+    assert_eq!(gestures, [(521, Gesture::Short)]);
+}
+
+#[test]
+fn bounces_alone_are_ignored() {
+    let gestures = run(
+        &[(100, true), (105, false), (110, true), (112, false)],
+        5000,
+    );
+    assert!(gestures.is_empty(), "{gestures:?}");
+}
+
+#[test]
+fn double_press() {
+    let mut edges = press(100, 100);
+    edges.extend(press(400, 100));
+    let gestures = run(&edges, 5000);
+    assert_eq!(gestures.len(), 1, "{gestures:?}");
+    assert_eq!(gestures[0].1, Gesture::Double);
+}
+
+#[test]
+fn slow_second_press_is_two_shorts() {
+    let mut edges = press(100, 100);
+    edges.extend(press(800, 100));
+    let gestures: Vec<_> = run(&edges, 5000).into_iter().map(|(_, g)| g).collect();
+    assert_eq!(gestures, [Gesture::Short, Gesture::Short]);
+}
+
+#[test]
+fn long_press_on_release() {
+    // Reported once the release has stopped bouncing
+    let gestures = run(&press(100, 900), 5000);
+    assert_eq!(gestures, [(1025, Gesture::Long)]);
+}
+
+#[test]
+fn hold_fires_while_held_and_nothing_on_release() {
+    let gestures = run(&press(100, 3000), 5000);
+    assert_eq!(gestures, [(2100, Gesture::Hold)]);
+}
+
+#[test]
+fn missed_deadline_still_reports_the_first_press() {
+    // Without deadline calls, the late second press reports the first one
+    let mut button = Button::default();
+    let mut gestures = Vec::new();
+    for (now, level) in [
+        (0, true),
+        (30, false),
+        (60, false),
+        (900, true),
+        (930, true),
+    ] {
+        gestures.extend(button.update(level, now));
+    }
+    assert_eq!(gestures, [Gesture::Short]);
+    assert!(button.is_pressed());
+}
+
+#[test]
+fn selector_follows_gestures() {
+    let selector = EffectSelector::new(3);
+    assert_eq!((selector.effect(), selector.brightness()), (0, 255));
+
+    assert_eq!(selector.handle(Gesture::Short), Action::Next);
+    assert_eq!(selector.handle(Gesture::Short), Action::Next);
+    assert_eq!(selector.handle(Gesture::Short), Action::Next);
+    assert_eq!(selector.effect(), 0);
+    assert_eq!(selector.handle(Gesture::Double), Action::Previous);
+    assert_eq!(selector.effect(), 2);
+
+    // Long presses step brightness up and wrap around to the dimmest level
+    assert_eq!(
+        selector.handle(Gesture::Long),
+        Action::Brightness(BRIGHTNESS_LEVELS[0])
+    );
+    assert_eq!(
+        selector.handle(Gesture::Long),
+        Action::Brightness(BRIGHTNESS_LEVELS[1])
+    );
+
+    // Hold powers off; the next gesture only powers back on
+    assert_eq!(selector.handle(Gesture::Hold), Action::PowerOff);
+    assert!(!selector.is_on());
+    assert_eq!(selector.handle(Gesture::Short), Action::PowerOn);
+    assert!(selector.is_on());
+    assert_eq!(selector.effect(), 2);
+}
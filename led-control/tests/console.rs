@@ -0,0 +1,109 @@
+//! Console line assembly and command parsing.
+
+use led_control::{Command, Input, LineBuffer, ParseError, parse};
+
+/// Feed `bytes` and collect the results other than `Pending`
+fn lines<const N: usize>(buffer: &mut LineBuffer<N>, bytes: &[u8]) -> Vec<String> {
+    let mut out = Vec::new();
+    for &byte in bytes {
+        match buffer.push(byte) {
+            Input::Pending => {}
+            Input::Line(line) => out.push(line.to_string()),
+            Input::Overflow => out.push("<overflow>".to_string()),
+            Input::Invalid => out.push("<invalid>".to_string()),
+        }
+    }
+    out
+}
+
+#[test]
+fn parses_every_command() {
+    assert_eq!(parse("list"), Ok(Command::List));
+    assert_eq!(parse("select 2"), Ok(Command::Select(2)));
+    assert_eq!(parse("brightness 128"), Ok(Command::Brightness(128)));
+    assert_eq!(
+        parse("param hue -12"),
+        Ok(Command::Param {
+            name: "hue",
+            value: -12
+        })
+    );
+    assert_eq!(parse("stats"), Ok(Command::Stats));
+    assert_eq!(parse("fps 60"), Ok(Command::Fps(60)));
+    assert_eq!(parse("stream"), Ok(Command::Stream));
+    assert_eq!(parse("fire"), Ok(Command::Fire));
+    assert_eq!(
+        parse("channel 1 120 3 4"),
+        Ok(Command::Channel {
+            channel: 1,
+            pixels: 120,
+            universe: 3,
+            address: 4
+        })
+    );
+    assert_eq!(parse("help"), Ok(Command::Help));
+}
+
+#[test]
+fn tolerates_extra_whitespace() {
+    assert_eq!(parse("  select\t 1  "), Ok(Command::Select(1)));
+}
+
+#[test]
+fn reports_errors() {
+    assert_eq!(parse(""), Err(ParseError::Empty));
+    assert_eq!(parse("   "), Err(ParseError::Empty));
+    assert_eq!(parse("dance"), Err(ParseError::UnknownCommand("dance")));
+    assert_eq!(parse("select"), Err(ParseError::MissingArgument("n")));
+    assert_eq!(parse("select two"), Err(ParseError::InvalidNumber("two")));
+    assert_eq!(parse("brightness 300"), Err(ParseError::OutOfRange("300")));
+    assert_eq!(parse("brightness -1"), Err(ParseError::OutOfRange("-1")));
+    assert_eq!(
+        parse("param hue"),
+        Err(ParseError::MissingArgument("value"))
+    );
+    assert_eq!(parse("fps 0"), Err(ParseError::OutOfRange("0")));
+    assert_eq!(parse("fps 5000"), Err(ParseError::OutOfRange("5000")));
+    assert_eq!(
+        parse("channel 0 60 1"),
+        Err(ParseError::MissingArgument("address"))
+    );
+    assert_eq!(parse("channel 0 60 1 0"), Err(ParseError::OutOfRange("0")));
+    assert_eq!(parse("list all"), Err(ParseError::TooManyArguments));
+    assert_eq!(
+        parse("select x").unwrap_err().to_string(),
+        "'x' is not a number"
+    );
+}
+
+#[test]
+fn assembles_lines_with_any_ending() {
+    let mut buffer = LineBuffer::<32>::new();
+    assert_eq!(
+        lines(&mut buffer, b"list\nstats\r\nfps 30\r"),
+        ["list", "stats", "fps 30"]
+    );
+    // The \n of a split \r\n is not a second line; a blank line still
+    // comes through and the parser rejects it
+    assert_eq!(lines(&mut buffer, b"\n\n"), [""]);
+}
+
+#[test]
+fn handles_backspace() {
+    let mut buffer = LineBuffer::<32>::new();
+    assert_eq!(lines(&mut buffer, b"selx\x08ect 1\x7f2\r"), ["select 2"]);
+    assert_eq!(lines(&mut buffer, b"\x08\x08list\n"), ["list"]);
+}
+
+#[test]
+fn drops_overlong_and_invalid_lines() {
+    let mut buffer = LineBuffer::<8>::new();
+    assert_eq!(
+        lines(&mut buffer, b"brightness 255\nlist\n"),
+        ["<overflow>", "list"]
+    );
+    assert_eq!(
+        lines(&mut buffer, b"\xff\xfe\nstats\n"),
+        ["<invalid>", "stats"]
+    );
+}
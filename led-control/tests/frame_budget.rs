@@ -0,0 +1,60 @@
+//! Overrun and drop accounting and the automatic frame rate.
+
+use led_control::FrameBudget;
+
+#[test]
+fn frames_within_the_period_are_not_overruns() {
+    let mut budget = FrameBudget::new(50);
+    assert_eq!(budget.period_us(), 20_000);
+    for render in [3_000, 9_000, 5_000] {
+        assert_eq!(budget.frame_done(render, render + 2_000, 2_000), 0);
+    }
+
+    let stats = budget.stats();
+    assert_eq!(stats.frames, 3);
+    assert_eq!((stats.overruns, stats.dropped), (0, 0));
+    assert_eq!((stats.last_render_us, stats.max_render_us), (5_000, 9_000));
+    assert_eq!(stats.fps, 50);
+}
+
+#[test]
+fn an_overrun_drops_every_tick_it_runs_into() {
+    let mut budget = FrameBudget::new(100);
+    assert_eq!(budget.frame_done(10_000, 10_500, 2_000), 1);
+    assert_eq!(budget.frame_done(35_000, 35_000, 2_000), 3);
+    assert_eq!(budget.frame_done(1_000, 2_000, 2_000), 0);
+
+    let stats = budget.stats();
+    assert_eq!((stats.overruns, stats.dropped), (2, 4));
+}
+
+#[test]
+fn a_long_wire_time_lowers_the_rate_until_it_fits() {
+    // 1000 pixels take about 30 ms to send
+    let mut budget = FrameBudget::new(60);
+    budget.frame_done(1_000, 31_000, 30_300);
+    assert_eq!(budget.fps(), 33);
+    assert_eq!(budget.target_fps(), 60);
+    assert!(budget.period_us() >= 30_300);
+
+    // Shorter strips, the request fits again
+    budget.frame_done(1_000, 3_000, 2_100);
+    assert_eq!(budget.fps(), 60);
+}
+
+#[test]
+fn without_auto_lower_the_rate_stays() {
+    let mut budget = FrameBudget::new(60);
+    budget.auto_lower = false;
+    budget.frame_done(1_000, 31_000, 30_300);
+    assert_eq!(budget.fps(), 60);
+    assert_eq!(budget.stats().overruns, 1);
+}
+
+#[test]
+fn a_new_target_applies_at_once() {
+    let mut budget = FrameBudget::new(0);
+    assert_eq!(budget.fps(), 1);
+    budget.set_target(200);
+    assert_eq!((budget.fps(), budget.period_us()), (200, 5_000));
+}
@@ -0,0 +1,271 @@
+//! Settings store against a RAM flash with NOR semantics.
+
+use embassy_futures::block_on;
+use embedded_storage_async::nor_flash::{
+    ErrorType, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash,
+};
+use led_control::settings::VERSION;
+use led_control::{ChannelConfig, DecodeError, Settings, Store};
+
+const PAGE: usize = 256;
+const PAGES: usize = 4;
+
+#[derive(Debug, PartialEq, Eq)]
+struct PowerLoss;
+
+impl NorFlashError for PowerLoss {
+    fn kind(&self) -> NorFlashErrorKind {
+        NorFlashErrorKind::Other
+    }
+}
+
+/// Erases to 0xFF and only ever clears bits, like the real thing. A write
+/// budget simulates losing power part way through a write.
+struct RamFlash {
+    bytes: Vec<u8>,
+    erases: [u32; PAGES],
+    /// Bytes still written before the power goes
+    budget: Option<usize>,
+}
+
+impl RamFlash {
+    fn new() -> Self {
+        Self {
+            bytes: vec![0xFF; PAGE * PAGES],
+            erases: [0; PAGES],
+            budget: None,
+        }
+    }
+}
+
+impl ErrorType for RamFlash {
+    type Error = PowerLoss;
+}
+
+impl ReadNorFlash for RamFlash {
+    const READ_SIZE: usize = 1;
+
+    async fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), PowerLoss> {
+        let offset = offset as usize;
+        bytes.copy_from_slice(&self.bytes[offset..offset + bytes.len()]);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.bytes.len()
+    }
+}
+
+impl NorFlash for RamFlash {
+    const WRITE_SIZE: usize = 4;
+    const ERASE_SIZE: usize = PAGE;
+
+    async fn erase(&mut self, from: u32, to: u32) -> Result<(), PowerLoss> {
+        let (from, to) = (from as usize, to as usize);
+        assert!(from.is_multiple_of(PAGE) && to.is_multiple_of(PAGE));
+        self.bytes[from..to].fill(0xFF);
+        for page in from / PAGE..to / PAGE {
+            self.erases[page] += 1;
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), PowerLoss> {
+        let offset = offset as usize;
+        assert!(offset.is_multiple_of(Self::WRITE_SIZE));
+        assert!(bytes.len().is_multiple_of(Self::WRITE_SIZE));
+        for (index, &byte) in bytes.iter().enumerate() {
+            if let Some(budget) = &mut self.budget {
+                if *budget == 0 {
+                    return Err(PowerLoss);
+                }
+                *budget -= 1;
+            }
+            let cell = &mut self.bytes[offset + index];
+            assert_eq!(*cell & byte, byte, "programming a 0 bit back to 1");
+            *cell = byte;
+        }
+        Ok(())
+    }
+}
+
+fn settings(effect: u8) -> Settings {
+    let mut settings = Settings {
+        effect,
+        brightness: 80,
+        fps: 60,
+        ..Settings::DEFAULT
+    };
+    settings.channels[0] = ChannelConfig {
+        pixels: 60,
+        universe: 3,
+        address: 4,
+    };
+    settings
+}
+
+/// Forget everything but the flash contents, as a reset does
+fn reboot(store: Store<RamFlash, 128>) -> Store<RamFlash, 128> {
+    let mut flash = store.into_inner();
+    flash.budget = None;
+    Store::new(flash)
+}
+
+#[test]
+fn blank_flash_gives_the_defaults() {
+    let mut store = Store::<_, 128>::new(RamFlash::new());
+    assert_eq!(
+        block_on(Settings::load(&mut store, Settings::DEFAULT)),
+        Settings::DEFAULT
+    );
+}
+
+#[test]
+fn survives_a_reset() {
+    let mut store = Store::<_, 128>::new(RamFlash::new());
+    block_on(settings(1).save(&mut store)).unwrap();
+    block_on(settings(2).save(&mut store)).unwrap();
+    assert_eq!(
+        block_on(Settings::load(&mut store, Settings::DEFAULT)),
+        settings(2)
+    );
+
+    let mut store = reboot(store);
+    assert_eq!(
+        block_on(Settings::load(&mut store, Settings::DEFAULT)),
+        settings(2)
+    );
+    // Appending carries on after the reset
+    block_on(settings(3).save(&mut store)).unwrap();
+    let mut store = reboot(store);
+    assert_eq!(
+        block_on(Settings::load(&mut store, Settings::DEFAULT)),
+        settings(3)
+    );
+}
+
+#[test]
+fn wear_is_spread_over_all_pages() {
+    let mut store = Store::<_, 128>::new(RamFlash::new());
+    for round in 0..200u32 {
+        block_on(settings(round as u8).save(&mut store)).unwrap();
+        // Reset now and then, the log must pick up where it was
+        if round % 7 == 0 {
+            store = reboot(store);
+        }
+    }
+    let mut store = reboot(store);
+    assert_eq!(
+        block_on(Settings::load(&mut store, Settings::DEFAULT)),
+        settings(199)
+    );
+
+    let erases = store.into_inner().erases;
+    let (min, max) = (erases.iter().min().unwrap(), erases.iter().max().unwrap());
+    assert!(*min > 0 && max - min <= 1, "uneven wear: {erases:?}");
+}
+
+#[test]
+fn a_corrupt_record_falls_back_to_the_one_before() {
+    let mut store = Store::<_, 128>::new(RamFlash::new());
+    block_on(settings(1).save(&mut store)).unwrap();
+    block_on(settings(2).save(&mut store)).unwrap();
+
+    // Flip a bit in the second record's brightness
+    let mut flash = store.into_inner();
+    let record = 12 + Settings::ENCODED_LEN;
+    flash.bytes[record + 12 + 2] ^= 0x01;
+    let mut store = Store::<_, 128>::new(flash);
+    assert_eq!(
+        block_on(Settings::load(&mut store, Settings::DEFAULT)),
+        settings(1)
+    );
+}
+
+#[test]
+fn a_torn_write_keeps_the_previous_settings() {
+    for budget in [0, 3, 8, 20, 60] {
+        let mut store = Store::<_, 128>::new(RamFlash::new());
+        block_on(settings(1).save(&mut store)).unwrap();
+
+        let mut flash = store.into_inner();
+        flash.budget = Some(budget);
+        let mut store = Store::<_, 128>::new(flash);
+        assert!(block_on(settings(2).save(&mut store)).is_err());
+
+        let mut store = reboot(store);
+        assert_eq!(
+            block_on(Settings::load(&mut store, Settings::DEFAULT)),
+            settings(1)
+        );
+        // And saving works again afterwards
+        block_on(settings(3).save(&mut store)).unwrap();
+        let mut store = reboot(store);
+        assert_eq!(
+            block_on(Settings::load(&mut store, Settings::DEFAULT)),
+            settings(3)
+        );
+    }
+}
+
+#[test]
+fn a_torn_page_switch_keeps_the_previous_settings() {
+    let mut store = Store::<_, 128>::new(RamFlash::new());
+    // 68 byte records, three to a page
+    for effect in 0..3 {
+        block_on(settings(effect).save(&mut store)).unwrap();
+    }
+
+    // The next save erases page 1 and dies writing; page 0 was erased by
+    // the first save
+    let mut flash = store.into_inner();
+    flash.budget = Some(10);
+    let mut store = Store::<_, 128>::new(flash);
+    assert!(block_on(settings(9).save(&mut store)).is_err());
+
+    let mut store = reboot(store);
+    assert_eq!(
+        block_on(Settings::load(&mut store, Settings::DEFAULT)),
+        settings(2)
+    );
+    assert_eq!(store.into_inner().erases, [1, 1, 0, 0]);
+}
+
+#[test]
+fn decode_checks_version_and_ranges() {
+    let bytes = settings(1).encode();
+    assert_eq!(bytes[0], VERSION);
+    assert_eq!(
+        Settings::decode(&bytes, &Settings::DEFAULT),
+        Ok(settings(1))
+    );
+    assert_eq!(
+        Settings::decode(&bytes[..10], &Settings::DEFAULT),
+        Err(DecodeError::TooShort)
+    );
+
+    let mut newer = bytes;
+    newer[0] = VERSION + 1;
+    assert_eq!(
+        Settings::decode(&newer, &Settings::DEFAULT),
+        Err(DecodeError::UnknownVersion(VERSION + 1))
+    );
+
+    let mut zero_fps = bytes;
+    zero_fps[4..6].copy_from_slice(&[0, 0]);
+    assert_eq!(
+        Settings::decode(&zero_fps, &Settings::DEFAULT),
+        Err(DecodeError::Invalid)
+    );
+}
+
+#[test]
+fn unreadable_records_load_as_the_defaults() {
+    let mut store = Store::<_, 128>::new(RamFlash::new());
+    let mut newer = settings(1).encode();
+    newer[0] = VERSION + 1;
+    block_on(store.save(&newer)).unwrap();
+
+    let defaults = settings(7);
+    assert_eq!(block_on(Settings::load(&mut store, defaults)), defaults);
+}
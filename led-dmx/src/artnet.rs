@@ -0,0 +1,65 @@
+//! Art-Net 4 ArtDmx and ArtSync packets.
+
+use crate::{DEFAULT_PRIORITY, Dmx, Packet, ParseError, Protocol, be16};
+
+/// UDP port nodes listen on
+pub const PORT: u16 = 6454;
+
+pub const ID: [u8; 8] = *b"Art-Net\0";
+
+const OP_DMX: u16 = 0x5000;
+const OP_SYNC: u16 = 0x5200;
+
+// Field offsets
+const OPCODE: usize = 8;
+const SEQUENCE: usize = 12;
+const SUB_UNI: usize = 14;
+const NET: usize = 15;
+const LENGTH: usize = 16;
+const DATA: usize = 18;
+const SYNC_LEN: usize = 14;
+
+pub fn parse(bytes: &[u8]) -> Result<Packet<'_>, ParseError> {
+    if bytes.len() < OPCODE + 2 {
+        return Err(ParseError::TooShort);
+    }
+    if bytes[..8] != ID {
+        return Err(ParseError::UnknownProtocol);
+    }
+
+    // The only little-endian field in the protocol
+    match u16::from_le_bytes([bytes[OPCODE], bytes[OPCODE + 1]]) {
+        OP_DMX => dmx(bytes),
+        OP_SYNC if bytes.len() >= SYNC_LEN => Ok(Packet::Sync {
+            protocol: Protocol::ArtNet,
+            address: 0,
+        }),
+        OP_SYNC => Err(ParseError::TooShort),
+        _ => Err(ParseError::Unsupported),
+    }
+}
+
+fn dmx(bytes: &[u8]) -> Result<Packet<'_>, ParseError> {
+    if bytes.len() < DATA {
+        return Err(ParseError::TooShort);
+    }
+
+    // Even, 2-512 slots
+    let length = be16(bytes, LENGTH) as usize;
+    if !(2..=512).contains(&length) || !length.is_multiple_of(2) || DATA + length > bytes.len() {
+        return Err(ParseError::Malformed);
+    }
+
+    Ok(Packet::Dmx(Dmx {
+        protocol: Protocol::ArtNet,
+        universe: ((bytes[NET] as u16 & 0x7F) << 8) | bytes[SUB_UNI] as u16,
+        source: [0; 16],
+        priority: DEFAULT_PRIORITY,
+        sequence: bytes[SEQUENCE],
+        sync_address: 0,
+        preview: false,
+        terminated: false,
+        start_code: 0,
+        data: &bytes[DATA..DATA + length],
+    }))
+}
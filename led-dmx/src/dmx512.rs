@@ -0,0 +1,151 @@
+//! DMX512-A frames from a UART at 250 kbaud, 8N2.
+//!
+//! A frame is a break (the line held low for at least 88 µs), a mark after
+//! break, a start code and up to 512 slots. The UART sees the break as a
+//! character with a framing error and all-zero data; the mark after break
+//! is what lets it find the start bit of the start code, so a too-short
+//! mark shows up as a garbled start code or a framing error.
+//!
+//! [`Decoder`] is fed one [`Event`] per received character, typically from
+//! the UART interrupt, and keeps the last complete frame in a second buffer
+//! while it fills the first.
+
+pub const BAUD_RATE: u32 = 250_000;
+
+pub const MAX_SLOTS: usize = 512;
+
+/// Start code of ordinary level data
+pub const NULL_START_CODE: u8 = 0x00;
+
+/// Remote Device Management, sent on the same line and skipped here
+pub const RDM_START_CODE: u8 = 0xCC;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A character received without errors
+    Byte(u8),
+    /// A framing error with all-zero data
+    Break,
+    /// Noise, overrun, or a framing error with data. The frame in progress
+    /// is dropped.
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Lost sync, or a full frame received; only a break gets us going
+    WaitBreak,
+    StartCode,
+    Slots,
+    /// Alternate start code packet, ignored up to the next break
+    Skip,
+}
+
+pub struct Decoder {
+    buffers: [[u8; MAX_SLOTS]; 2],
+    back: usize, // Buffer being filled
+    len: usize,
+    front_len: usize,
+    state: State,
+    frames: u32,
+    skipped: u32,
+    errors: u32,
+}
+
+impl Decoder {
+    pub const fn new() -> Self {
+        Self {
+            buffers: [[0; MAX_SLOTS]; 2],
+            back: 0,
+            len: 0,
+            front_len: 0,
+            state: State::WaitBreak,
+            frames: 0,
+            skipped: 0,
+            errors: 0,
+        }
+    }
+
+    /// Feed one character. Returns `true` when it completed a frame.
+    pub fn push(&mut self, event: Event) -> bool {
+        match (self.state, event) {
+            // A frame ends at the next break, or after 512 slots
+            (State::Slots, Event::Break) => {
+                let complete = self.complete();
+                self.state = State::StartCode;
+                complete
+            }
+            (_, Event::Break) => {
+                self.state = State::StartCode;
+                false
+            }
+            (State::WaitBreak | State::Skip, _) => false,
+            (_, Event::Error) => {
+                self.errors = self.errors.wrapping_add(1);
+                self.state = State::WaitBreak;
+                false
+            }
+            (State::StartCode, Event::Byte(NULL_START_CODE)) => {
+                self.len = 0;
+                self.state = State::Slots;
+                false
+            }
+            (State::StartCode, Event::Byte(_)) => {
+                self.skipped = self.skipped.wrapping_add(1);
+                self.state = State::Skip;
+                false
+            }
+            (State::Slots, Event::Byte(value)) => {
+                self.buffers[self.back][self.len] = value;
+                self.len += 1;
+                if self.len < MAX_SLOTS {
+                    return false;
+                }
+                self.state = State::WaitBreak;
+                self.complete()
+            }
+        }
+    }
+
+    /// Swap buffers if the frame has any slots
+    fn complete(&mut self) -> bool {
+        if self.len == 0 {
+            return false;
+        }
+        self.front_len = core::mem::take(&mut self.len);
+        self.back ^= 1;
+        self.frames = self.frames.wrapping_add(1);
+        true
+    }
+
+    /// Slots of the last complete frame, slot 1 first; empty before the
+    /// first one
+    #[inline(always)]
+    pub fn frame(&self) -> &[u8] {
+        &self.buffers[self.back ^ 1][..self.front_len]
+    }
+
+    /// Complete frames, wrapping
+    #[inline(always)]
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Packets with other start codes (RDM, text, ...), wrapping
+    #[inline(always)]
+    pub fn skipped(&self) -> u32 {
+        self.skipped
+    }
+
+    /// Frames lost to line errors, wrapping
+    #[inline(always)]
+    pub fn errors(&self) -> u32 {
+        self.errors
+    }
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
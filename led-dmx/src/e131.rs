@@ -0,0 +1,126 @@
+//! E1.31 (Streaming ACN) data and universe synchronisation packets.
+
+use crate::{DEFAULT_PRIORITY, Dmx, Packet, ParseError, Protocol, be16};
+
+/// UDP port receivers listen on
+pub const PORT: u16 = 5568;
+
+/// ACN packet identifier, after the preamble and post-amble sizes
+pub const IDENTIFIER: [u8; 12] = *b"ASC-E1.17\0\0\0";
+
+const VECTOR_ROOT_DATA: u32 = 0x0000_0004;
+const VECTOR_ROOT_EXTENDED: u32 = 0x0000_0008;
+const VECTOR_FRAMING_DATA: u32 = 0x0000_0002;
+const VECTOR_EXTENDED_SYNC: u32 = 0x0000_0001;
+const VECTOR_DMP_SET_PROPERTY: u8 = 0x02;
+const DMP_ADDRESS_TYPE: u8 = 0xA1;
+
+// Field offsets
+const ROOT_VECTOR: usize = 18;
+const CID: usize = 22;
+const FRAMING_VECTOR: usize = 40;
+const PRIORITY: usize = 108;
+const SYNC_ADDRESS: usize = 109;
+const SEQUENCE: usize = 111;
+const OPTIONS: usize = 112;
+const UNIVERSE: usize = 113;
+const DMP_VECTOR: usize = 117;
+const ADDRESS_TYPE: usize = 118;
+const PROPERTY_COUNT: usize = 123;
+const PROPERTY_VALUES: usize = 125;
+// Synchronisation packets have their own, shorter framing layer: sequence
+// at 44 (not checked, a late sync only shows what is already held), then
+// the sync address
+const SYNC_UNIVERSE: usize = 45;
+const SYNC_LEN: usize = 49;
+
+// Options bits
+const PREVIEW: u8 = 0x80;
+const TERMINATED: u8 = 0x40;
+
+/// Multicast group a universe is sent to, 239.255.<hi>.<lo>
+pub const fn multicast_address(universe: u16) -> [u8; 4] {
+    let [hi, lo] = universe.to_be_bytes();
+    [239, 255, hi, lo]
+}
+
+pub fn parse(bytes: &[u8]) -> Result<Packet<'_>, ParseError> {
+    match bytes.get(4..16) {
+        Some(identifier) if identifier == IDENTIFIER => {}
+        Some(_) => return Err(ParseError::UnknownProtocol),
+        None => return Err(ParseError::TooShort),
+    }
+    if bytes.len() < CID {
+        return Err(ParseError::TooShort);
+    }
+
+    match be32(bytes, ROOT_VECTOR) {
+        VECTOR_ROOT_DATA => data(bytes),
+        VECTOR_ROOT_EXTENDED => sync(bytes),
+        _ => Err(ParseError::Unsupported),
+    }
+}
+
+fn data(bytes: &[u8]) -> Result<Packet<'_>, ParseError> {
+    if bytes.len() < PROPERTY_VALUES + 1 {
+        return Err(ParseError::TooShort);
+    }
+    if be32(bytes, FRAMING_VECTOR) != VECTOR_FRAMING_DATA
+        || bytes[DMP_VECTOR] != VECTOR_DMP_SET_PROPERTY
+        || bytes[ADDRESS_TYPE] != DMP_ADDRESS_TYPE
+    {
+        return Err(ParseError::Malformed);
+    }
+
+    // Start code plus up to 512 slots
+    let count = be16(bytes, PROPERTY_COUNT) as usize;
+    let end = PROPERTY_VALUES + count;
+    if count == 0 || count > 513 || end > bytes.len() {
+        return Err(ParseError::Malformed);
+    }
+
+    let universe = be16(bytes, UNIVERSE);
+    if universe == 0 {
+        return Err(ParseError::Malformed);
+    }
+
+    let options = bytes[OPTIONS];
+    let mut source = [0; 16];
+    source.copy_from_slice(&bytes[CID..CID + 16]);
+    Ok(Packet::Dmx(Dmx {
+        protocol: Protocol::E131,
+        universe,
+        source,
+        priority: match bytes[PRIORITY] {
+            // Out of range priorities are treated as the default
+            p @ 0..=200 => p,
+            _ => DEFAULT_PRIORITY,
+        },
+        sequence: bytes[SEQUENCE],
+        sync_address: be16(bytes, SYNC_ADDRESS),
+        preview: options & PREVIEW != 0,
+        terminated: options & TERMINATED != 0,
+        start_code: bytes[PROPERTY_VALUES],
+        data: &bytes[PROPERTY_VALUES + 1..end],
+    }))
+}
+
+fn sync(bytes: &[u8]) -> Result<Packet<'_>, ParseError> {
+    if bytes.len() < SYNC_LEN {
+        return Err(ParseError::TooShort);
+    }
+    if be32(bytes, FRAMING_VECTOR) != VECTOR_EXTENDED_SYNC {
+        // Universe discovery shares the root vector
+        return Err(ParseError::Unsupported);
+    }
+
+    Ok(Packet::Sync {
+        protocol: Protocol::E131,
+        address: be16(bytes, SYNC_UNIVERSE),
+    })
+}
+
+#[inline(always)]
+fn be32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_be_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
@@ -0,0 +1,112 @@
+//! DMX input, over the network or a DMX512 line, without allocation
+//! (no_std).
+//!
+//! - [`e131`] and [`artnet`] turn UDP payloads into [`Packet`]s
+//! - [`dmx512`] assembles frames from UART characters
+//! - [`Receiver`] picks which source drives each universe (priority,
+//!   sequence, timeouts) and when to show (synchronisation)
+//! - [`Mapping`] says where a universe's slots land on the LED channels
+//!
+//! ```text
+//! let packet = led_dmx::parse(payload)?;
+//! match receiver.receive(&packet, now_ms) {
+//!     Received::Data { universe, data, show } => {
+//!         for span in receiver.mapping.spans(universe, data) { /* copy */ }
+//!     }
+//!     Received::Show => { /* refresh */ }
+//!     Received::Ignored(_) => {}
+//! }
+//! ```
+
+#![no_std]
+
+pub mod artnet;
+pub mod dmx512;
+pub mod e131;
+mod mapping;
+mod receiver;
+
+pub use mapping::{ChannelMap, Mapping, PIXELS_PER_UNIVERSE, SLOTS, Span};
+pub use receiver::{
+    ARTNET_SYNC_TIMEOUT_MS, E131_SYNC_TIMEOUT_MS, Ignored, Received, Receiver, SOURCE_TIMEOUT_MS,
+};
+
+/// Priority of sources that don't send one (Art-Net, E1.31 default)
+pub const DEFAULT_PRIORITY: u8 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Protocol {
+    E131,
+    ArtNet,
+}
+
+/// Levels for one universe
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dmx<'a> {
+    pub protocol: Protocol,
+    /// E1.31 universe (1-63999) or Art-Net port-address (0-32767)
+    pub universe: u16,
+    /// E1.31 component id; all zeros for Art-Net
+    pub source: [u8; 16],
+    /// 0-200, higher wins
+    pub priority: u8,
+    /// 0 = not sequenced (Art-Net only)
+    pub sequence: u8,
+    /// Universe of the sync packets this data waits for, 0 = show at once
+    pub sync_address: u16,
+    /// For visualisers, not for output
+    pub preview: bool,
+    /// Last packet from this source for this universe
+    pub terminated: bool,
+    /// 0x00 for levels; other start codes carry something else
+    pub start_code: u8,
+    /// Slot values, slot 1 first
+    pub data: &'a [u8],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Packet<'a> {
+    Dmx(Dmx<'a>),
+    /// Show everything held for `address` (E1.31) or since the last sync
+    /// (Art-Net, where `address` is 0)
+    Sync {
+        protocol: Protocol,
+        address: u16,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    TooShort,
+    /// Neither an E1.31 nor an Art-Net header
+    UnknownProtocol,
+    /// Valid header, but a packet type we don't handle (e.g. ArtPoll)
+    Unsupported,
+    /// Lengths or fixed fields don't add up
+    Malformed,
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooShort => write!(f, "packet too short"),
+            Self::UnknownProtocol => write!(f, "not E1.31 or Art-Net"),
+            Self::Unsupported => write!(f, "unsupported packet type"),
+            Self::Malformed => write!(f, "malformed packet"),
+        }
+    }
+}
+
+/// Parse a UDP payload from either protocol
+pub fn parse(bytes: &[u8]) -> Result<Packet<'_>, ParseError> {
+    if bytes.starts_with(&artnet::ID) {
+        artnet::parse(bytes)
+    } else {
+        e131::parse(bytes)
+    }
+}
+
+#[inline(always)]
+fn be16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_be_bytes([bytes[at], bytes[at + 1]])
+}
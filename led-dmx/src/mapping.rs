@@ -0,0 +1,119 @@
+/// Slots in a DMX universe
+pub const SLOTS: u16 = 512;
+
+/// Whole RGB pixels in a universe; the last 2 slots stay unused so pixels
+/// never straddle two universes
+pub const PIXELS_PER_UNIVERSE: u16 = SLOTS / 3;
+
+/// Where one LED channel's pixels come from.
+///
+/// The channel starts at `address` (1-512) in `universe` with three slots
+/// (R, G, B) per pixel. Pixels that don't fit continue at address 1 of the
+/// following universes, 170 to a universe, the usual layout for long strips.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChannelMap {
+    pub universe: u16,
+    pub address: u16,
+    pub pixels: u16,
+}
+
+impl ChannelMap {
+    #[inline(always)]
+    fn first_slot(&self) -> u16 {
+        self.address.clamp(1, SLOTS) - 1
+    }
+
+    /// Pixels in the first universe
+    #[inline(always)]
+    fn first_pixels(&self) -> u16 {
+        ((SLOTS - self.first_slot()) / 3).min(self.pixels)
+    }
+
+    /// Last universe the channel uses
+    pub fn last_universe(&self) -> u16 {
+        let rest = self.pixels - self.first_pixels();
+        self.universe
+            .saturating_add(rest.div_ceil(PIXELS_PER_UNIVERSE))
+    }
+
+    pub fn contains(&self, universe: u16) -> bool {
+        (self.universe..=self.last_universe()).contains(&universe)
+    }
+
+    /// First pixel fed by `universe` and its slots, whole pixels only
+    fn span<'a>(&self, universe: u16, data: &'a [u8]) -> Option<(usize, &'a [u8])> {
+        if !self.contains(universe) {
+            return None;
+        }
+
+        let (pixel, slot, count) = if universe == self.universe {
+            (0, self.first_slot(), self.first_pixels())
+        } else {
+            let pixel = self.first_pixels() + (universe - self.universe - 1) * PIXELS_PER_UNIVERSE;
+            (pixel, 0, PIXELS_PER_UNIVERSE.min(self.pixels - pixel))
+        };
+
+        // Senders may stop short of the full universe
+        let slots = data.get(slot as usize..)?;
+        let count = (count as usize).min(slots.len() / 3);
+        (count > 0).then(|| (pixel as usize, &slots[..count * 3]))
+    }
+}
+
+/// RGB slot values for `data.len() / 3` pixels of `channel` from `pixel` on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span<'a> {
+    pub channel: u8,
+    pub pixel: usize,
+    pub data: &'a [u8],
+}
+
+/// Universe layout of up to `C` LED channels; `None` channels don't listen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mapping<const C: usize> {
+    pub channels: [Option<ChannelMap>; C],
+}
+
+impl<const C: usize> Mapping<C> {
+    pub const fn new(channels: [Option<ChannelMap>; C]) -> Self {
+        Self { channels }
+    }
+
+    /// Whether any channel listens to `universe`
+    pub fn contains(&self, universe: u16) -> bool {
+        self.channels
+            .iter()
+            .flatten()
+            .any(|map| map.contains(universe))
+    }
+
+    /// Every universe some channel listens to, possibly more than once
+    pub fn universes(&self) -> impl Iterator<Item = u16> + '_ {
+        self.channels
+            .iter()
+            .flatten()
+            .flat_map(|map| map.universe..=map.last_universe())
+    }
+
+    /// Where the slots of one universe go, channel by channel
+    pub fn spans<'a, 'd>(
+        &'a self,
+        universe: u16,
+        data: &'d [u8],
+    ) -> impl Iterator<Item = Span<'d>> + 'a
+    where
+        'd: 'a,
+    {
+        self.channels
+            .iter()
+            .enumerate()
+            .filter_map(move |(channel, map)| {
+                let (pixel, data) = map.as_ref()?.span(universe, data)?;
+                Some(Span {
+                    channel: channel as u8,
+                    pixel,
+                    data,
+                })
+            })
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{Dmx, Mapping, Packet, Protocol};
+
+/// A source that sent nothing for this long has gone (E1.31 network data
+/// loss); any other source may take its universe
+pub const SOURCE_TIMEOUT_MS: u64 = 2500;
+
+/// Art-Net data is held for the next ArtSync until ArtSyncs stop for this
+/// long
+pub const ARTNET_SYNC_TIMEOUT_MS: u64 = 4000;
+
+/// E1.31 data with a sync address is held for its sync until syncs to that
+/// address stop for this long (the network data loss timeout)
+pub const E131_SYNC_TIMEOUT_MS: u64 = SOURCE_TIMEOUT_MS;
+
+/// Why [`Receiver::receive`] dropped a packet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ignored {
+    /// No channel listens to the universe
+    Unmapped,
+    Preview,
+    /// Not level data (e.g. per-slot priorities)
+    StartCode,
+    /// Another source with a higher priority, or the same priority and there
+    /// first, drives the universe
+    Priority,
+    /// Older than or a repeat of what was already received
+    OutOfOrder,
+    /// The source stopped sending to the universe
+    Terminated,
+    /// Sync for an address no held data waits for
+    NoHeldData,
+    /// Every source slot is in use
+    Full,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Received<'a> {
+    /// Copy `data` to the channels (see [`Mapping::spans`]). Show it now if
+    /// `show`, otherwise a later [`Received::Show`] does.
+    Data {
+        universe: u16,
+        data: &'a [u8],
+        show: bool,
+    },
+    /// Show all held data
+    Show,
+    Ignored(Ignored),
+}
+
+/// The source currently driving a universe
+#[derive(Clone, Copy, Debug)]
+struct Source {
+    universe: u16,
+    id: [u8; 16],
+    priority: u8,
+    sequence: u8,
+    last_seen: u64,
+}
+
+/// Decides which packets reach the LEDs and when.
+///
+/// Each universe follows one source: the highest priority one, or among
+/// equals the one that was there first, until it terminates or times out.
+/// All Art-Net senders count as one source at the default priority.
+/// `U` is the number of universes tracked at once.
+pub struct Receiver<const C: usize, const U: usize> {
+    pub mapping: Mapping<C>,
+    sources: [Option<Source>; U],
+    /// Sync address the latest E1.31 data asked for
+    sync_address: Option<u16>,
+    /// E1.31 data waits for a sync to `sync_address`
+    held: bool,
+    last_sync: Option<u64>,
+    last_artsync: Option<u64>,
+}
+
+impl<const C: usize, const U: usize> Receiver<C, U> {
+    pub const fn new(mapping: Mapping<C>) -> Self {
+        Self {
+            mapping,
+            sources: [None; U],
+            sync_address: None,
+            held: false,
+            last_sync: None,
+            last_artsync: None,
+        }
+    }
+
+    /// Sync address the latest E1.31 data asked for. Sync packets go to its
+    /// multicast group, which is normally not one of the mapped universes.
+    pub fn sync_address(&self) -> Option<u16> {
+        self.sync_address
+    }
+
+    /// Handle a packet arriving at `now` ms
+    pub fn receive<'a>(&mut self, packet: &Packet<'a>, now: u64) -> Received<'a> {
+        match *packet {
+            Packet::Dmx(dmx) => self.data(dmx, now),
+            Packet::Sync {
+                protocol: Protocol::E131,
+                address,
+            } => {
+                if self.sync_address != Some(address) {
+                    return Received::Ignored(Ignored::NoHeldData);
+                }
+                self.last_sync = Some(now);
+                if core::mem::take(&mut self.held) {
+                    Received::Show
+                } else {
+                    Received::Ignored(Ignored::NoHeldData)
+                }
+            }
+            Packet::Sync {
+                protocol: Protocol::ArtNet,
+                ..
+            } => {
+                self.last_artsync = Some(now);
+                Received::Show
+            }
+        }
+    }
+
+    fn data<'a>(&mut self, dmx: Dmx<'a>, now: u64) -> Received<'a> {
+        if !self.mapping.contains(dmx.universe) {
+            return Received::Ignored(Ignored::Unmapped);
+        }
+        if dmx.preview {
+            return Received::Ignored(Ignored::Preview);
+        }
+        if dmx.start_code != 0 {
+            return Received::Ignored(Ignored::StartCode);
+        }
+        if let Err(reason) = self.arbitrate(&dmx, now) {
+            return Received::Ignored(reason);
+        }
+
+        let show = match dmx.protocol {
+            // Shown at once until its syncs arrive, and again if they stop
+            Protocol::E131 if dmx.sync_address != 0 => {
+                if self.sync_address != Some(dmx.sync_address) {
+                    self.sync_address = Some(dmx.sync_address);
+                    self.last_sync = None;
+                }
+                self.held = self
+                    .last_sync
+                    .is_some_and(|at| now.saturating_sub(at) < E131_SYNC_TIMEOUT_MS);
+                !self.held
+            }
+            Protocol::E131 => {
+                self.sync_address = None;
+                self.held = false;
+                true
+            }
+            Protocol::ArtNet => !self
+                .last_artsync
+                .is_some_and(|at| now.saturating_sub(at) < ARTNET_SYNC_TIMEOUT_MS),
+        };
+        Received::Data {
+            universe: dmx.universe,
+            data: dmx.data,
+            show,
+        }
+    }
+
+    /// Make `dmx`'s sender the universe's source, if it may be
+    fn arbitrate(&mut self, dmx: &Dmx<'_>, now: u64) -> Result<(), Ignored> {
+        let candidate = Source {
+            universe: dmx.universe,
+            id: dmx.source,
+            priority: dmx.priority,
+            sequence: dmx.sequence,
+            last_seen: now,
+        };
+        let existing = self
+            .sources
+            .iter()
+            .enumerate()
+            .find_map(|(i, s)| s.filter(|s| s.universe == dmx.universe).map(|s| (i, s)));
+
+        let Some((index, current)) = existing else {
+            if dmx.terminated {
+                return Err(Ignored::Terminated);
+            }
+            let free = self.sources.iter_mut().find(|s| s.is_none());
+            *free.ok_or(Ignored::Full)? = Some(candidate);
+            return Ok(());
+        };
+
+        let slot = &mut self.sources[index];
+        let timed_out = now.saturating_sub(current.last_seen) > SOURCE_TIMEOUT_MS;
+
+        if current.id == dmx.source && !timed_out {
+            if dmx.terminated {
+                *slot = None;
+                return Err(Ignored::Terminated);
+            }
+            // E1.31 6.7.2: a step back of up to 20 is a late packet. Art-Net
+            // sequence 0 means the sender doesn't number its packets.
+            let step = dmx.sequence.wrapping_sub(current.sequence) as i8;
+            let unnumbered = dmx.protocol == Protocol::ArtNet && dmx.sequence == 0;
+            if !unnumbered && step <= 0 && step > -20 {
+                return Err(Ignored::OutOfOrder);
+            }
+            *slot = Some(candidate);
+            Ok(())
+        } else if dmx.terminated {
+            Err(Ignored::Terminated)
+        } else if timed_out || dmx.priority > current.priority {
+            *slot = Some(candidate);
+            Ok(())
+        } else {
+            Err(Ignored::Priority)
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! DMX512 frame assembly from UART events.
+
+use led_dmx::dmx512::{Decoder, Event, MAX_SLOTS, RDM_START_CODE};
+use led_effects::{DmxSlot, DmxUniverse, Parameter, Timestamp};
+
+/// Events for a break, a start code and `slots`
+fn packet(start_code: u8, slots: &[u8]) -> Vec<Event> {
+    let mut events = vec![Event::Break, Event::Byte(start_code)];
+    events.extend(slots.iter().map(|&slot| Event::Byte(slot)));
+    events
+}
+
+/// Feed `events`, counting completed frames
+fn feed(decoder: &mut Decoder, events: &[Event]) -> usize {
+    events.iter().filter(|&&event| decoder.push(event)).count()
+}
+
+#[test]
+fn full_frame_completes_without_waiting_for_the_break() {
+    let slots: Vec<u8> = (0..MAX_SLOTS).map(|i| i as u8).collect();
+    let mut decoder = Decoder::new();
+
+    assert_eq!(feed(&mut decoder, &packet(0, &slots)), 1);
+    assert_eq!(decoder.frame(), slots);
+
+    // The break that follows doesn't count it twice
+    assert!(!decoder.push(Event::Break));
+    assert_eq!(decoder.frames(), 1);
+}
+
+#[test]
+fn short_frames_complete_at_the_next_break() {
+    let mut decoder = Decoder::new();
+    assert!(decoder.frame().is_empty());
+
+    assert_eq!(feed(&mut decoder, &packet(0, &[10, 20, 30])), 0);
+    assert!(decoder.push(Event::Break));
+    assert_eq!(decoder.frame(), [10, 20, 30]);
+}
+
+#[test]
+fn last_frame_stays_put_while_the_next_one_arrives() {
+    let mut decoder = Decoder::new();
+    feed(&mut decoder, &packet(0, &[1; 24]));
+    feed(&mut decoder, &packet(0, &[2; 24]));
+    assert_eq!(decoder.frame(), [1; 24]);
+
+    // Half of the third frame
+    feed(&mut decoder, &packet(0, &[3; 12]));
+    assert_eq!(decoder.frame(), [2; 24]);
+    assert_eq!(decoder.frames(), 2);
+}
+
+#[test]
+fn other_start_codes_are_skipped() {
+    let mut decoder = Decoder::new();
+    feed(&mut decoder, &packet(0, &[7; 8]));
+    feed(
+        &mut decoder,
+        &packet(RDM_START_CODE, &[0x01, 0x18, 0xAA, 0xBB]),
+    );
+    feed(&mut decoder, &packet(0x17, b"text packet"));
+    feed(&mut decoder, &[Event::Break]);
+
+    assert_eq!(decoder.frames(), 1);
+    assert_eq!(decoder.skipped(), 2);
+    assert_eq!(decoder.frame(), [7; 8]);
+}
+
+#[test]
+fn line_errors_drop_the_frame_until_the_next_break() {
+    let mut decoder = Decoder::new();
+
+    // Powered up mid-frame: nothing until a break
+    assert_eq!(feed(&mut decoder, &[Event::Byte(0), Event::Byte(9)]), 0);
+
+    let mut events = packet(0, &[5; 6]);
+    events.push(Event::Error);
+    events.extend(packet(0, &[6; 6]));
+    events.push(Event::Break);
+    assert_eq!(feed(&mut decoder, &events), 1);
+    assert_eq!(decoder.frame(), [6; 6]);
+    assert_eq!(decoder.errors(), 1);
+
+    // Breaks without slots between them aren't frames
+    assert_eq!(
+        feed(
+            &mut decoder,
+            &[Event::Break, Event::Break, Event::Byte(0), Event::Break]
+        ),
+        0
+    );
+}
+
+#[test]
+fn frames_feed_effect_parameters() {
+    static UNIVERSE: DmxUniverse = DmxUniverse::new();
+    let dimmer = DmxSlot {
+        universe: &UNIVERSE,
+        address: 1,
+    };
+    let effect = DmxSlot {
+        universe: &UNIVERSE,
+        address: 2,
+    };
+
+    let mut decoder = Decoder::new();
+    let mut events = packet(0, &[200, 3, 0, 0]);
+    events.push(Event::Break);
+    for event in events {
+        if decoder.push(event) {
+            UNIVERSE.publish(decoder.frame());
+        }
+    }
+
+    assert_eq!(UNIVERSE.frames(), 1);
+    assert_eq!(Parameter::<u8>::sample(&dimmer, Timestamp::ZERO), 200);
+    assert_eq!(Parameter::<u8>::sample(&effect, Timestamp::ZERO), 3);
+    assert_eq!(UNIVERSE.slot(0), 0);
+    assert_eq!(UNIVERSE.slot(513), 0);
+}
+
+#[test]
+fn shorter_frame_clears_the_slots_past_its_end() {
+    let universe = DmxUniverse::new();
+    universe.publish(&[10, 20, 30, 40]);
+    universe.publish(&[50, 60]);
+
+    assert_eq!(universe.frames(), 2);
+    assert_eq!(universe.slot(1), 50);
+    assert_eq!(universe.slot(2), 60);
+    assert_eq!(universe.slot(3), 0);
+    assert_eq!(universe.slot(4), 0);
+}
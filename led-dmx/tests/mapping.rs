@@ -0,0 +1,92 @@
+//! Universe to channel mapping.
+
+use led_dmx::{ChannelMap, Mapping, Span};
+
+fn slots(count: usize) -> Vec<u8> {
+    (0..count).map(|i| i as u8).collect()
+}
+
+#[test]
+fn channels_share_a_universe_by_address() {
+    let mapping = Mapping::new([
+        Some(ChannelMap {
+            universe: 1,
+            address: 1,
+            pixels: 2,
+        }),
+        Some(ChannelMap {
+            universe: 1,
+            address: 7,
+            pixels: 2,
+        }),
+        None,
+    ]);
+    let data = slots(12);
+
+    let spans: Vec<Span> = mapping.spans(1, &data).collect();
+    assert_eq!(
+        spans,
+        [
+            Span {
+                channel: 0,
+                pixel: 0,
+                data: &data[0..6]
+            },
+            Span {
+                channel: 1,
+                pixel: 0,
+                data: &data[6..12]
+            },
+        ]
+    );
+    assert_eq!(mapping.spans(2, &data).count(), 0);
+}
+
+#[test]
+fn long_channels_continue_in_the_next_universes() {
+    // 100 pixels from address 301: 70 fit, then 30 in universe 11
+    let map = ChannelMap {
+        universe: 10,
+        address: 301,
+        pixels: 100,
+    };
+    let mapping = Mapping::new([Some(map)]);
+    assert_eq!(map.last_universe(), 11);
+    assert_eq!(mapping.universes().collect::<Vec<_>>(), [10, 11]);
+
+    let data = slots(512);
+    let first: Vec<Span> = mapping.spans(10, &data).collect();
+    assert_eq!(first[0].pixel, 0);
+    assert_eq!(first[0].data, &data[300..510]);
+
+    let second: Vec<Span> = mapping.spans(11, &data).collect();
+    assert_eq!(second[0].pixel, 70);
+    assert_eq!(second[0].data, &data[..90]);
+
+    // 400 pixels need three universes, the last one partly
+    let map = ChannelMap {
+        universe: 1,
+        address: 1,
+        pixels: 400,
+    };
+    assert_eq!(map.last_universe(), 3);
+    let spans: Vec<Span> = Mapping::new([Some(map)]).spans(3, &data).collect();
+    assert_eq!((spans[0].pixel, spans[0].data.len()), (340, 60 * 3));
+}
+
+#[test]
+fn short_universes_fill_whole_pixels_only() {
+    let mapping = Mapping::new([Some(ChannelMap {
+        universe: 1,
+        address: 4,
+        pixels: 10,
+    })]);
+
+    // Slots 4-11 reach 2 whole pixels and part of a third
+    let data = slots(11);
+    let spans: Vec<Span> = mapping.spans(1, &data).collect();
+    assert_eq!(spans[0].data, &data[3..9]);
+
+    // Nothing at all past the end
+    assert_eq!(mapping.spans(1, &data[..3]).count(), 0);
+}
@@ -0,0 +1,114 @@
+//! Parsing reference packets. The `.hex` files under `tests/packets` hold
+//! what a desk sends, field by field as laid out in E1.31-2016 and the
+//! Art-Net 4 specification.
+
+use led_dmx::{DEFAULT_PRIORITY, Packet, ParseError, Protocol, artnet, e131, parse};
+
+/// Hex bytes, `#` starts a comment
+fn packet(hex: &str) -> Vec<u8> {
+    hex.lines()
+        .map(|line| line.split('#').next().unwrap())
+        .flat_map(str::split_ascii_whitespace)
+        .map(|byte| u8::from_str_radix(byte, 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn e131_data() {
+    let bytes = packet(include_str!("packets/e131_data.hex"));
+    let Ok(Packet::Dmx(dmx)) = parse(&bytes) else {
+        panic!("not a data packet");
+    };
+
+    assert_eq!(dmx.protocol, Protocol::E131);
+    assert_eq!(dmx.universe, 1);
+    assert_eq!(dmx.priority, DEFAULT_PRIORITY);
+    assert_eq!(dmx.sequence, 42);
+    assert_eq!(dmx.sync_address, 0);
+    assert_eq!(dmx.source[..4], [0x5c, 0x1f, 0xa3, 0x1e]);
+    assert!(!dmx.preview && !dmx.terminated);
+    assert_eq!(dmx.start_code, 0);
+    assert_eq!(dmx.data.len(), 510);
+    assert_eq!(dmx.data[..6], [0, 255, 0, 1, 254, 3]);
+}
+
+#[test]
+fn e131_synchronised_data_and_sync() {
+    let bytes = packet(include_str!("packets/e131_data_synced.hex"));
+    let Ok(Packet::Dmx(dmx)) = parse(&bytes) else {
+        panic!("not a data packet");
+    };
+    assert_eq!(
+        (dmx.universe, dmx.priority, dmx.sync_address),
+        (2, 150, 7000)
+    );
+    assert_eq!(
+        dmx.data,
+        [10, 20, 30, 40, 50, 60, 70, 80, 90, 100, 110, 120]
+    );
+
+    let bytes = packet(include_str!("packets/e131_sync.hex"));
+    assert_eq!(
+        parse(&bytes),
+        Ok(Packet::Sync {
+            protocol: Protocol::E131,
+            address: 7000
+        })
+    );
+}
+
+#[test]
+fn artnet_dmx_and_sync() {
+    let bytes = packet(include_str!("packets/artnet_dmx.hex"));
+    let Ok(Packet::Dmx(dmx)) = parse(&bytes) else {
+        panic!("not ArtDmx");
+    };
+    assert_eq!(dmx.protocol, Protocol::ArtNet);
+    assert_eq!(dmx.universe, 0x0123);
+    assert_eq!(dmx.sequence, 200);
+    assert_eq!(dmx.data, [255, 128, 0, 0, 64, 255]);
+
+    let bytes = packet(include_str!("packets/artnet_sync.hex"));
+    assert_eq!(
+        parse(&bytes),
+        Ok(Packet::Sync {
+            protocol: Protocol::ArtNet,
+            address: 0
+        })
+    );
+
+    let bytes = packet(include_str!("packets/artnet_poll.hex"));
+    assert_eq!(parse(&bytes), Err(ParseError::Unsupported));
+}
+
+#[test]
+fn rejects_damaged_packets() {
+    let e131 = packet(include_str!("packets/e131_data.hex"));
+    let artnet = packet(include_str!("packets/artnet_dmx.hex"));
+
+    // Cut short in the header, and in the slots the count promises
+    assert_eq!(e131::parse(&e131[..100]), Err(ParseError::TooShort));
+    assert_eq!(parse(&e131[..e131.len() - 1]), Err(ParseError::Malformed));
+    assert_eq!(artnet::parse(&artnet[..12]), Err(ParseError::TooShort));
+
+    // Odd ArtDmx length
+    let mut odd = artnet.clone();
+    odd[17] = 5;
+    assert_eq!(parse(&odd), Err(ParseError::Malformed));
+
+    // Wrong DMP address type
+    let mut wrong = e131.clone();
+    wrong[118] = 0xA2;
+    assert_eq!(parse(&wrong), Err(ParseError::Malformed));
+
+    assert_eq!(
+        parse(b"GET / HTTP/1.1\r\n\r\n"),
+        Err(ParseError::UnknownProtocol)
+    );
+}
+
+#[test]
+fn multicast_groups() {
+    assert_eq!(e131::multicast_address(1), [239, 255, 0, 1]);
+    assert_eq!(e131::multicast_address(7000), [239, 255, 0x1B, 0x58]);
+}
@@ -0,0 +1,207 @@
+//! Source arbitration and synchronisation.
+
+use led_dmx::{
+    ARTNET_SYNC_TIMEOUT_MS, ChannelMap, Dmx, E131_SYNC_TIMEOUT_MS, Ignored, Mapping, Packet,
+    Protocol, Received, Receiver, SOURCE_TIMEOUT_MS,
+};
+
+const DATA: [u8; 6] = [1, 2, 3, 4, 5, 6];
+
+fn receiver() -> Receiver<1, 2> {
+    Receiver::new(Mapping::new([Some(ChannelMap {
+        universe: 1,
+        address: 1,
+        pixels: 2,
+    })]))
+}
+
+fn e131(source: u8, priority: u8, sequence: u8) -> Dmx<'static> {
+    Dmx {
+        protocol: Protocol::E131,
+        universe: 1,
+        source: [source; 16],
+        priority,
+        sequence,
+        sync_address: 0,
+        preview: false,
+        terminated: false,
+        start_code: 0,
+        data: &DATA,
+    }
+}
+
+fn receive(receiver: &mut Receiver<1, 2>, dmx: Dmx<'static>, now: u64) -> Received<'static> {
+    receiver.receive(&Packet::Dmx(dmx), now)
+}
+
+const SHOWN: Received = Received::Data {
+    universe: 1,
+    data: &DATA,
+    show: true,
+};
+
+#[test]
+fn highest_priority_source_wins() {
+    let mut rx = receiver();
+    assert_eq!(receive(&mut rx, e131(1, 100, 1), 0), SHOWN);
+
+    // Equal priority: the first source keeps the universe
+    assert_eq!(
+        receive(&mut rx, e131(2, 100, 1), 10),
+        Received::Ignored(Ignored::Priority)
+    );
+    // Higher priority takes over, and then the old source is the lower one
+    assert_eq!(receive(&mut rx, e131(3, 150, 1), 20), SHOWN);
+    assert_eq!(
+        receive(&mut rx, e131(1, 100, 2), 30),
+        Received::Ignored(Ignored::Priority)
+    );
+
+    // Once the winner goes quiet anyone may have it
+    let later = 20 + SOURCE_TIMEOUT_MS + 1;
+    assert_eq!(receive(&mut rx, e131(1, 100, 3), later), SHOWN);
+}
+
+#[test]
+fn late_packets_are_dropped() {
+    let mut rx = receiver();
+    assert_eq!(receive(&mut rx, e131(1, 100, 250), 0), SHOWN);
+    assert_eq!(
+        receive(&mut rx, e131(1, 100, 250), 1),
+        Received::Ignored(Ignored::OutOfOrder)
+    );
+    assert_eq!(
+        receive(&mut rx, e131(1, 100, 240), 2),
+        Received::Ignored(Ignored::OutOfOrder)
+    );
+    // Wrapping around is progress, a big jump back is a restarted source
+    assert_eq!(receive(&mut rx, e131(1, 100, 3), 3), SHOWN);
+    assert_eq!(receive(&mut rx, e131(1, 100, 100), 4), SHOWN);
+    assert_eq!(receive(&mut rx, e131(1, 100, 50), 5), SHOWN);
+}
+
+#[test]
+fn sequence_zero_is_only_special_for_artnet() {
+    // E1.31 wraps through 0 like any other number
+    let mut rx = receiver();
+    assert_eq!(receive(&mut rx, e131(1, 100, 0), 0), SHOWN);
+    assert_eq!(
+        receive(&mut rx, e131(1, 100, 0), 1),
+        Received::Ignored(Ignored::OutOfOrder)
+    );
+
+    // Art-Net senders without sequencing send 0 throughout
+    let mut rx = receiver();
+    let mut dmx = e131(0, 100, 0);
+    dmx.protocol = Protocol::ArtNet;
+    assert_eq!(receive(&mut rx, dmx, 0), SHOWN);
+    assert_eq!(receive(&mut rx, dmx, 1), SHOWN);
+}
+
+#[test]
+fn terminated_stream_frees_the_universe() {
+    let mut rx = receiver();
+    assert_eq!(receive(&mut rx, e131(1, 200, 1), 0), SHOWN);
+
+    let mut last = e131(1, 200, 2);
+    last.terminated = true;
+    assert_eq!(
+        receive(&mut rx, last, 10),
+        Received::Ignored(Ignored::Terminated)
+    );
+    assert_eq!(receive(&mut rx, e131(2, 10, 1), 20), SHOWN);
+}
+
+#[test]
+fn filters_what_is_not_for_output() {
+    let mut rx = receiver();
+
+    let mut other = e131(1, 100, 1);
+    other.universe = 2;
+    assert_eq!(
+        receive(&mut rx, other, 0),
+        Received::Ignored(Ignored::Unmapped)
+    );
+
+    let mut preview = e131(1, 100, 1);
+    preview.preview = true;
+    assert_eq!(
+        receive(&mut rx, preview, 0),
+        Received::Ignored(Ignored::Preview)
+    );
+
+    let mut priorities = e131(1, 100, 1);
+    priorities.start_code = 0xDD;
+    assert_eq!(
+        receive(&mut rx, priorities, 0),
+        Received::Ignored(Ignored::StartCode)
+    );
+}
+
+#[test]
+fn e131_data_waits_for_its_sync_while_syncs_arrive() {
+    let mut rx = receiver();
+    let synced = |sequence| {
+        let mut dmx = e131(1, 100, sequence);
+        dmx.sync_address = 7000;
+        dmx
+    };
+    let sync = |address| Packet::Sync {
+        protocol: Protocol::E131,
+        address,
+    };
+    const HELD: Received = Received::Data {
+        universe: 1,
+        data: &DATA,
+        show: false,
+    };
+
+    // No sync seen yet: shown at once, and the address is there to join
+    assert_eq!(receive(&mut rx, synced(1), 0), SHOWN);
+    assert_eq!(rx.sync_address(), Some(7000));
+    assert_eq!(
+        rx.receive(&sync(7001), 1),
+        Received::Ignored(Ignored::NoHeldData)
+    );
+    assert_eq!(
+        rx.receive(&sync(7000), 2),
+        Received::Ignored(Ignored::NoHeldData)
+    );
+
+    // Syncs arrive, so data waits for them
+    assert_eq!(receive(&mut rx, synced(2), 10), HELD);
+    assert_eq!(rx.receive(&sync(7000), 12), Received::Show);
+    assert_eq!(
+        rx.receive(&sync(7000), 13),
+        Received::Ignored(Ignored::NoHeldData)
+    );
+
+    // Syncs stopped: back to showing at once
+    assert_eq!(
+        receive(&mut rx, synced(3), 13 + E131_SYNC_TIMEOUT_MS),
+        SHOWN
+    );
+}
+
+#[test]
+fn artsync_switches_to_synchronous_output_until_it_stops() {
+    let mut rx = receiver();
+    let mut dmx = e131(0, 100, 0);
+    dmx.protocol = Protocol::ArtNet;
+    let artsync = Packet::Sync {
+        protocol: Protocol::ArtNet,
+        address: 0,
+    };
+
+    assert_eq!(receive(&mut rx, dmx, 0), SHOWN);
+    assert_eq!(rx.receive(&artsync, 10), Received::Show);
+    assert_eq!(
+        receive(&mut rx, dmx, 20),
+        Received::Data {
+            universe: 1,
+            data: &DATA,
+            show: false
+        }
+    );
+    assert_eq!(receive(&mut rx, dmx, 10 + ARTNET_SYNC_TIMEOUT_MS), SHOWN);
+}
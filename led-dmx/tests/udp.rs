@@ -0,0 +1,159 @@
+//! A desk stand-in sending E1.31 and Art-Net over local UDP to a receiver
+//! doing what the firmware does: parse, arbitrate, copy spans and show.
+
+use led_dmx::{ChannelMap, Ignored, Mapping, Received, Receiver, parse};
+use std::net::UdpSocket;
+use std::thread;
+use std::time::Duration;
+
+const LONG: usize = 200; // Channel 0 spans universes 1 and 2
+const SHORT: usize = 4; // Channel 1 on Art-Net port-address 0x10
+
+type Pixels = [Vec<[u8; 3]>; 2];
+
+fn e131(universe: u16, source: u8, priority: u8, sequence: u8, sync: u16, slots: &[u8]) -> Vec<u8> {
+    let flags = |len: usize| (0x7000 | len as u16).to_be_bytes();
+    let total = 126 + slots.len();
+
+    let mut p = vec![0x00, 0x10, 0x00, 0x00];
+    p.extend(b"ASC-E1.17\0\0\0");
+    p.extend(flags(total - 16));
+    p.extend(4u32.to_be_bytes());
+    p.extend([source; 16]);
+    p.extend(flags(total - 38));
+    p.extend(2u32.to_be_bytes());
+    p.extend([0; 64]);
+    p.push(priority);
+    p.extend(sync.to_be_bytes());
+    p.push(sequence);
+    p.push(if slots.is_empty() { 0x40 } else { 0x00 }); // Terminate when empty
+    p.extend(universe.to_be_bytes());
+    p.extend(flags(total - 115));
+    p.extend([0x02, 0xA1, 0x00, 0x00, 0x00, 0x01]);
+    p.extend((slots.len() as u16 + 1).to_be_bytes());
+    p.push(0x00);
+    p.extend(slots);
+    p
+}
+
+fn e131_sync(address: u16) -> Vec<u8> {
+    let mut p = vec![0x00, 0x10, 0x00, 0x00];
+    p.extend(b"ASC-E1.17\0\0\0");
+    p.extend([0x70, 33]);
+    p.extend(8u32.to_be_bytes());
+    p.extend([1; 16]);
+    p.extend([0x70, 11]);
+    p.extend(1u32.to_be_bytes());
+    p.push(0);
+    p.extend(address.to_be_bytes());
+    p.extend([0, 0]);
+    p
+}
+
+fn artdmx(universe: u16, slots: &[u8]) -> Vec<u8> {
+    let mut p = b"Art-Net\0".to_vec();
+    p.extend([0x00, 0x50, 0, 14, 0, 0]);
+    p.extend([universe as u8, (universe >> 8) as u8]);
+    p.extend((slots.len() as u16).to_be_bytes());
+    p.extend(slots);
+    p
+}
+
+fn rgb(pixels: usize, seed: u8) -> Vec<u8> {
+    (0..pixels * 3)
+        .map(|i| (i as u8).wrapping_mul(seed))
+        .collect()
+}
+
+/// Receive until the stream for universe 1 is terminated; returns what was
+/// on the strips at each show
+fn device(socket: UdpSocket, mapping: Mapping<2>) -> Vec<Pixels> {
+    let mut receiver = Receiver::<2, 4>::new(mapping);
+    let mut pixels: Pixels = [vec![[0; 3]; LONG], vec![[0; 3]; SHORT]];
+    let mut shown = Vec::new();
+    let mut buf = [0; 1500];
+
+    loop {
+        let (len, _) = socket.recv_from(&mut buf).expect("sender went quiet");
+        let Ok(packet) = parse(&buf[..len]) else {
+            continue;
+        };
+        match receiver.receive(&packet, 0) {
+            Received::Data {
+                universe,
+                data,
+                show,
+            } => {
+                for span in receiver.mapping.spans(universe, data) {
+                    let target = &mut pixels[span.channel as usize][span.pixel..];
+                    for (pixel, rgb) in target.iter_mut().zip(span.data.as_chunks::<3>().0) {
+                        *pixel = *rgb;
+                    }
+                }
+                if show {
+                    shown.push(pixels.clone());
+                }
+            }
+            Received::Show => shown.push(pixels.clone()),
+            Received::Ignored(Ignored::Terminated) => return shown,
+            Received::Ignored(_) => {}
+        }
+    }
+}
+
+#[test]
+fn desk_drives_two_channels_over_udp() {
+    let mapping = Mapping::new([
+        Some(ChannelMap {
+            universe: 1,
+            address: 1,
+            pixels: LONG as u16,
+        }),
+        Some(ChannelMap {
+            universe: 0x10,
+            address: 1,
+            pixels: SHORT as u16,
+        }),
+    ]);
+
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let address = socket.local_addr().unwrap();
+    let device = thread::spawn(move || device(socket, mapping));
+
+    let long = rgb(LONG, 7);
+    let short = rgb(SHORT, 13);
+    let (first, rest) = long.split_at(170 * 3);
+
+    let desk = UdpSocket::bind("127.0.0.1:0").unwrap();
+    for packet in [
+        // Synced data is shown at once until a sync for it arrives
+        e131(1, 1, 100, 1, 7000, first),
+        e131_sync(7000),
+        // From then on both universes of channel 0 wait for the sync
+        e131(1, 1, 100, 2, 7000, first),
+        e131(2, 1, 100, 1, 7000, rest),
+        // A backup desk at lower priority is ignored
+        e131(1, 2, 50, 1, 0, &[255; 510]),
+        e131_sync(7000),
+        // Art-Net without ArtSync is shown at once
+        artdmx(0x10, &short),
+        // The desk leaves
+        e131(1, 1, 100, 3, 0, &[]),
+    ] {
+        desk.send_to(&packet, address).unwrap();
+    }
+
+    let shown = device.join().unwrap();
+    assert_eq!(shown.len(), 3);
+
+    let expected_long = long.as_chunks::<3>().0.to_vec();
+    let expected_short = short.as_chunks::<3>().0.to_vec();
+    assert_eq!(shown[0][0][..170], expected_long[..170]);
+    // The sync showed all of channel 0 at once
+    assert_eq!(shown[1][0], expected_long);
+    assert_eq!(shown[1][1], vec![[0; 3]; SHORT]);
+    assert_eq!(shown[2][1], expected_short);
+}
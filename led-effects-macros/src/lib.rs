@@ -9,11 +9,10 @@ pub fn generate_hsv_lut(_input: TokenStream) -> TokenStream {
     for hue_idx in 0..256 {
         for sat_idx in 0..8 {
             for val_idx in 0..8 {
-                let h = (hue_idx * 360) / 256;
                 let s = (sat_idx * 255) / 7;
                 let v = (val_idx * 255) / 7;
 
-                let pixel = hsv_to_rgb(h, s, v);
+                let pixel = hsv_to_rgb(hue_idx, s, v);
                 data.push(pixel);
             }
         }
@@ -50,18 +49,22 @@ pub fn generate_gamma_lut(_input: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
-// Helper function for HSV to RGB conversion
+// Helper function for HSV to RGB conversion.
+// Hue uses the same 0-255 scale as `Pixel::to_hsv_u8` (6 sectors of 256/6)
+// so that converting back and forth doesn't drift.
 fn hsv_to_rgb(h: u32, s: u32, v: u32) -> (u8, u8, u8) {
     if s == 0 {
         return (v as u8, v as u8, v as u8);
     }
 
-    let region = (h / 60) as u32;
-    let remainder = ((h % 60) * 255) / 60;
+    let scaled = h * 6;
+    let region = scaled / 256;
+    let remainder = scaled % 256; // Position within the sector, 0-255
 
-    let p = (v * (255 - s)) / 255;
-    let q = (v * (255 - ((s * remainder) / 255))) / 255;
-    let t = (v * (255 - ((s * (255 - remainder)) / 255))) / 255;
+    // Rounded fixed-point: remainder is in 1/256 units of a sector
+    let p = (v * (255 - s) + 127) / 255;
+    let q = (v * (255 * 256 - s * remainder) + 255 * 128) / (255 * 256);
+    let t = (v * (255 * 256 - s * (256 - remainder)) + 255 * 128) / (255 * 256);
 
     match region {
         0 => (v as u8, t as u8, p as u8),
@@ -0,0 +1,61 @@
+use std::time::{Duration, Instant};
+
+/// Simulated time of the live view: wall time while playing, moved by the
+/// pause, step and scrub keys. Kept as a `Duration`, so frames shorter than
+/// a millisecond still add up.
+pub struct PlaybackClock {
+    now: Duration,
+    paused: bool,
+    last_tick: Instant,
+}
+
+impl PlaybackClock {
+    /// Start at 0, playing, with wall time counted from `at`
+    pub fn new(at: Instant) -> Self {
+        Self {
+            now: Duration::ZERO,
+            paused: false,
+            last_tick: at,
+        }
+    }
+
+    /// Advance by the wall time since the previous tick, unless paused
+    pub fn tick(&mut self, at: Instant) {
+        if !self.paused {
+            self.now += at.saturating_duration_since(self.last_tick);
+        }
+        self.last_tick = at;
+    }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn forward(&mut self, by: Duration) {
+        self.now += by;
+    }
+
+    /// Move back by `by`, stopping at 0
+    pub fn back(&mut self, by: Duration) {
+        self.now = self.now.saturating_sub(by);
+    }
+
+    pub fn restart(&mut self) {
+        self.now = Duration::ZERO;
+    }
+
+    /// Whole milliseconds, for `Timestamp`
+    #[inline]
+    pub fn now_ms(&self) -> u64 {
+        self.now.as_millis() as u64
+    }
+}
@@ -0,0 +1,73 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+use crate::render::Recording;
+
+/// Write an animated GIF with one frame per recorded frame.
+/// Every LED becomes a `scale`×`scale` square.
+pub fn write_gif(recording: &Recording, path: &Path, scale: u16) -> io::Result<()> {
+    let scale = scale.max(1);
+    let width = dimension(recording.num_leds, scale)?;
+    let height = scale;
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = gif::Encoder::new(file, width, height, &[]).map_err(io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(io::Error::other)?;
+
+    let mut rgb = Vec::with_capacity(width as usize * height as usize * 3);
+
+    for (index, frame) in recording.frames.iter().enumerate() {
+        rgb.clear();
+        for _ in 0..height {
+            push_scaled_row(&mut rgb, frame, scale);
+        }
+
+        // GIF delays are in centiseconds, derive them from timestamps to avoid drift
+        let start_cs = recording.timestamp(index).as_millis() / 10;
+        let end_cs = recording.timestamp(index + 1).as_millis() / 10;
+
+        let mut gif_frame = gif::Frame::from_rgb_speed(width, height, &rgb, 10);
+        gif_frame.delay = (end_cs - start_cs).max(1) as u16;
+        encoder.write_frame(&gif_frame).map_err(io::Error::other)?;
+    }
+
+    Ok(())
+}
+
+/// Write a time×pixel "spacetime" PNG: LEDs run left to right, time runs top
+/// to bottom with one row per frame. Every LED is `scale` pixels wide.
+pub fn write_spacetime_png(recording: &Recording, path: &Path, scale: u16) -> io::Result<()> {
+    let scale = scale.max(1);
+    let width = dimension(recording.num_leds, scale)?;
+    let height = recording.frames.len() as u32;
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut rgb = Vec::with_capacity(width as usize * height as usize * 3);
+    for frame in &recording.frames {
+        push_scaled_row(&mut rgb, frame, scale);
+    }
+
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&rgb).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
+
+fn push_scaled_row(rgb: &mut Vec<u8>, frame: &[led_effects::Pixel], scale: u16) {
+    for pixel in frame {
+        for _ in 0..scale {
+            rgb.extend_from_slice(&[pixel.r, pixel.g, pixel.b]);
+        }
+    }
+}
+
+fn dimension(num_leds: usize, scale: u16) -> io::Result<u16> {
+    u16::try_from(num_leds * scale as usize)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "image too wide"))
+}
@@ -0,0 +1,4 @@
+//! Parts of the preview that are tested on their own; the commands live in
+//! the binary.
+
+pub mod clock;
@@ -0,0 +1,260 @@
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use led_effects::{Generator, Pixel, Timestamp};
+use led_effects_preview::clock::PlaybackClock;
+
+use crate::presets;
+
+/// How far the arrow keys move the simulated clock
+const SCRUB: Duration = Duration::from_secs(1);
+
+/// How long the rest of an escape sequence may take to arrive; a lone ESC
+/// is dropped after it instead of swallowing the next keys
+const ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+
+const HELP: &str = "space pause | . , step | \u{2190} \u{2192} scrub 1s | r restart | q quit";
+
+enum Key {
+    Pause,
+    StepForward,
+    StepBack,
+    ScrubForward,
+    ScrubBack,
+    Restart,
+    Quit,
+}
+
+struct Strip {
+    name: String,
+    generator: Box<dyn Generator>,
+    buffer: Vec<Pixel>,
+}
+
+/// Live terminal viewer: renders every strip as a row of truecolor blocks,
+/// driving `Generator::generate` with a simulated `now` that can be paused,
+/// stepped and scrubbed from the keyboard.
+pub fn run(effects: &[String], num_leds: usize, fps: u32) -> io::Result<()> {
+    let frame_time = Duration::from_secs(1) / fps.max(1);
+
+    let mut strips = build_strips(effects, num_leds)?;
+
+    let _terminal = RawTerminal::enter()?;
+    let keys = spawn_key_reader();
+    let mut out = io::stdout().lock();
+    write!(out, "\x1b[2J")?;
+
+    let mut clock = PlaybackClock::new(Instant::now());
+
+    loop {
+        while let Ok(key) = keys.try_recv() {
+            match key {
+                Key::Pause => clock.toggle_pause(),
+                Key::StepForward => {
+                    clock.pause();
+                    clock.forward(frame_time);
+                }
+                Key::StepBack => {
+                    clock.pause();
+                    clock.back(frame_time);
+                }
+                Key::ScrubForward => clock.forward(SCRUB),
+                Key::ScrubBack => clock.back(SCRUB),
+                Key::Restart => {
+                    // Generators may carry state (e.g. RNG seeds), so rebuild them
+                    strips = build_strips(effects, num_leds)?;
+                    clock.restart();
+                }
+                Key::Quit => return Ok(()),
+            }
+        }
+
+        let tick = Instant::now();
+        clock.tick(tick);
+
+        draw(
+            &mut out,
+            &mut strips,
+            Timestamp::from_millis(clock.now_ms()),
+            clock.is_paused(),
+        )?;
+
+        if let Some(remaining) = frame_time.checked_sub(tick.elapsed()) {
+            thread::sleep(remaining);
+        }
+    }
+}
+
+fn build_strips(effects: &[String], num_leds: usize) -> io::Result<Vec<Strip>> {
+    effects
+        .iter()
+        .map(|name| {
+            let generator = presets::build(name, num_leds).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown effect '{name}', see `list`"),
+                )
+            })?;
+
+            Ok(Strip {
+                name: name.clone(),
+                generator,
+                buffer: vec![Pixel::BLACK; num_leds],
+            })
+        })
+        .collect()
+}
+
+fn draw(
+    out: &mut impl Write,
+    strips: &mut [Strip],
+    now: Timestamp,
+    paused: bool,
+) -> io::Result<()> {
+    let name_width = strips
+        .iter()
+        .map(|strip| strip.name.len())
+        .max()
+        .unwrap_or(0);
+
+    // Raw mode disables newline translation, so every line ends in \r\n
+    write!(out, "\x1b[H")?;
+    for strip in strips.iter_mut() {
+        strip.buffer.fill(Pixel::BLACK);
+        strip.generator.generate(&mut strip.buffer, now);
+
+        write!(out, "{:>name_width$} ", strip.name)?;
+        for pixel in &strip.buffer {
+            write!(
+                out,
+                "\x1b[38;2;{};{};{}m\u{2588}\u{2588}",
+                pixel.r, pixel.g, pixel.b
+            )?;
+        }
+        write!(out, "\x1b[0m\x1b[K\r\n")?;
+    }
+
+    let state = if paused { "paused " } else { "playing" };
+    write!(out, "\r\n{state} t={now:>8} ms\x1b[K\r\n{HELP}\x1b[K")?;
+    out.flush()
+}
+
+fn spawn_key_reader() -> Receiver<Key> {
+    // Reads block, so bytes come through a channel that the parser can
+    // wait on with a timeout
+    let (byte_sender, bytes) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdin = io::stdin().lock();
+        let mut byte = [0u8; 1];
+        while stdin.read(&mut byte).is_ok_and(|n| n == 1) && byte_sender.send(byte[0]).is_ok() {}
+    });
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut escape = Vec::new();
+
+        loop {
+            let byte = if escape.is_empty() {
+                bytes.recv().ok()
+            } else {
+                match bytes.recv_timeout(ESCAPE_TIMEOUT) {
+                    Ok(byte) => Some(byte),
+                    Err(RecvTimeoutError::Timeout) => {
+                        escape.clear();
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => None,
+                }
+            };
+            let Some(byte) = byte else {
+                return;
+            };
+
+            if let Some(key) = parse_key(&mut escape, byte)
+                && sender.send(key).is_err()
+            {
+                return;
+            }
+        }
+    });
+
+    receiver
+}
+
+/// Turn one input byte into a key, collecting escape sequences in `escape`
+fn parse_key(escape: &mut Vec<u8>, byte: u8) -> Option<Key> {
+    // Arrow keys arrive as ESC [ C / ESC [ D
+    if byte == 0x1b {
+        escape.clear();
+        escape.push(byte);
+        return None;
+    }
+    if !escape.is_empty() {
+        escape.push(byte);
+        match escape.as_slice() {
+            [0x1b, b'['] => return None,
+            [0x1b, b'[', b'C'] => {
+                escape.clear();
+                return Some(Key::ScrubForward);
+            }
+            [0x1b, b'[', b'D'] => {
+                escape.clear();
+                return Some(Key::ScrubBack);
+            }
+            [0x1b, b'[', _] => {
+                escape.clear();
+                return None;
+            }
+            // Not a sequence after all, the ESC was typed on its own
+            _ => escape.clear(),
+        }
+    }
+
+    match byte {
+        b' ' | b'p' => Some(Key::Pause),
+        b'.' => Some(Key::StepForward),
+        b',' => Some(Key::StepBack),
+        b'r' => Some(Key::Restart),
+        // Ctrl-C is delivered as a plain byte in raw mode
+        b'q' | 0x03 => Some(Key::Quit),
+        _ => None,
+    }
+}
+
+/// Puts the terminal into raw mode through `stty` and restores it on drop
+struct RawTerminal {
+    saved: String,
+}
+
+impl RawTerminal {
+    fn enter() -> io::Result<Self> {
+        let saved = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+        print!("\x1b[?25l");
+        Ok(Self {
+            saved: saved.trim().to_owned(),
+        })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        print!("\x1b[0m\x1b[?25h\r\n");
+        let _ = io::stdout().flush();
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("stty failed, is stdin a terminal?"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,125 @@
+//! Host-side preview of `led-effects` generators.
+//!
+//! Renders a named effect for a given duration and frame rate and exports it
+//! as an animated GIF or as a time×pixel "spacetime" PNG, so effects can be
+//! reviewed without flashing the board. `live` plays effects directly in a
+//! truecolor terminal.
+//!
+//! ```text
+//! cargo run --release -p led-effects-preview --target x86_64-unknown-linux-gnu -- \
+//!     render chase -o chase.gif --leds 60 --duration 5000 --fps 40
+//! cargo run --release -p led-effects-preview --target x86_64-unknown-linux-gnu -- \
+//!     live chase sparkle --leds 60 --fps 40
+//! ```
+
+mod export;
+mod live;
+mod presets;
+mod render;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(about = "Preview led-effects generators without hardware")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the available effects
+    List,
+    /// Render an effect to an animated GIF (`.gif`) or spacetime PNG (`.png`)
+    Render {
+        /// Effect name, see `list`
+        effect: String,
+        /// Output file, the format is picked from the extension
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Number of LEDs on the simulated strip
+        #[arg(long, default_value_t = 60)]
+        leds: usize,
+        /// Length of the recording in milliseconds
+        #[arg(long, default_value_t = 5000)]
+        duration: u32,
+        /// Frames per second
+        #[arg(long, default_value_t = 40)]
+        fps: u32,
+        /// Size of one LED in image pixels
+        #[arg(long, default_value_t = 8)]
+        scale: u16,
+    },
+    /// Play one or more effects in the terminal, one strip per effect
+    Live {
+        /// Effect names, see `list`
+        #[arg(required = true)]
+        effects: Vec<String>,
+        /// Number of LEDs on each simulated strip
+        #[arg(long, default_value_t = 60)]
+        leds: usize,
+        /// Target frames per second
+        #[arg(long, default_value_t = 40)]
+        fps: u32,
+    },
+}
+
+fn main() -> ExitCode {
+    match Cli::parse().command {
+        Command::List => {
+            for name in presets::NAMES {
+                println!("{name}");
+            }
+            ExitCode::SUCCESS
+        }
+        Command::Render {
+            effect,
+            output,
+            leds,
+            duration,
+            fps,
+            scale,
+        } => {
+            let Some(mut generator) = presets::build(&effect, leds) else {
+                eprintln!("unknown effect '{effect}', see `list`");
+                return ExitCode::FAILURE;
+            };
+
+            let recording = render::record(generator.as_mut(), leds, duration, fps);
+
+            let result = match output.extension().and_then(|ext| ext.to_str()) {
+                Some("gif") => export::write_gif(&recording, &output, scale),
+                Some("png") => export::write_spacetime_png(&recording, &output, scale),
+                _ => {
+                    eprintln!("output must end in .gif or .png");
+                    return ExitCode::FAILURE;
+                }
+            };
+
+            match result {
+                Ok(()) => {
+                    println!(
+                        "wrote {} frames of '{effect}' to {}",
+                        recording.frames.len(),
+                        output.display()
+                    );
+                    ExitCode::SUCCESS
+                }
+                Err(err) => {
+                    eprintln!("failed to write {}: {err}", output.display());
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::Live { effects, leds, fps } => match live::run(&effects, leds, fps) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("live preview failed: {err}");
+                ExitCode::FAILURE
+            }
+        },
+    }
+}
@@ -0,0 +1,214 @@
+use led_effects::effects::modifiers::Sparkle;
+use led_effects::rng::Rng;
+use led_effects::{
+    Blur, Boundary, Chase, DynamicParam, EffectBuilder, Emitter, Fade, Generator, Gradient,
+    HueOscillate, ParticlePool, Particles, Pulse, RotatingHue, Sine, SolidColor, StaticHue,
+    StaticParam, Stripes, Timestamp, Trail, VelocityIntegral,
+};
+
+/// Names accepted by [`build`], in the order they are listed by `--list`
+pub const NAMES: &[&str] = &[
+    "chase", "pulse", "breathe", "gradient", "stripes", "sparkle", "fountain", "rain",
+];
+
+/// Build a named demo effect for a strip of `num_leds` pixels
+pub fn build(name: &str, num_leds: usize) -> Option<Box<dyn Generator>> {
+    let effect: Box<dyn Generator> = match name {
+        "chase" => Box::new(chase(num_leds)),
+        "pulse" => Box::new(Pulse {
+            start_time: Timestamp::ZERO,
+            duration: 3000,
+            position: num_leds / 2,
+            spread_speed: 0.02,
+            width: StaticParam(4),
+            intensity: DynamicParam::<_, u8>::new(
+                Fade {
+                    start_time: Timestamp::ZERO,
+                    duration: 3000,
+                    inverted: true,
+                },
+                0.0,
+                255.0,
+            ),
+            hue: StaticHue::CYAN,
+            saturation: StaticParam(255),
+        }),
+        "breathe" => Box::new(SolidColor {
+            start_time: Timestamp::ZERO,
+            duration: u32::MAX,
+            intensity: DynamicParam::<_, u8>::new(
+                Sine {
+                    start_time: Timestamp::ZERO,
+                    period: 2000,
+                },
+                16.0,
+                255.0,
+            ),
+            hue: StaticHue::ORANGE,
+            saturation: StaticParam(255),
+        }),
+        "gradient" => Box::new(Gradient {
+            start_time: Timestamp::ZERO,
+            duration: u32::MAX,
+            intensity: StaticParam(255),
+            hue_start: RotatingHue {
+                start_time: Timestamp::ZERO,
+                degrees_per_ms: 0.1,
+            },
+            hue_end: HueOscillate {
+                start_time: Timestamp::ZERO,
+                period: 4000,
+                hue1: 128,
+                hue2: 200,
+            },
+            saturation: StaticParam(255),
+        }),
+        "stripes" => Box::new(Stripes {
+            start_time: Timestamp::ZERO,
+            duration: u32::MAX,
+            intensity: StaticParam(255),
+            hue1: StaticHue::RED,
+            hue2: StaticHue::BLUE,
+            saturation: StaticParam(255),
+            stripe_width: StaticParam(4),
+        }),
+        "sparkle" => Box::new(
+            EffectBuilder::new(SolidColor {
+                start_time: Timestamp::ZERO,
+                duration: u32::MAX,
+                intensity: StaticParam(32),
+                hue: StaticHue::BLUE,
+                saturation: StaticParam(255),
+            })
+            .with_modifier(Sparkle {
+                chance: StaticParam(8),
+                hue: StaticHue::BLUE,
+                saturation: StaticParam(64),
+                intensity: StaticParam(255),
+                rng: Rng::new(0x1234_5678),
+            })
+            .build(),
+        ),
+        "fountain" => Box::new(Particles::<64, _, _> {
+            start_time: Timestamp::ZERO,
+            duration: u32::MAX,
+            emitter: Emitter::Point {
+                position: 0.0,
+                rate: 25,
+            },
+            min_velocity: num_leds as f32 * 0.8,
+            max_velocity: num_leds as f32 * 1.2,
+            gravity: -(num_leds as f32),
+            drag: 0.1,
+            lifetime: 2500,
+            lifetime_jitter: 1000,
+            boundary: Boundary::Bounce { restitution: 0.4 },
+            hue: RotatingHue {
+                start_time: Timestamp::ZERO,
+                degrees_per_ms: 0.02,
+            },
+            hue_spread: 24,
+            hue_shift: 0,
+            saturation: 255,
+            intensity: Fade {
+                start_time: Timestamp::ZERO,
+                duration: 2500,
+                inverted: true,
+            },
+            rng: Rng::new(0x0F0F_0F0F),
+            pool: ParticlePool::new(),
+        }),
+        "rain" => Box::new(Particles::<64, _, _> {
+            start_time: Timestamp::ZERO,
+            duration: u32::MAX,
+            emitter: Emitter::Random { rate: 12 },
+            min_velocity: -4.0,
+            max_velocity: -1.0,
+            gravity: -(num_leds as f32) / 2.0,
+            drag: 0.0,
+            lifetime: 4000,
+            lifetime_jitter: 0,
+            boundary: Boundary::Kill,
+            hue: StaticHue::CYAN,
+            hue_spread: 16,
+            hue_shift: 0,
+            saturation: 160,
+            intensity: Fade {
+                start_time: Timestamp::ZERO,
+                duration: 4000,
+                inverted: true,
+            },
+            rng: Rng::new(0xDEAD_BEEF),
+            pool: ParticlePool::new(),
+        }),
+        _ => return None,
+    };
+
+    Some(effect)
+}
+
+/// The effect currently running in the firmware's `led_effects` task
+fn chase(num_leds: usize) -> impl Generator {
+    EffectBuilder::new(Chase {
+        start_time: Timestamp::ZERO,
+        duration: 5000,
+
+        position: DynamicParam::<_, u8>::new(
+            VelocityIntegral::new(
+                Timestamp::ZERO,
+                Fade {
+                    start_time: Timestamp::ZERO,
+                    duration: 5000,
+                    inverted: true,
+                },
+                0,  // initial position
+                16, // dt_ms
+                20, // velocity scale, adjust for desired speed
+            ),
+            0.0,
+            num_leds as f32,
+        ),
+
+        width: DynamicParam::<_, u8>::new(
+            Fade {
+                start_time: Timestamp::ZERO,
+                duration: 5000,
+                inverted: false,
+            },
+            2.0,
+            8.0,
+        ),
+
+        intensity: DynamicParam::<_, u8>::new(
+            Fade {
+                start_time: Timestamp::ZERO,
+                duration: 5000,
+                inverted: true,
+            },
+            0.0,
+            255.0,
+        ),
+
+        hue: RotatingHue {
+            start_time: Timestamp::ZERO,
+            degrees_per_ms: 0.05,
+        },
+
+        saturation: StaticParam(255),
+    })
+    .with_modifier(Trail {
+        decay_rate: DynamicParam::<_, u8>::new(
+            Fade {
+                start_time: Timestamp::ZERO,
+                duration: 5000,
+                inverted: false,
+            },
+            204.0,
+            242.0,
+        ),
+    })
+    .with_modifier(Blur {
+        strength: StaticParam(51),
+    })
+    .build()
+}
@@ -0,0 +1,44 @@
+use led_effects::{Generator, Pixel, Timestamp};
+
+/// A rendered strip: one frame per entry, `num_leds` pixels per frame.
+pub struct Recording {
+    pub num_leds: usize,
+    pub fps: u32,
+    pub frames: Vec<Vec<Pixel>>,
+}
+
+impl Recording {
+    /// Simulated `now` at which `frame` was generated
+    #[inline]
+    pub fn timestamp(&self, frame: usize) -> Timestamp {
+        Timestamp::from_millis((frame as u64 * 1000) / self.fps as u64)
+    }
+}
+
+/// Run `generator` from `now = 0` for `duration_ms` at `fps`, mimicking the
+/// firmware render loop: the buffer is cleared before every frame.
+pub fn record<G>(generator: &mut G, num_leds: usize, duration_ms: u32, fps: u32) -> Recording
+where
+    G: Generator + ?Sized,
+{
+    let mut recording = Recording {
+        num_leds,
+        fps: fps.max(1),
+        frames: Vec::new(),
+    };
+
+    let frame_count = (duration_ms as u64 * recording.fps as u64)
+        .div_ceil(1000)
+        .max(1) as usize;
+    let mut buffer = vec![Pixel::BLACK; num_leds];
+
+    for frame in 0..frame_count {
+        // Timestamps come from the frame index so rounding doesn't accumulate
+        let now = recording.timestamp(frame);
+        buffer.fill(Pixel::BLACK);
+        generator.generate(&mut buffer, now);
+        recording.frames.push(buffer.clone());
+    }
+
+    recording
+}
@@ -0,0 +1,52 @@
+//! The live view's simulated clock.
+
+use std::time::{Duration, Instant};
+
+use led_effects_preview::clock::PlaybackClock;
+
+/// Tick `frames` times, `frame` apart, returning the clock
+fn play(frame: Duration, frames: u32) -> PlaybackClock {
+    let start = Instant::now();
+    let mut clock = PlaybackClock::new(start);
+    for i in 1..=frames {
+        clock.tick(start + frame * i);
+    }
+    clock
+}
+
+#[test]
+fn fractional_frames_add_up() {
+    // 60 fps, 16.667 ms a frame
+    assert_eq!(play(Duration::from_micros(16_667), 60).now_ms(), 1000);
+    // Well under a millisecond a frame
+    assert_eq!(play(Duration::from_micros(400), 2500).now_ms(), 1000);
+}
+
+#[test]
+fn pause_stops_the_clock() {
+    let start = Instant::now();
+    let mut clock = PlaybackClock::new(start);
+    clock.tick(start + Duration::from_millis(100));
+    clock.toggle_pause();
+    clock.tick(start + Duration::from_millis(500));
+    assert!(clock.is_paused());
+    assert_eq!(clock.now_ms(), 100);
+
+    // Time spent paused doesn't count after resuming either
+    clock.toggle_pause();
+    clock.tick(start + Duration::from_millis(550));
+    assert_eq!(clock.now_ms(), 150);
+}
+
+#[test]
+fn steps_and_scrubs_move_the_clock() {
+    let mut clock = play(Duration::from_millis(10), 10);
+    clock.pause();
+    clock.forward(Duration::from_millis(1000));
+    assert_eq!(clock.now_ms(), 1100);
+    clock.back(Duration::from_millis(2000));
+    assert_eq!(clock.now_ms(), 0);
+    clock.forward(Duration::from_micros(1500));
+    clock.restart();
+    assert_eq!(clock.now_ms(), 0);
+}
@@ -0,0 +1,185 @@
+use core::f32::consts::PI;
+
+/// Bass, low-mid, high-mid and treble
+pub const BAND_COUNT: usize = 4;
+
+/// Crossover frequencies between neighbouring bands, in Hz
+pub const DEFAULT_BAND_EDGES: [u32; BAND_COUNT - 1] = [250, 2000, 6000];
+
+/// Levels are on a dB scale: `FLOOR_DB` and below read 0, 0 dBFS reads 255
+pub const FLOOR_DB: f32 = -60.0;
+
+/// One block's worth of analysis, every field on the same 0-255 dB scale
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AudioLevels {
+    pub rms: u8,
+    pub peak: u8,
+    pub bands: [u8; BAND_COUNT],
+}
+
+/// RMS, peak and FFT band energy over blocks of `N` samples.
+///
+/// `N` must be a power of two. It sets both the latency (one block) and the
+/// band resolution (`sample_rate / N` Hz per FFT bin). All buffers live in
+/// the struct, so nothing is allocated per block.
+pub struct AudioAnalyzer<const N: usize> {
+    sample_rate: u32,
+    // First FFT bin of each band, plus the end of the last one
+    band_bins: [usize; BAND_COUNT + 1],
+    window: [f32; N],
+    window_power: f32,
+    // e^(-2πik/N) for k < N/2, real and imaginary parts interleaved
+    twiddles: [f32; N],
+    re: [f32; N],
+    im: [f32; N],
+}
+
+impl<const N: usize> AudioAnalyzer<N> {
+    pub fn new(sample_rate: u32) -> Self {
+        Self::with_bands(sample_rate, DEFAULT_BAND_EDGES)
+    }
+
+    /// `edges` are the crossovers between bands in Hz, lowest first
+    pub fn with_bands(sample_rate: u32, edges: [u32; BAND_COUNT - 1]) -> Self {
+        assert!(
+            N.is_power_of_two() && N >= 8,
+            "block size must be a power of two"
+        );
+
+        let mut window = [0.0; N];
+        let mut twiddles = [0.0; N];
+        for (i, w) in window.iter_mut().enumerate() {
+            *w = hann(i, N);
+        }
+        for (k, [re, im]) in twiddles.as_chunks_mut::<2>().0.iter_mut().enumerate() {
+            let angle = -2.0 * PI * k as f32 / N as f32;
+            *re = libm::cosf(angle);
+            *im = libm::sinf(angle);
+        }
+
+        // Bin 0 is DC, which is removed before the transform anyway
+        let mut band_bins = [1; BAND_COUNT + 1];
+        for (bin, edge) in band_bins[1..BAND_COUNT].iter_mut().zip(edges) {
+            let index = (edge as u64 * N as u64 / sample_rate.max(1) as u64) as usize;
+            *bin = index.clamp(1, N / 2);
+        }
+        band_bins[BAND_COUNT] = N / 2;
+
+        Self {
+            sample_rate,
+            band_bins,
+            window_power: window.iter().map(|w| w * w).sum(),
+            window,
+            twiddles,
+            re: [0.0; N],
+            im: [0.0; N],
+        }
+    }
+
+    #[inline(always)]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Analyze up to `N` samples; a shorter block is windowed over its own
+    /// length and zero padded
+    pub fn analyze(&mut self, samples: &[i16]) -> AudioLevels {
+        let samples = &samples[..samples.len().min(N)];
+        if samples.is_empty() {
+            return AudioLevels::default();
+        }
+
+        // Microphone front ends sit on a DC bias, measure around the mean
+        let count = samples.len() as f32;
+        let mean = samples.iter().map(|&s| s as f32).sum::<f32>() / count;
+
+        let full = samples.len() == N;
+        let mut window_power = if full { self.window_power } else { 0.0 };
+        let mut peak = 0.0_f32;
+        let mut square_sum = 0.0_f32;
+        for (i, &sample) in samples.iter().enumerate() {
+            let value = (sample as f32 - mean) / 32768.0;
+            peak = peak.max(libm::fabsf(value));
+            square_sum += value * value;
+
+            let w = if full {
+                self.window[i]
+            } else {
+                let w = hann(i, samples.len());
+                window_power += w * w;
+                w
+            };
+            self.re[i] = value * w;
+            self.im[i] = 0.0;
+        }
+        self.re[samples.len()..].fill(0.0);
+        self.im[samples.len()..].fill(0.0);
+
+        self.fft();
+
+        let mut bands = [0; BAND_COUNT];
+        for (band, level) in bands.iter_mut().enumerate() {
+            let energy: f32 = (self.band_bins[band]..self.band_bins[band + 1])
+                .map(|bin| self.re[bin] * self.re[bin] + self.im[bin] * self.im[bin])
+                .sum();
+            // Parseval: RMS of the part of the signal that falls in this band
+            *level = to_level(libm::sqrtf(2.0 * energy / (N as f32 * window_power)));
+        }
+
+        AudioLevels {
+            rms: to_level(libm::sqrtf(square_sum / count)),
+            peak: to_level(peak),
+            bands,
+        }
+    }
+
+    /// In-place iterative radix-2 FFT over `re`/`im`
+    fn fft(&mut self) {
+        let bits = N.trailing_zeros();
+        for i in 0..N {
+            let j = i.reverse_bits() >> (usize::BITS - bits);
+            if j > i {
+                self.re.swap(i, j);
+                self.im.swap(i, j);
+            }
+        }
+
+        let mut size = 2;
+        while size <= N {
+            let half = size / 2;
+            let stride = N / size;
+            for start in (0..N).step_by(size) {
+                for k in 0..half {
+                    let index = 2 * k * stride;
+                    let (w_re, w_im) = (self.twiddles[index], self.twiddles[index + 1]);
+                    let (a, b) = (start + k, start + k + half);
+
+                    let t_re = self.re[b] * w_re - self.im[b] * w_im;
+                    let t_im = self.re[b] * w_im + self.im[b] * w_re;
+                    self.re[b] = self.re[a] - t_re;
+                    self.im[b] = self.im[a] - t_im;
+                    self.re[a] += t_re;
+                    self.im[a] += t_im;
+                }
+            }
+            size *= 2;
+        }
+    }
+}
+
+/// Hann window over `len` samples, which keeps a tone from leaking into
+/// the other bands
+#[inline(always)]
+fn hann(i: usize, len: usize) -> f32 {
+    0.5 - 0.5 * libm::cosf(2.0 * PI * i as f32 / len as f32)
+}
+
+/// Amplitude relative to full scale → 0-255 on the dB scale
+#[inline(always)]
+fn to_level(amplitude: f32) -> u8 {
+    if amplitude <= 0.0 {
+        return 0;
+    }
+    let db = 20.0 * libm::log10f(amplitude);
+    ((db - FLOOR_DB) / -FLOOR_DB * 255.0).clamp(0.0, 255.0) as u8
+}
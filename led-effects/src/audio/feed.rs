@@ -0,0 +1,100 @@
+use super::analyzer::{AudioLevels, BAND_COUNT};
+use crate::effects::core::{
+    time::Timestamp,
+    traits::{Envelope, EnvelopeValue, Parameter},
+};
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Latest audio levels, shared between the analysis task and effects.
+///
+/// Byte-sized atomics need no lock and no CAS, so a `static AudioFeed` can
+/// be written from one task (or interrupt) and read from any other.
+pub struct AudioFeed {
+    rms: AtomicU8,
+    peak: AtomicU8,
+    bands: [AtomicU8; BAND_COUNT],
+}
+
+/// Which value of an [`AudioFeed`] to read
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioSignal {
+    Rms,
+    Peak,
+    /// Band index, 0 = bass. Out of range bands read as silence.
+    Band(u8),
+}
+
+/// Live audio level as a parameter or envelope.
+///
+/// Raw levels change every analysis block; wrap this in `Smooth` or `Slew`
+/// for a calmer response.
+pub struct AudioLevel<'a> {
+    pub feed: &'a AudioFeed,
+    pub signal: AudioSignal,
+}
+
+impl AudioFeed {
+    pub const fn new() -> Self {
+        Self {
+            rms: AtomicU8::new(0),
+            peak: AtomicU8::new(0),
+            bands: [const { AtomicU8::new(0) }; BAND_COUNT],
+        }
+    }
+
+    pub fn publish(&self, levels: &AudioLevels) {
+        self.rms.store(levels.rms, Ordering::Relaxed);
+        self.peak.store(levels.peak, Ordering::Relaxed);
+        for (band, &level) in self.bands.iter().zip(&levels.bands) {
+            band.store(level, Ordering::Relaxed);
+        }
+    }
+
+    pub fn levels(&self) -> AudioLevels {
+        AudioLevels {
+            rms: self.rms.load(Ordering::Relaxed),
+            peak: self.peak.load(Ordering::Relaxed),
+            bands: core::array::from_fn(|band| self.bands[band].load(Ordering::Relaxed)),
+        }
+    }
+
+    #[inline(always)]
+    pub fn level(&self, signal: AudioSignal) -> u8 {
+        match signal {
+            AudioSignal::Rms => self.rms.load(Ordering::Relaxed),
+            AudioSignal::Peak => self.peak.load(Ordering::Relaxed),
+            AudioSignal::Band(band) => self
+                .bands
+                .get(band as usize)
+                .map_or(0, |level| level.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl Default for AudioFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parameter<u8> for AudioLevel<'_> {
+    #[inline(always)]
+    fn sample(&self, _now: Timestamp) -> u8 {
+        self.feed.level(self.signal)
+    }
+}
+
+impl<T> Envelope<T> for AudioLevel<'_>
+where
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn sample(&self, _now: Timestamp) -> T {
+        T::from_progress(self.feed.level(self.signal) as u32, 255)
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, _now: Timestamp) -> bool {
+        true
+    }
+}
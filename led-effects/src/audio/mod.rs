@@ -0,0 +1,13 @@
+//! Audio analysis for audio-reactive effects (no_std, hardware independent).
+//!
+//! [`AudioAnalyzer`] turns a block of signed 16-bit samples into
+//! [`AudioLevels`]: RMS, peak and the energy in a few frequency bands. The
+//! firmware feeds it from the microphone ADC and publishes each result into
+//! a shared [`AudioFeed`]; effects read the feed through [`AudioLevel`],
+//! which works anywhere a `Parameter<u8>` or `Envelope` does.
+
+mod analyzer;
+mod feed;
+
+pub use analyzer::{AudioAnalyzer, AudioLevels, BAND_COUNT, DEFAULT_BAND_EDGES, FLOOR_DB};
+pub use feed::{AudioFeed, AudioLevel, AudioSignal};
@@ -1,5 +1,7 @@
 pub mod builder;
+pub mod trigger;
 pub mod with_modifier;
 
 pub use builder::EffectBuilder;
+pub use trigger::{Retrigger, Trigger, Triggered};
 pub use with_modifier::WithModifier;
@@ -0,0 +1,185 @@
+use crate::effects::core::{
+    pixel::Pixel,
+    time::Timestamp,
+    traits::{EnvelopeValue, Generator, StatefulEnvelope},
+};
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Fire-and-forget event source, e.g. a `static` set from an EXTI handler.
+///
+/// `fire` only bumps an atomic counter, so it is safe from any interrupt or
+/// task. Every [`Triggered`] watching the trigger picks the event up on its
+/// next frame.
+pub struct Trigger {
+    fired: AtomicU32,
+}
+
+impl Trigger {
+    pub const fn new() -> Self {
+        Self {
+            fired: AtomicU32::new(0),
+        }
+    }
+
+    #[inline(always)]
+    pub fn fire(&self) {
+        self.fired.fetch_add(1, Ordering::Release);
+    }
+
+    /// Total number of fires, wrapping
+    #[inline(always)]
+    pub fn count(&self) -> u32 {
+        self.fired.load(Ordering::Acquire)
+    }
+}
+
+impl Default for Trigger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What a fire does while a voice is still playing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Retrigger {
+    /// Start the first voice over
+    Restart,
+    /// Drop fires until the first voice has finished
+    IgnoreWhileAlive,
+    /// Start an idle voice, or take over the oldest one if all are playing
+    Stack,
+}
+
+/// One-shot effect started by a [`Trigger`].
+///
+/// Voices are built with `start_time: Timestamp::ZERO` and see time counted
+/// from their own fire, so every envelope and parameter inside them restarts
+/// together. A generator voice also gets [`Generator::restart`], which
+/// e.g. makes a burst [`Particles`](crate::Particles) emitter go off.
+/// `Restart` and `IgnoreWhileAlive` only use the first voice;
+/// `Stack` plays up to `N` at once. Idle voices render nothing.
+pub struct Triggered<'a, V, const N: usize> {
+    pub trigger: &'a Trigger,
+    pub policy: Retrigger,
+    voices: [V; N],
+    started: [Option<Timestamp>; N],
+    seen: u32,
+}
+
+impl<'a, V, const N: usize> Triggered<'a, V, N> {
+    /// Fires from before this call are ignored
+    pub fn new(trigger: &'a Trigger, policy: Retrigger, voices: [V; N]) -> Self {
+        Self {
+            trigger,
+            policy,
+            voices,
+            started: [None; N],
+            seen: trigger.count(),
+        }
+    }
+
+    /// Number of voices currently playing
+    pub fn active(&self) -> usize {
+        self.started.iter().filter(|s| s.is_some()).count()
+    }
+
+    /// Time as seen by a voice started at `started`
+    #[inline(always)]
+    fn local_time(now: Timestamp, started: Timestamp) -> Timestamp {
+        Timestamp::from_millis(now.millis_since(started))
+    }
+
+    /// Retire finished voices, then start voices for fires since the last
+    /// frame, passing each one to `start`
+    fn poll(
+        &mut self,
+        now: Timestamp,
+        alive: impl Fn(&V, Timestamp) -> bool,
+        mut start: impl FnMut(&mut V),
+    ) {
+        for (voice, started) in self.voices.iter().zip(self.started.iter_mut()) {
+            if let Some(start) = *started
+                && (now < start || !alive(voice, Self::local_time(now, start)))
+            {
+                *started = None;
+            }
+        }
+
+        let fired = self.trigger.count();
+        let pending = fired.wrapping_sub(self.seen);
+        self.seen = fired;
+        if pending == 0 || N == 0 {
+            return;
+        }
+
+        match self.policy {
+            Retrigger::Restart => self.start(0, now, &mut start),
+            Retrigger::IgnoreWhileAlive => {
+                if self.started[0].is_none() {
+                    self.start(0, now, &mut start);
+                }
+            }
+            Retrigger::Stack => {
+                // Fires beyond N in one frame would only replace each other
+                for _ in 0..pending.min(N as u32) {
+                    let slot = match self.started.iter().position(Option::is_none) {
+                        Some(idle) => idle,
+                        None => (0..N).min_by_key(|&i| self.started[i]).unwrap_or(0),
+                    };
+                    self.start(slot, now, &mut start);
+                }
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn start(&mut self, slot: usize, now: Timestamp, start: &mut impl FnMut(&mut V)) {
+        self.started[slot] = Some(now);
+        start(&mut self.voices[slot]);
+    }
+}
+
+impl<V, const N: usize> Generator for Triggered<'_, V, N>
+where
+    V: Generator,
+{
+    fn generate(&mut self, buffer: &mut [Pixel], now: Timestamp) {
+        self.poll(now, |voice, local| voice.is_alive(local), V::restart);
+
+        for (voice, started) in self.voices.iter_mut().zip(&self.started) {
+            if let Some(start) = *started {
+                voice.generate(buffer, Self::local_time(now, start));
+            }
+        }
+    }
+
+    /// Always alive: an idle effect is waiting for its next fire
+    #[inline(always)]
+    fn is_alive(&self, _now: Timestamp) -> bool {
+        true
+    }
+}
+
+/// The loudest playing voice; ZERO while idle
+impl<V, T, const N: usize> StatefulEnvelope<T> for Triggered<'_, V, N>
+where
+    V: StatefulEnvelope<T>,
+    T: EnvelopeValue,
+{
+    fn update(&mut self, now: Timestamp) -> T {
+        self.poll(now, |voice, local| voice.is_alive(local), |_| {});
+
+        let mut value = T::ZERO;
+        for (voice, started) in self.voices.iter_mut().zip(&self.started) {
+            if let Some(start) = *started {
+                value = value.max(voice.update(Self::local_time(now, start)));
+            }
+        }
+        value
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, _now: Timestamp) -> bool {
+        true
+    }
+}
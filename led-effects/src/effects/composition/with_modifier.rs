@@ -1,5 +1,6 @@
 use crate::effects::core::{
     pixel::Pixel,
+    time::Timestamp,
     traits::{Generator, Modifier},
 };
 
@@ -10,13 +11,18 @@ pub struct WithModifier<G: Generator, M: Modifier> {
 
 impl<G: Generator, M: Modifier> Generator for WithModifier<G, M> {
     #[inline(always)]
-    fn generate(&mut self, buffer: &mut [Pixel], now: u32) {
+    fn generate(&mut self, buffer: &mut [Pixel], now: Timestamp) {
         self.generator.generate(buffer, now);
         self.modifier.modify(buffer, now);
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
+    fn is_alive(&self, now: Timestamp) -> bool {
         self.generator.is_alive(now)
     }
+
+    #[inline(always)]
+    fn restart(&mut self) {
+        self.generator.restart();
+    }
 }
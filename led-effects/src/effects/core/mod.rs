@@ -1,5 +1,7 @@
 pub mod pixel;
+pub mod time;
 pub mod traits;
 
 pub use pixel::Pixel;
-pub use traits::{Envelope, Generator, HueParameter, Modifier, Parameter};
+pub use time::Timestamp;
+pub use traits::{Envelope, Generator, HueParameter, Modifier, Parameter, StatefulEnvelope};
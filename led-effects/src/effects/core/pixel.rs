@@ -44,10 +44,16 @@ impl Pixel {
     /// Linear interpolation (t: 0-255)
     #[inline(always)]
     pub fn lerp(&self, other: &Self, t: u8) -> Self {
+        #[inline(always)]
+        fn channel(from: u8, to: u8, t: u8) -> u8 {
+            // (to - from) * t spans ±65025, which needs more than i16
+            (from as i32 + ((to as i32 - from as i32) * t as i32) / 255) as u8
+        }
+
         Self {
-            r: self.r + (((other.r as i16 - self.r as i16) * t as i16) / 255) as u8,
-            g: self.g + (((other.g as i16 - self.g as i16) * t as i16) / 255) as u8,
-            b: self.b + (((other.b as i16 - self.b as i16) * t as i16) / 255) as u8,
+            r: channel(self.r, other.r, t),
+            g: channel(self.g, other.g, t),
+            b: channel(self.b, other.b, t),
         }
     }
 
@@ -90,23 +96,21 @@ impl Pixel {
 
         let saturation = ((delta as u16 * 255) / max as u16) as u8;
 
-        // Calculate hue: map 0-360° to 0-255
-        // Each of 6 sectors = 255/6 ≈ 42.5, we use 256/6 for better precision
-        let hue = if max == self.r {
-            let diff = if self.g >= self.b {
-                ((self.g - self.b) as u32 * 256) / (delta as u32 * 6)
-            } else {
-                256 - (((self.b - self.g) as u32 * 256) / (delta as u32 * 6))
-            };
-            diff as u8
+        // Calculate hue on the 0-255 scale used by the HSV LUT: 6 sectors of
+        // 256/6, red at 0, green at 2 sectors, blue at 4 sectors. Everything
+        // is scaled by 6 * delta to stay in integers, then rounded.
+        let delta = delta as i32;
+        let (sector, offset) = if max == self.r {
+            (0, self.g as i32 - self.b as i32)
         } else if max == self.g {
-            let diff = ((self.b as i16 - self.r as i16) as i32 * 256) / (delta as i32 * 6);
-            (85 + diff) as u8
+            (2, self.b as i32 - self.r as i32)
         } else {
-            let diff = ((self.r as i16 - self.g as i16) as i32 * 256) / (delta as i32 * 6);
-            (171 + diff) as u8
+            (4, self.r as i32 - self.g as i32)
         };
 
+        let scaled = (sector * 256 * delta + offset * 256).rem_euclid(6 * 256 * delta);
+        let hue = ((scaled + 3 * delta) / (6 * delta)) as u8;
+
         (hue, saturation, max)
     }
 }
@@ -0,0 +1,73 @@
+use core::fmt;
+
+/// Milliseconds since an arbitrary epoch (usually boot).
+///
+/// Stored as u64 so it never wraps in practice; a u32 millisecond counter
+/// wraps after ~49.7 days. Durations and periods stay u32 milliseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    pub const ZERO: Self = Self(0);
+
+    #[inline(always)]
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(millis)
+    }
+
+    #[inline(always)]
+    pub const fn as_millis(self) -> u64 {
+        self.0
+    }
+
+    /// Milliseconds since `earlier`, or 0 if `earlier` is in the future
+    #[inline(always)]
+    pub const fn millis_since(self, earlier: Self) -> u64 {
+        self.0.saturating_sub(earlier.0)
+    }
+
+    /// [`millis_since`](Self::millis_since) clamped to u32, for comparing
+    /// against durations
+    #[inline(always)]
+    pub const fn elapsed_since(self, earlier: Self) -> u32 {
+        let elapsed = self.millis_since(earlier);
+        if elapsed > u32::MAX as u64 {
+            u32::MAX
+        } else {
+            elapsed as u32
+        }
+    }
+
+    /// Position within a cycle of `period` ms that started at `start`.
+    /// Computed on the full u64 elapsed time so the phase never jumps.
+    #[inline(always)]
+    pub const fn phase(self, start: Self, period: u32) -> u32 {
+        (self.millis_since(start) % period as u64) as u32
+    }
+
+    /// `self + millis`, saturating at the end of time
+    #[inline(always)]
+    pub const fn add_millis(self, millis: u64) -> Self {
+        Self(self.0.saturating_add(millis))
+    }
+}
+
+impl From<u64> for Timestamp {
+    #[inline(always)]
+    fn from(millis: u64) -> Self {
+        Self(millis)
+    }
+}
+
+impl From<Timestamp> for u64 {
+    #[inline(always)]
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.0
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
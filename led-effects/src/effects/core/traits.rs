@@ -1,25 +1,62 @@
-use crate::effects::core::Pixel;
+use crate::effects::core::{Pixel, Timestamp};
 
 pub trait Parameter<T> {
-    fn sample(&self, now: u32) -> T;
+    fn sample(&self, now: Timestamp) -> T;
 }
 
 pub trait Envelope<T> {
-    fn sample(&self, now: u32) -> T;
-    fn is_alive(&self, now: u32) -> bool;
+    fn sample(&self, now: Timestamp) -> T;
+    fn is_alive(&self, now: Timestamp) -> bool;
 }
 
+/// Envelope that may keep state between samples (smoothing, caching,
+/// randomness). Every u8/u16/u32 [`Envelope`] is also a `StatefulEnvelope`.
+pub trait StatefulEnvelope<T> {
+    fn update(&mut self, now: Timestamp) -> T;
+    fn is_alive(&self, now: Timestamp) -> bool;
+}
+
+// One blanket impl per value type: a generic `T` would let downstream crates
+// implement `Envelope<Local>` for our stateful types and overlap with them.
+macro_rules! impl_stateful_for_envelopes {
+    ($($t:ty),*) => {
+        $(
+            impl<E> StatefulEnvelope<$t> for E
+            where
+                E: Envelope<$t>,
+            {
+                #[inline(always)]
+                fn update(&mut self, now: Timestamp) -> $t {
+                    self.sample(now)
+                }
+
+                #[inline(always)]
+                fn is_alive(&self, now: Timestamp) -> bool {
+                    Envelope::is_alive(self, now)
+                }
+            }
+        )*
+    };
+}
+
+impl_stateful_for_envelopes!(u8, u16, u32);
+
 pub trait Generator {
-    fn generate(&mut self, buffer: &mut [Pixel], now: u32);
-    fn is_alive(&self, now: u32) -> bool;
+    fn generate(&mut self, buffer: &mut [Pixel], now: Timestamp);
+    fn is_alive(&self, now: Timestamp) -> bool;
+
+    /// Called when a [`Triggered`](crate::Triggered) voice starts, before it
+    /// renders from time zero. State kept between frames starts over here.
+    #[inline(always)]
+    fn restart(&mut self) {}
 }
 
 pub trait Modifier {
-    fn modify(&mut self, buffer: &mut [Pixel], now: u32);
+    fn modify(&mut self, buffer: &mut [Pixel], now: Timestamp);
 }
 
 pub trait HueParameter {
-    fn sample(&self, now: u32) -> u8;
+    fn sample(&self, now: Timestamp) -> u8;
 }
 
 pub trait Easing {
@@ -34,6 +71,7 @@ pub trait EnvelopeValue: Copy + Sized + Ord {
     const MAX: Self;
 
     fn from_progress(elapsed: u32, duration: u32) -> Self;
+    fn from_u32(value: u32) -> Self;
     fn saturating_add(self, other: Self) -> Self;
     fn saturating_mul_div(self, other: Self) -> Self;
     fn saturating_sub(self, other: Self) -> Self;
@@ -48,7 +86,12 @@ impl EnvelopeValue for u8 {
 
     #[inline(always)]
     fn from_progress(elapsed: u32, duration: u32) -> Self {
-        ((elapsed * Self::MAX as u32) / duration) as Self
+        ((elapsed as u64 * Self::MAX as u64) / duration as u64) as Self
+    }
+
+    #[inline(always)]
+    fn from_u32(value: u32) -> Self {
+        value.min(Self::MAX as u32) as Self
     }
 
     #[inline(always)]
@@ -84,7 +127,12 @@ impl EnvelopeValue for u16 {
 
     #[inline(always)]
     fn from_progress(elapsed: u32, duration: u32) -> Self {
-        ((elapsed * Self::MAX as u32) / duration) as Self
+        ((elapsed as u64 * Self::MAX as u64) / duration as u64) as Self
+    }
+
+    #[inline(always)]
+    fn from_u32(value: u32) -> Self {
+        value.min(Self::MAX as u32) as Self
     }
 
     #[inline(always)]
@@ -120,7 +168,12 @@ impl EnvelopeValue for u32 {
 
     #[inline(always)]
     fn from_progress(elapsed: u32, duration: u32) -> Self {
-        ((elapsed * Self::MAX as u32) / duration) as Self
+        ((elapsed as u64 * Self::MAX as u64) / duration as u64) as Self
+    }
+
+    #[inline(always)]
+    fn from_u32(value: u32) -> Self {
+        value
     }
 
     #[inline(always)]
@@ -145,6 +198,6 @@ impl EnvelopeValue for u32 {
 
     #[inline(always)]
     fn to_u32(self) -> u32 {
-        self as u32
+        self
     }
 }
@@ -1,4 +1,7 @@
-use crate::effects::core::traits::{Envelope, EnvelopeValue};
+use crate::effects::core::{
+    time::Timestamp,
+    traits::{Envelope, EnvelopeValue},
+};
 
 pub struct Product<E1, E2> {
     pub env1: E1,
@@ -37,7 +40,7 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
+    fn sample(&self, now: Timestamp) -> T {
         let v1 = self.env1.sample(now);
         let v2 = self.env2.sample(now);
 
@@ -45,7 +48,7 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
+    fn is_alive(&self, now: Timestamp) -> bool {
         self.env1.is_alive(now) && self.env2.is_alive(now)
     }
 }
@@ -57,7 +60,7 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
+    fn sample(&self, now: Timestamp) -> T {
         let v1 = self.env1.sample(now);
         let v2 = self.env2.sample(now);
 
@@ -65,7 +68,7 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
+    fn is_alive(&self, now: Timestamp) -> bool {
         self.env1.is_alive(now) || self.env2.is_alive(now)
     }
 }
@@ -77,7 +80,7 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
+    fn sample(&self, now: Timestamp) -> T {
         let v1 = self.env1.sample(now);
         let v2 = self.env2.sample(now);
 
@@ -85,7 +88,7 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
+    fn is_alive(&self, now: Timestamp) -> bool {
         self.env1.is_alive(now) || self.env2.is_alive(now)
     }
 }
@@ -97,7 +100,7 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
+    fn sample(&self, now: Timestamp) -> T {
         let v1 = self.env1.sample(now);
         let v2 = self.env2.sample(now);
 
@@ -105,7 +108,7 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
+    fn is_alive(&self, now: Timestamp) -> bool {
         self.env1.is_alive(now) || self.env2.is_alive(now)
     }
 }
@@ -116,12 +119,12 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
+    fn sample(&self, now: Timestamp) -> T {
         T::MAX.saturating_sub(self.inner.sample(now))
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
+    fn is_alive(&self, now: Timestamp) -> bool {
         self.inner.is_alive(now)
     }
 }
@@ -132,14 +135,14 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
+    fn sample(&self, now: Timestamp) -> T {
         let value = self.inner.sample(now);
 
         value.clamp_value(self.min, self.max)
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
+    fn is_alive(&self, now: Timestamp) -> bool {
         self.inner.is_alive(now)
     }
 }
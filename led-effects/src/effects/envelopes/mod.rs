@@ -1,5 +1,7 @@
 pub mod combinators;
 pub mod easing;
+pub mod noise;
+pub mod stateful;
 pub mod timing;
 pub mod utilities;
 
@@ -10,5 +12,7 @@ pub use easing::{
     EaseOutCubic, EaseOutExpo, EaseOutQuad, EaseOutQuart, ElasticIn, ElasticInOut, ElasticOut,
     Linear,
 };
+pub use noise::{Flicker, PerlinNoise, RandomStep, RandomWalk, ValueNoise};
+pub use stateful::{SampleHold, Slew, Smooth, Stateful};
 pub use timing::{ADSR, Constant, Fade, Sawtooth, Sine, Square, Triangle};
 pub use utilities::{LoopCount, Pulse, TimeLimited, VelocityIntegral};
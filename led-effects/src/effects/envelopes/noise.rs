@@ -0,0 +1,270 @@
+use crate::effects::core::{
+    time::Timestamp,
+    traits::{Easing, Envelope, EnvelopeValue, StatefulEnvelope},
+};
+use crate::rng::{Rng, hash};
+
+/// Smooth random curve: a random value every `period` ms, joined with
+/// smoothstep so there are no corners
+pub struct ValueNoise {
+    pub start_time: Timestamp,
+    pub period: u32,
+    pub seed: u32,
+}
+
+/// 1D Perlin (gradient) noise with lattice spacing `period` ms. Softer
+/// than value noise and crosses the midpoint at every lattice point.
+pub struct PerlinNoise {
+    pub start_time: Timestamp,
+    pub period: u32,
+    pub seed: u32,
+}
+
+/// Jumps to a new random value every `period` ms, easing from the previous
+/// value over the first `transition` ms of each step
+pub struct RandomStep<E> {
+    pub start_time: Timestamp,
+    pub period: u32,
+    pub transition: u32,
+    pub seed: u32,
+    pub easing: E,
+}
+
+/// Candle flicker: mostly bright with random dips, changing every
+/// `interval` ms and never going below `floor` (0-255)
+pub struct Flicker {
+    pub start_time: Timestamp,
+    pub interval: u32,
+    pub floor: u8,
+    pub seed: u32,
+}
+
+/// Random walk: every `interval` ms moves up or down by up to `max_step`
+/// (in value units), bouncing off 0 and MAX. Starts at half scale.
+///
+/// Steps are taken per interval, not per update, so the walk is the same
+/// for a given seed whatever the frame rate.
+pub struct RandomWalk<T> {
+    pub start_time: Timestamp,
+    pub interval: u32,
+    pub max_step: T,
+    pub seed: u32,
+    rng: Rng,
+    steps: u64,
+    position: T,
+}
+
+impl<T: EnvelopeValue> RandomWalk<T> {
+    pub fn new(start_time: Timestamp, interval: u32, max_step: T, seed: u32) -> Self {
+        Self {
+            start_time,
+            interval,
+            max_step,
+            seed,
+            rng: Rng::new(seed),
+            steps: 0,
+            position: T::from_u32(T::MAX.to_u32() / 2),
+        }
+    }
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+/// Split time into (lattice cell, position within the cell as 0..65536)
+#[inline(always)]
+fn lattice(now: Timestamp, start_time: Timestamp, period: u32) -> (u64, u32) {
+    let elapsed = now.millis_since(start_time);
+    let cell = elapsed / period as u64;
+    let fraction = ((elapsed % period as u64) << 16) / period as u64;
+    (cell, fraction as u32)
+}
+
+/// Random 16-bit value for a lattice point
+#[inline(always)]
+fn lattice_value(seed: u32, cell: u64) -> u32 {
+    hash(seed, cell) >> 16
+}
+
+/// `a + (b - a) * t` with t in 0..=65536
+#[inline(always)]
+fn lerp16(a: u32, b: u32, t: u32) -> u32 {
+    let (a, b, t) = (a as i64, b as i64, t as i64);
+    (a + (((b - a) * t) >> 16)) as u32
+}
+
+/// 3t² - 2t³ in 16.16 fixed point
+#[inline(always)]
+fn smoothstep16(t: u32) -> u32 {
+    let t = t as u64;
+    ((((t * t) >> 16) * (3 * 65536 - 2 * t)) >> 16) as u32
+}
+
+/// 6t⁵ - 15t⁴ + 10t³ in 16.16 fixed point
+#[inline(always)]
+fn quintic16(t: u32) -> u32 {
+    let t = t as i64;
+    let t3 = (((t * t) >> 16) * t) >> 16;
+    let inner = ((t * ((t * 6) - 15 * 65536)) >> 16) + 10 * 65536;
+    ((t3 * inner) >> 16) as u32
+}
+
+#[inline(always)]
+fn to_value<T: EnvelopeValue>(value16: u32) -> T {
+    T::from_progress(value16.min(65535), 65535)
+}
+
+// ============================================================================
+// ENVELOPES
+// ============================================================================
+
+impl<T> Envelope<T> for ValueNoise
+where
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn sample(&self, now: Timestamp) -> T {
+        let (cell, fraction) = lattice(now, self.start_time, self.period);
+        let a = lattice_value(self.seed, cell);
+        let b = lattice_value(self.seed, cell + 1);
+
+        to_value(lerp16(a, b, smoothstep16(fraction)))
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, _now: Timestamp) -> bool {
+        true
+    }
+}
+
+impl<T> Envelope<T> for PerlinNoise
+where
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn sample(&self, now: Timestamp) -> T {
+        let (cell, fraction) = lattice(now, self.start_time, self.period);
+
+        // Gradients in -1.0..=1.0 (16.16)
+        let gradient = |cell| (lattice_value(self.seed, cell) as i64 * 2) - 65535;
+        let x = fraction as i64;
+        let from_left = (gradient(cell) * x) >> 16;
+        let from_right = (gradient(cell + 1) * (x - 65536)) >> 16;
+
+        let blend = quintic16(fraction) as i64;
+        let noise = from_left + (((from_right - from_left) * blend) >> 16);
+
+        // 1D Perlin stays within ±0.5, shift it into 0..1
+        to_value((noise + 32768).clamp(0, 65535) as u32)
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, _now: Timestamp) -> bool {
+        true
+    }
+}
+
+impl<E, T> Envelope<T> for RandomStep<E>
+where
+    E: Easing,
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn sample(&self, now: Timestamp) -> T {
+        let elapsed = now.millis_since(self.start_time);
+        let cell = elapsed / self.period as u64;
+        let in_cell = (elapsed % self.period as u64) as u32;
+
+        let target = lattice_value(self.seed, cell);
+        if cell == 0 || in_cell >= self.transition {
+            return to_value(target);
+        }
+
+        let previous = lattice_value(self.seed, cell - 1);
+        let eased = self.easing.ease(in_cell as f32 / self.transition as f32);
+        let t = (eased.clamp(0.0, 1.0) * 65536.0) as u32;
+
+        to_value(lerp16(previous, target, t))
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, _now: Timestamp) -> bool {
+        true
+    }
+}
+
+impl Flicker {
+    #[inline(always)]
+    fn level(&self, cell: u64) -> u32 {
+        let bits = hash(self.seed, cell);
+
+        // The brighter of two draws keeps the flame near the top
+        let mut level = (bits & 0xFF).max((bits >> 8) & 0xFF);
+        // Roughly one interval in sixteen gutters down
+        if (bits >> 16) & 0x0F == 0 {
+            level /= 3;
+        }
+
+        let floor = self.floor as u32;
+        (floor + (level * (255 - floor)) / 255) * 257
+    }
+}
+
+impl<T> Envelope<T> for Flicker
+where
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn sample(&self, now: Timestamp) -> T {
+        let (cell, fraction) = lattice(now, self.start_time, self.interval);
+
+        to_value(lerp16(self.level(cell), self.level(cell + 1), fraction))
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, _now: Timestamp) -> bool {
+        true
+    }
+}
+
+impl<T> StatefulEnvelope<T> for RandomWalk<T>
+where
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn update(&mut self, now: Timestamp) -> T {
+        let steps = now.millis_since(self.start_time) / self.interval as u64;
+
+        // Replay from the seed if time went backwards
+        if steps < self.steps {
+            *self = Self::new(self.start_time, self.interval, self.max_step, self.seed);
+        }
+
+        let max = T::MAX.to_u32() as i64;
+        let reach = self.max_step.to_u32() as i64;
+        let span = 2 * reach as u64 + 1;
+
+        for _ in self.steps..steps {
+            // Uniform in -reach..=reach
+            let delta = ((self.rng.next_u32() as u64 * span) >> 32) as i64 - reach;
+            let mut position = self.position.to_u32() as i64 + delta;
+
+            // Bounce off the ends instead of sticking to them
+            if position < 0 {
+                position = -position;
+            } else if position > max {
+                position = 2 * max - position;
+            }
+            self.position = T::from_u32(position.clamp(0, max) as u32);
+        }
+
+        self.steps = steps;
+        self.position
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, _now: Timestamp) -> bool {
+        true
+    }
+}
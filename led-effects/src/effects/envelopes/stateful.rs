@@ -0,0 +1,207 @@
+use crate::effects::core::{
+    time::Timestamp,
+    traits::{Envelope, EnvelopeValue, StatefulEnvelope},
+};
+use core::cell::RefCell;
+use core::marker::PhantomData;
+
+/// Lets a [`StatefulEnvelope`] drive anything that takes an [`Envelope`]
+/// (e.g. `DynamicParam`). Every `sample` advances the inner state.
+pub struct Stateful<S>(RefCell<S>);
+
+/// Exponential smoothing: moves `dt / (time_constant + dt)` of the way to
+/// the inner value on every update. With frames much shorter than
+/// `time_constant` a step settles to about 63% after `time_constant` ms;
+/// longer frames settle slower, down to 50% for a single frame that long.
+pub struct Smooth<E, T> {
+    pub inner: E,
+    pub time_constant: u32,
+    state: Option<(Timestamp, T)>,
+}
+
+/// Slew-rate limiter: follows the inner value, but never rises faster than
+/// `rise_rate` or falls faster than `fall_rate` (value units per second).
+pub struct Slew<E, T> {
+    pub inner: E,
+    pub rise_rate: u32,
+    pub fall_rate: u32,
+    // Level in thousandths of a unit so slow rates still move at high FPS
+    state: Option<(Timestamp, u64)>,
+    _value: PhantomData<T>,
+}
+
+/// Sample-and-hold: samples the inner envelope at the start of every
+/// `period` and holds that value until the next one. A `period` of 0
+/// passes the inner value through.
+pub struct SampleHold<E, T> {
+    pub inner: E,
+    pub start_time: Timestamp,
+    pub period: u32,
+    held: Option<(u64, T)>,
+}
+
+impl<S> Stateful<S> {
+    pub fn new(inner: S) -> Self {
+        Self(RefCell::new(inner))
+    }
+
+    pub fn into_inner(self) -> S {
+        self.0.into_inner()
+    }
+}
+
+impl<E, T> Smooth<E, T> {
+    pub fn new(inner: E, time_constant: u32) -> Self {
+        Self {
+            inner,
+            time_constant,
+            state: None,
+        }
+    }
+}
+
+impl<E, T> Slew<E, T> {
+    pub fn new(inner: E, rise_rate: u32, fall_rate: u32) -> Self {
+        Self {
+            inner,
+            rise_rate,
+            fall_rate,
+            state: None,
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<E, T> SampleHold<E, T> {
+    pub fn new(inner: E, start_time: Timestamp, period: u32) -> Self {
+        Self {
+            inner,
+            start_time,
+            period,
+            held: None,
+        }
+    }
+}
+
+impl<S, T> Envelope<T> for Stateful<S>
+where
+    S: StatefulEnvelope<T>,
+{
+    #[inline(always)]
+    fn sample(&self, now: Timestamp) -> T {
+        self.0.borrow_mut().update(now)
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, now: Timestamp) -> bool {
+        self.0.borrow().is_alive(now)
+    }
+}
+
+impl<E, T> StatefulEnvelope<T> for Smooth<E, T>
+where
+    E: StatefulEnvelope<T>,
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn update(&mut self, now: Timestamp) -> T {
+        let target = self.inner.update(now);
+
+        let value = match self.state {
+            // Time going backwards restarts the filter
+            Some((last, value)) if now >= last => {
+                let dt = now.elapsed_since(last) as u64;
+                let span = self.time_constant as u64 + dt;
+
+                if span == 0 {
+                    target
+                } else {
+                    let current = value.to_u32() as u64;
+                    let goal = target.to_u32() as u64;
+                    let distance = current.abs_diff(goal);
+                    // Round up so the output always reaches the target
+                    let step = (distance * dt).div_ceil(span);
+
+                    if goal > current {
+                        T::from_u32((current + step) as u32)
+                    } else {
+                        T::from_u32((current - step) as u32)
+                    }
+                }
+            }
+            _ => target,
+        };
+
+        self.state = Some((now, value));
+        value
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, now: Timestamp) -> bool {
+        self.inner.is_alive(now)
+    }
+}
+
+impl<E, T> StatefulEnvelope<T> for Slew<E, T>
+where
+    E: StatefulEnvelope<T>,
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn update(&mut self, now: Timestamp) -> T {
+        let goal = self.inner.update(now).to_u32() as u64 * 1000;
+
+        let level = match self.state {
+            Some((last, level)) if now >= last => {
+                let dt = now.elapsed_since(last) as u64;
+
+                if goal > level {
+                    level + (goal - level).min(self.rise_rate as u64 * dt)
+                } else {
+                    level - (level - goal).min(self.fall_rate as u64 * dt)
+                }
+            }
+            _ => goal,
+        };
+
+        self.state = Some((now, level));
+        T::from_u32((level / 1000) as u32)
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, now: Timestamp) -> bool {
+        self.inner.is_alive(now)
+    }
+}
+
+impl<E, T> StatefulEnvelope<T> for SampleHold<E, T>
+where
+    E: StatefulEnvelope<T>,
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn update(&mut self, now: Timestamp) -> T {
+        let Some(index) = now
+            .millis_since(self.start_time)
+            .checked_div(self.period as u64)
+        else {
+            return self.inner.update(now);
+        };
+
+        match self.held {
+            Some((held_index, value)) if held_index == index => value,
+            _ => {
+                // Sample at the period boundary so the result doesn't depend on frame timing
+                let boundary = self.start_time.add_millis(index * self.period as u64);
+                let value = self.inner.update(boundary);
+                self.held = Some((index, value));
+                value
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, now: Timestamp) -> bool {
+        self.inner.is_alive(now)
+    }
+}
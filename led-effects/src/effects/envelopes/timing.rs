@@ -1,27 +1,28 @@
-use core::f32::consts::PI;
-
-use crate::effects::core::traits::{Envelope, EnvelopeValue};
+use crate::effects::core::{
+    time::Timestamp,
+    traits::{Envelope, EnvelopeValue},
+};
 
 pub struct Constant;
 
 pub struct Fade {
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub duration: u32,
     pub inverted: bool,
 }
 
 pub struct Triangle {
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub period: u32,
 }
 
 pub struct Sine {
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub period: u32,
 }
 
-const SINE_LUT_SIZE: usize = 256;
-static SINE_LUT: [u8; SINE_LUT_SIZE] = [
+pub(crate) const SINE_LUT_SIZE: usize = 256;
+pub(crate) static SINE_LUT: [u8; SINE_LUT_SIZE] = [
     128, 131, 134, 137, 140, 143, 146, 149, 152, 155, 158, 162, 165, 167, 170, 173, 176, 179, 182,
     185, 188, 190, 193, 196, 198, 201, 203, 206, 208, 211, 213, 215, 218, 220, 222, 224, 226, 228,
     230, 232, 234, 235, 237, 238, 240, 241, 243, 244, 245, 246, 248, 249, 250, 250, 251, 252, 253,
@@ -38,18 +39,18 @@ static SINE_LUT: [u8; SINE_LUT_SIZE] = [
 ];
 
 pub struct Square {
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub period: u32,    // full period in ms
     pub duty_cycle: u8, // 0-255 -> 0-100%
 }
 
 pub struct Sawtooth {
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub period: u32,
 }
 
 pub struct ADSR {
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub attack: u32,           // Attack time in ms
     pub decay: u32,            // Decay time in ms
     pub sustain_level: u8,     // Sustain level (0 - 255)
@@ -62,12 +63,12 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, _now: u32) -> T {
+    fn sample(&self, _now: Timestamp) -> T {
         T::ONE
     }
 
     #[inline(always)]
-    fn is_alive(&self, _now: u32) -> bool {
+    fn is_alive(&self, _now: Timestamp) -> bool {
         true
     }
 }
@@ -77,11 +78,11 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
-        let elapsed = now.saturating_sub(self.start_time);
+    fn sample(&self, now: Timestamp) -> T {
+        let elapsed = now.elapsed_since(self.start_time);
 
         if elapsed >= self.duration {
-            return if self.inverted { T::ZERO } else { T::ONE };
+            return if self.inverted { T::ZERO } else { T::MAX };
         }
 
         let progress = T::from_progress(elapsed, self.duration);
@@ -94,8 +95,8 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
-        now < self.start_time + self.duration
+    fn is_alive(&self, now: Timestamp) -> bool {
+        now < self.start_time.add_millis(self.duration as u64)
     }
 }
 
@@ -104,25 +105,25 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
-        let elapsed = now.saturating_sub(self.start_time);
-        let elapsed_in_cycle = elapsed % self.period;
+    fn sample(&self, now: Timestamp) -> T {
+        let elapsed_in_cycle = now.phase(self.start_time, self.period) as u64;
+        let period = self.period as u64;
 
         // Double the phase to avoid division (0 to 2*period range)
         let phase_doubled = elapsed_in_cycle * 2;
 
-        if phase_doubled < self.period {
+        if phase_doubled < period {
             // First half: ascending 0 → MAX
-            T::from_progress(phase_doubled, self.period)
+            T::from_progress(phase_doubled as u32, self.period)
         } else {
             // Second half: descending MAX → 0
-            let descending = phase_doubled - self.period;
+            let descending = (phase_doubled - period) as u32;
             T::MAX.saturating_sub(T::from_progress(descending, self.period))
         }
     }
 
     #[inline(always)]
-    fn is_alive(&self, _now: u32) -> bool {
+    fn is_alive(&self, _now: Timestamp) -> bool {
         true
     }
 }
@@ -132,9 +133,8 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
-        let elapsed = now.saturating_sub(self.start_time);
-        let elapsed_in_cycle = (elapsed % self.period) as u64;
+    fn sample(&self, now: Timestamp) -> T {
+        let elapsed_in_cycle = now.phase(self.start_time, self.period) as u64;
 
         let phase_index = ((elapsed_in_cycle * SINE_LUT_SIZE as u64) / self.period as u64) as usize;
         let sine_u8 = SINE_LUT[phase_index & (SINE_LUT_SIZE - 1)];
@@ -143,7 +143,7 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, _now: u32) -> bool {
+    fn is_alive(&self, _now: Timestamp) -> bool {
         true
     }
 }
@@ -153,9 +153,8 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
-        let elapsed = now.saturating_sub(self.start_time);
-        let elapsed_in_cycle = (elapsed % self.period) as u64;
+    fn sample(&self, now: Timestamp) -> T {
+        let elapsed_in_cycle = now.phase(self.start_time, self.period) as u64;
 
         let threshold = (self.period as u64 * self.duty_cycle as u64) / 255;
 
@@ -167,7 +166,7 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, _now: u32) -> bool {
+    fn is_alive(&self, _now: Timestamp) -> bool {
         true
     }
 }
@@ -177,15 +176,14 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
-        let elapsed = now.saturating_sub(self.start_time);
-        let elapsed_in_cycle = elapsed % self.period;
+    fn sample(&self, now: Timestamp) -> T {
+        let elapsed_in_cycle = now.phase(self.start_time, self.period);
 
         T::from_progress(elapsed_in_cycle, self.period)
     }
 
     #[inline(always)]
-    fn is_alive(&self, _now: u32) -> bool {
+    fn is_alive(&self, _now: Timestamp) -> bool {
         true
     }
 }
@@ -195,15 +193,20 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
-        let elapsed = now.saturating_sub(self.start_time);
+    fn sample(&self, now: Timestamp) -> T {
+        let elapsed = now.millis_since(self.start_time);
+
+        // Phase boundaries in u64 so long phases can't overflow
+        let decay_start = self.attack as u64;
+        let sustain_start = decay_start + self.decay as u64;
+        let release_start = sustain_start + self.sustain_duration as u64;
 
-        if elapsed < self.attack {
+        if elapsed < decay_start {
             // Attack phase: ramp 0 → MAX
-            T::from_progress(elapsed, self.attack)
-        } else if elapsed < self.attack + self.decay {
+            T::from_progress(elapsed as u32, self.attack)
+        } else if elapsed < sustain_start {
             // Decay phase: fall MAX → sustain_level
-            let decay_elapsed = elapsed - self.attack;
+            let decay_elapsed = (elapsed - decay_start) as u32;
             let decay_progress = T::from_progress(decay_elapsed, self.decay);
 
             // Interpolate: MAX - (MAX - sustain_level) * progress
@@ -211,19 +214,18 @@ where
             let range = T::MAX.saturating_sub(sustain_t);
             let drop = range.saturating_mul_div(decay_progress);
             T::MAX.saturating_sub(drop)
-        } else if elapsed < self.attack + self.decay + self.sustain_duration {
+        } else if elapsed < release_start {
             // Sustain phase: hold at sustain_level
             T::from_progress(self.sustain_level as u32, 255)
         } else {
             // Release phase: fall sustain_level → 0
-            let release_start = self.attack + self.decay + self.sustain_duration;
-            let release_elapsed = elapsed.saturating_sub(release_start);
+            let release_elapsed = elapsed - release_start;
 
-            if release_elapsed >= self.release {
+            if release_elapsed >= self.release as u64 {
                 return T::ZERO;
             }
 
-            let release_progress = T::from_progress(release_elapsed, self.release);
+            let release_progress = T::from_progress(release_elapsed as u32, self.release);
             let sustain_t = T::from_progress(self.sustain_level as u32, 255);
 
             // sustain_level * (1 - progress)
@@ -233,8 +235,11 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
-        let total = self.attack + self.decay + self.sustain_duration + self.release;
-        now < self.start_time + total
+    fn is_alive(&self, now: Timestamp) -> bool {
+        let total = self.attack as u64
+            + self.decay as u64
+            + self.sustain_duration as u64
+            + self.release as u64;
+        now < self.start_time.add_millis(total)
     }
 }
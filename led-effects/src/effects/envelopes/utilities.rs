@@ -1,27 +1,31 @@
-use crate::effects::core::traits::{Envelope, EnvelopeValue};
+use crate::effects::core::{
+    time::Timestamp,
+    traits::{Envelope, EnvelopeValue},
+};
+use core::cell::Cell;
 
 pub struct TimeLimited<E> {
     pub inner: E,
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub duration: u32,
 }
 
 pub struct LoopCount<E> {
     pub inner: E,
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub period: u32,
     pub max_loops: u32,
 }
 
 pub struct Pulse {
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub attack: u32,
     pub decay: u32,
 }
 
 // Add exponential decay LUT (256 entries, exp(-5*t) for t in 0-1)
-const DECAY_LUT_SIZE: usize = 256;
-static DECAY_LUT: [u8; DECAY_LUT_SIZE] = [
+pub(crate) const DECAY_LUT_SIZE: usize = 256;
+pub(crate) static DECAY_LUT: [u8; DECAY_LUT_SIZE] = [
     255, 250, 245, 240, 235, 230, 226, 221, 217, 212, 208, 204, 200, 196, 192, 188, 184, 181, 177,
     174, 170, 167, 163, 160, 157, 154, 151, 148, 145, 142, 139, 136, 134, 131, 128, 126, 123, 121,
     118, 116, 114, 111, 109, 107, 105, 103, 101, 99, 97, 95, 93, 91, 89, 87, 86, 84, 82, 81, 79,
@@ -35,12 +39,88 @@ static DECAY_LUT: [u8; DECAY_LUT_SIZE] = [
     0, 0,
 ];
 
+/// Integrates `velocity_envelope` in `dt_ms` steps from `start_time`.
+///
+/// The running position is cached, so sampling forward in time only
+/// integrates the new steps. Sampling earlier than the last call restarts
+/// from `start_time`, and so does changing a parameter through its setter.
+/// A `dt_ms` of 0 counts as 1.
+///
+/// The cache assumes the velocity depends on time alone. A live source
+/// (`SharedParam`, `DmxSlot`, `AudioLevel`) keeps the velocities the cached
+/// steps were integrated with, and a wrapper that restarts the envelope's
+/// time on its own, such as `Triggered`, changes it without the cache
+/// seeing. Call [`reset`](Self::reset) when such a source changes to
+/// integrate from scratch with its current values.
 pub struct VelocityIntegral<V> {
-    pub start_time: u32,
-    pub velocity_envelope: V,
-    pub initial_position: u32,
-    pub dt_ms: u32,
-    pub velocity_scale: u32,
+    start_time: Timestamp,
+    velocity_envelope: V,
+    initial_position: u32,
+    dt_ms: u32,
+    velocity_scale: u32,
+    progress: Cell<IntegralProgress>,
+}
+
+#[derive(Clone, Copy)]
+struct IntegralProgress {
+    steps: u64,
+    position: u32,
+}
+
+impl<V> VelocityIntegral<V> {
+    pub fn new(
+        start_time: Timestamp,
+        velocity_envelope: V,
+        initial_position: u32,
+        dt_ms: u32,
+        velocity_scale: u32,
+    ) -> Self {
+        Self {
+            start_time,
+            velocity_envelope,
+            initial_position,
+            dt_ms: dt_ms.max(1),
+            velocity_scale,
+            progress: Cell::new(IntegralProgress {
+                steps: 0,
+                position: initial_position,
+            }),
+        }
+    }
+
+    pub fn set_start_time(&mut self, start_time: Timestamp) {
+        self.start_time = start_time;
+        self.reset();
+    }
+
+    pub fn set_initial_position(&mut self, initial_position: u32) {
+        self.initial_position = initial_position;
+        self.reset();
+    }
+
+    pub fn set_dt_ms(&mut self, dt_ms: u32) {
+        self.dt_ms = dt_ms.max(1);
+        self.reset();
+    }
+
+    pub fn set_velocity_scale(&mut self, velocity_scale: u32) {
+        self.velocity_scale = velocity_scale;
+        self.reset();
+    }
+
+    /// The velocity envelope, for changing it; the integral starts over
+    pub fn velocity_envelope_mut(&mut self) -> &mut V {
+        self.reset();
+        &mut self.velocity_envelope
+    }
+
+    /// Drop the cached position so the next sample integrates from scratch
+    pub fn reset(&self) {
+        self.progress.set(IntegralProgress {
+            steps: 0,
+            position: self.initial_position,
+        });
+    }
 }
 
 impl<E, T> Envelope<T> for TimeLimited<E>
@@ -48,13 +128,13 @@ where
     E: Envelope<T>,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
+    fn sample(&self, now: Timestamp) -> T {
         self.inner.sample(now)
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
-        now < self.start_time + self.duration && self.inner.is_alive(now)
+    fn is_alive(&self, now: Timestamp) -> bool {
+        now < self.start_time.add_millis(self.duration as u64) && self.inner.is_alive(now)
     }
 }
 
@@ -63,16 +143,15 @@ where
     E: Envelope<T>,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
+    fn sample(&self, now: Timestamp) -> T {
         self.inner.sample(now)
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
-        let elapsed = now.saturating_sub(self.start_time);
-        let loops_completed = elapsed / self.period;
+    fn is_alive(&self, now: Timestamp) -> bool {
+        let loops_completed = now.millis_since(self.start_time) / self.period as u64;
 
-        loops_completed < self.max_loops && self.inner.is_alive(now)
+        loops_completed < self.max_loops as u64 && self.inner.is_alive(now)
     }
 }
 
@@ -81,13 +160,13 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
-        let elapsed = now.saturating_sub(self.start_time);
+    fn sample(&self, now: Timestamp) -> T {
+        let elapsed = now.elapsed_since(self.start_time);
 
         if elapsed < self.attack {
             // Attack phase: ramp 0 → MAX
             T::from_progress(elapsed, self.attack)
-        } else if elapsed < self.attack + self.decay {
+        } else if (elapsed as u64) < self.attack as u64 + self.decay as u64 {
             // Decay phase: exponential decay using LUT
             let decay_elapsed = elapsed - self.attack;
             let decay_progress =
@@ -102,8 +181,10 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
-        now < self.start_time + self.attack + self.decay
+    fn is_alive(&self, now: Timestamp) -> bool {
+        now < self
+            .start_time
+            .add_millis(self.attack as u64 + self.decay as u64)
     }
 }
 
@@ -113,15 +194,22 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> T {
-        let elapsed = now.saturating_sub(self.start_time);
-        let steps = (elapsed / self.dt_ms).max(1);
+    fn sample(&self, now: Timestamp) -> T {
+        let elapsed = now.millis_since(self.start_time);
+        let steps = (elapsed / self.dt_ms as u64).max(1);
+
+        // Continue from the cached step, or start over if time went backwards
+        let mut progress = self.progress.get();
+        if steps < progress.steps {
+            self.reset();
+            progress = self.progress.get();
+        }
 
         // Accumulate in u32 (wide enough for any position)
-        let mut position: u32 = self.initial_position;
+        let mut position: u32 = progress.position;
 
-        for i in 0..steps {
-            let t = self.start_time + (i * self.dt_ms);
+        for i in progress.steps..steps {
+            let t = self.start_time.add_millis(i * self.dt_ms as u64);
             let velocity = self.velocity_envelope.sample(t);
 
             // Convert T velocity to u32, scale it, then add to position
@@ -131,13 +219,15 @@ where
             position = position.saturating_add(position_delta);
         }
 
+        self.progress.set(IntegralProgress { steps, position });
+
         // Map position to T range with wrapping
         let wrapped = position % T::MAX.to_u32();
         T::from_progress(wrapped, T::MAX.to_u32())
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
+    fn is_alive(&self, now: Timestamp) -> bool {
         self.velocity_envelope.is_alive(now)
     }
 }
@@ -1,7 +1,9 @@
 pub mod complex;
 pub mod movement;
+pub mod particles;
 pub mod static_patterns;
 
 // Re-export generators
 pub use movement::{Chase, Pulse};
+pub use particles::{Boundary, Emitter, ParticlePool, Particles};
 pub use static_patterns::{Gradient, SolidColor, Stripes};
@@ -1,5 +1,6 @@
 use crate::effects::core::{
     pixel::Pixel,
+    time::Timestamp,
     traits::{Generator, HueParameter, Parameter},
 };
 
@@ -11,7 +12,7 @@ where
     Hue: HueParameter,
     Sat: Parameter<u8>,
 {
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub duration: u32,
     pub position: Pos,
     pub width: Width,
@@ -29,7 +30,7 @@ where
     Sat: Parameter<u8>,
 {
     #[inline(always)]
-    fn generate(&mut self, buffer: &mut [Pixel], now: u32) {
+    fn generate(&mut self, buffer: &mut [Pixel], now: Timestamp) {
         let pos = self.position.sample(now);
         let width = self.width.sample(now);
         let intensity = self.intensity.sample(now);
@@ -42,7 +43,7 @@ where
         for i in -width_pixels..=width_pixels {
             let idx = center + i;
             if idx >= 0 && (idx as usize) < buffer.len() {
-                let dist = i.abs() as u8;
+                let dist = i.unsigned_abs() as u8;
 
                 // Calculate falloff: 255 at center, 0 at edges
                 let falloff_u8 = if dist >= width {
@@ -61,8 +62,8 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
-        now < self.start_time + self.duration
+    fn is_alive(&self, now: Timestamp) -> bool {
+        now < self.start_time.add_millis(self.duration as u64)
     }
 }
 
@@ -73,7 +74,7 @@ where
     Hue: HueParameter,
     Sat: Parameter<u8>,
 {
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub duration: u32,
     pub position: usize,
     pub spread_speed: f32, // Pixels per millisecond (keep f32 for smooth speed)
@@ -91,8 +92,8 @@ where
     Sat: Parameter<u8>,
 {
     #[inline(always)]
-    fn generate(&mut self, buffer: &mut [Pixel], now: u32) {
-        let elapsed = now.saturating_sub(self.start_time);
+    fn generate(&mut self, buffer: &mut [Pixel], now: Timestamp) {
+        let elapsed = now.elapsed_since(self.start_time);
         let width = self.width.sample(now);
         let intensity = self.intensity.sample(now);
         let hue = self.hue.sample(now);
@@ -105,7 +106,7 @@ where
             for i in -width_pixels..=width_pixels {
                 let idx = self.position as isize + edge_offset + i;
                 if idx >= 0 && (idx as usize) < buffer.len() {
-                    let dist_from_edge = i.abs() as u8;
+                    let dist_from_edge = i.unsigned_abs() as u8;
 
                     // Calculate falloff: 255 at center, 0 at edges
                     let falloff_u8 = if dist_from_edge >= width {
@@ -125,7 +126,7 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
-        now < self.start_time + self.duration
+    fn is_alive(&self, now: Timestamp) -> bool {
+        now < self.start_time.add_millis(self.duration as u64)
     }
 }
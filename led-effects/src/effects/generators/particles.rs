@@ -0,0 +1,285 @@
+use crate::effects::core::{
+    pixel::Pixel,
+    time::Timestamp,
+    traits::{Envelope, Generator, HueParameter},
+};
+use crate::rng::Rng;
+
+/// Longest physics step; a stalled frame doesn't fling particles off the strip
+const MAX_STEP_MS: u64 = 100;
+
+/// Where new particles come from. Positions are in pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Emitter {
+    /// `rate` particles per second from one spot (fountains, comets)
+    Point { position: f32, rate: u16 },
+    /// `rate` particles per second anywhere on the strip (rain, stars)
+    Random { rate: u16 },
+    /// `count` particles at once from one spot on every [`Particles::fire`]
+    /// (fireworks), or each time a [`Triggered`](crate::Triggered) starts it
+    Burst { position: f32, count: u16 },
+}
+
+/// What happens when a particle reaches either end of the strip
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Boundary {
+    /// Falls off the end and dies
+    Kill,
+    /// Reflects, keeping `restitution` (0.0-1.0) of its speed
+    Bounce { restitution: f32 },
+    /// Comes back in at the other end
+    Wrap,
+}
+
+#[derive(Clone, Copy)]
+struct Particle {
+    position: f32,
+    velocity: f32, // Pixels per second
+    born: Timestamp,
+    lifetime: u32,
+    hue: u8,
+    alive: bool,
+}
+
+impl Particle {
+    const DEAD: Self = Self {
+        position: 0.0,
+        velocity: 0.0,
+        born: Timestamp::ZERO,
+        lifetime: 0,
+        hue: 0,
+        alive: false,
+    };
+}
+
+/// Fixed-capacity particle storage for [`Particles`]. When all `N` slots
+/// are in use new particles are dropped until one dies.
+pub struct ParticlePool<const N: usize> {
+    particles: [Particle; N],
+    last_update: Option<Timestamp>,
+    emit_remainder: u32, // Thousandths of a particle carried to the next frame
+    pending: u32,        // Burst particles waiting for the next frame
+}
+
+impl<const N: usize> ParticlePool<N> {
+    pub const fn new() -> Self {
+        Self {
+            particles: [Particle::DEAD; N],
+            last_update: None,
+            emit_remainder: 0,
+            pending: 0,
+        }
+    }
+
+    /// Number of live particles
+    pub fn alive(&self) -> usize {
+        self.particles.iter().filter(|p| p.alive).count()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn spawn(&mut self, particle: Particle) {
+        if let Some(slot) = self.particles.iter_mut().find(|p| !p.alive) {
+            *slot = particle;
+        }
+    }
+}
+
+impl<const N: usize> Default for ParticlePool<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Particle system: up to `N` point lights with their own velocity and
+/// lifetime, rendered anti-aliased between pixels.
+///
+/// `intensity` is sampled at each particle's age, scaled so that
+/// `0..lifetime` ms covers its whole life (e.g. an inverted `Fade` with
+/// `duration: lifetime`). The hue is picked at birth and drifts by
+/// `hue_shift` over the particle's life.
+pub struct Particles<const N: usize, Hue, Intensity>
+where
+    Hue: HueParameter,
+    Intensity: Envelope<u8>,
+{
+    pub start_time: Timestamp,
+    pub duration: u32, // How long continuous emitters run
+    pub emitter: Emitter,
+    pub min_velocity: f32, // Pixels per second, negative = toward pixel 0
+    pub max_velocity: f32,
+    pub gravity: f32, // Pixels per second², positive = toward the far end
+    pub drag: f32,    // Fraction of velocity lost per second
+    pub lifetime: u32,
+    pub lifetime_jitter: u32, // Up to this many ms added at random
+    pub boundary: Boundary,
+    pub hue: Hue,
+    pub hue_spread: u8, // Random hue offset added at birth
+    pub hue_shift: i8,  // Hue change over a whole life
+    pub saturation: u8,
+    pub intensity: Intensity,
+    pub rng: Rng,
+    pub pool: ParticlePool<N>,
+}
+
+impl<const N: usize, Hue, Intensity> Particles<N, Hue, Intensity>
+where
+    Hue: HueParameter,
+    Intensity: Envelope<u8>,
+{
+    /// Queue a burst for the next frame (only for [`Emitter::Burst`])
+    pub fn fire(&mut self) {
+        if let Emitter::Burst { count, .. } = self.emitter {
+            self.pool.pending = self.pool.pending.saturating_add(count as u32);
+        }
+    }
+
+    #[inline(always)]
+    fn unit_random(&mut self) -> f32 {
+        self.rng.next_u32() as f32 / u32::MAX as f32
+    }
+
+    fn emit(&mut self, count: u32, len: usize, now: Timestamp) {
+        for _ in 0..count {
+            let position = match self.emitter {
+                Emitter::Point { position, .. } | Emitter::Burst { position, .. } => position,
+                Emitter::Random { .. } => self.rng.below(len as u32) as f32,
+            };
+            let velocity =
+                self.min_velocity + (self.max_velocity - self.min_velocity) * self.unit_random();
+            let lifetime = self.lifetime + self.rng.below(self.lifetime_jitter);
+            let hue = self
+                .hue
+                .sample(now)
+                .wrapping_add(self.rng.below(self.hue_spread as u32 + 1) as u8);
+
+            self.pool.spawn(Particle {
+                position,
+                velocity,
+                born: now,
+                lifetime,
+                hue,
+                alive: true,
+            });
+        }
+    }
+
+    fn step(&mut self, dt: f32, len: usize, now: Timestamp) {
+        let end = (len - 1) as f32;
+        let damping = (1.0 - self.drag * dt).max(0.0);
+
+        for particle in self.pool.particles.iter_mut().filter(|p| p.alive) {
+            if now.millis_since(particle.born) >= particle.lifetime as u64 {
+                particle.alive = false;
+                continue;
+            }
+
+            particle.velocity = (particle.velocity + self.gravity * dt) * damping;
+            particle.position += particle.velocity * dt;
+
+            match self.boundary {
+                Boundary::Kill => {
+                    if particle.position < 0.0 || particle.position > end {
+                        particle.alive = false;
+                    }
+                }
+                Boundary::Bounce { restitution } => {
+                    if particle.position < 0.0 {
+                        particle.position = -particle.position;
+                        particle.velocity = -particle.velocity * restitution;
+                    } else if particle.position > end {
+                        particle.position = 2.0 * end - particle.position;
+                        particle.velocity = -particle.velocity * restitution;
+                    }
+                    // A very fast particle could overshoot the far end too
+                    particle.position = particle.position.clamp(0.0, end);
+                }
+                Boundary::Wrap => {
+                    let wrapped = libm::fmodf(particle.position, len as f32);
+                    particle.position = if wrapped < 0.0 {
+                        wrapped + len as f32
+                    } else {
+                        wrapped
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl<const N: usize, Hue, Intensity> Generator for Particles<N, Hue, Intensity>
+where
+    Hue: HueParameter,
+    Intensity: Envelope<u8>,
+{
+    fn generate(&mut self, buffer: &mut [Pixel], now: Timestamp) {
+        let len = buffer.len();
+        if len == 0 {
+            return;
+        }
+
+        // Start over if time went backwards (e.g. a restarted effect loop)
+        let dt_ms = match self.pool.last_update {
+            Some(last) if now >= last => now.millis_since(last).min(MAX_STEP_MS),
+            Some(_) => {
+                self.pool.clear();
+                0
+            }
+            None => 0,
+        };
+        self.pool.last_update = Some(now);
+
+        self.step(dt_ms as f32 / 1000.0, len, now);
+
+        let emitting =
+            now >= self.start_time && now < self.start_time.add_millis(self.duration as u64);
+        let rate = match self.emitter {
+            Emitter::Point { rate, .. } | Emitter::Random { rate } if emitting => rate as u32,
+            _ => 0,
+        };
+        let owed = self.pool.emit_remainder + rate * dt_ms as u32;
+        self.pool.emit_remainder = owed % 1000;
+        let count = owed / 1000 + core::mem::take(&mut self.pool.pending);
+        self.emit(count, len, now);
+
+        for particle in self.pool.particles.iter().filter(|p| p.alive) {
+            let age = now.millis_since(particle.born);
+            let life_time = age * self.lifetime.max(1) as u64 / particle.lifetime.max(1) as u64;
+            let intensity = self.intensity.sample(Timestamp::from_millis(life_time));
+
+            let shift = (self.hue_shift as i64 * age as i64) / particle.lifetime.max(1) as i64;
+            let hue = particle.hue.wrapping_add(shift as u8);
+
+            // Split the light between the two nearest pixels
+            let floor = libm::floorf(particle.position);
+            let fraction = ((particle.position - floor) * 256.0) as u32;
+            let index = floor as isize;
+            for (offset, weight) in [(0, 256 - fraction), (1, fraction)] {
+                let mut idx = index + offset;
+                if self.boundary == Boundary::Wrap {
+                    idx = idx.rem_euclid(len as isize);
+                }
+                if idx >= 0 && (idx as usize) < len && weight > 0 {
+                    let value = ((intensity as u32 * weight) >> 8) as u8;
+                    let color = Pixel::from_hsv(hue, self.saturation, value);
+                    buffer[idx as usize] = buffer[idx as usize].add(color);
+                }
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, now: Timestamp) -> bool {
+        now < self.start_time.add_millis(self.duration as u64)
+            || self.pool.alive() > 0
+            || self.pool.pending > 0
+    }
+
+    /// Start with an empty pool and, for a burst emitter, a burst
+    fn restart(&mut self) {
+        self.pool.clear();
+        self.fire();
+    }
+}
@@ -1,5 +1,6 @@
 use crate::effects::core::{
     pixel::Pixel,
+    time::Timestamp,
     traits::{Generator, HueParameter, Parameter},
 };
 
@@ -9,7 +10,7 @@ where
     Hue: HueParameter,
     Sat: Parameter<u8>,
 {
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub duration: u32,
     pub intensity: Intensity,
     pub hue: Hue,
@@ -23,7 +24,7 @@ where
     Sat: Parameter<u8>,
 {
     #[inline(always)]
-    fn generate(&mut self, buffer: &mut [Pixel], now: u32) {
+    fn generate(&mut self, buffer: &mut [Pixel], now: Timestamp) {
         let intensity = self.intensity.sample(now);
         let hue = self.hue.sample(now);
         let saturation = self.saturation.sample(now);
@@ -33,8 +34,8 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
-        now < self.start_time + self.duration
+    fn is_alive(&self, now: Timestamp) -> bool {
+        now < self.start_time.add_millis(self.duration as u64)
     }
 }
 
@@ -45,7 +46,7 @@ where
     Hue2: HueParameter,
     Sat: Parameter<u8>,
 {
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub duration: u32,
     pub intensity: Intensity,
     pub hue_start: Hue1,
@@ -61,7 +62,7 @@ where
     Sat: Parameter<u8>,
 {
     #[inline(always)]
-    fn generate(&mut self, buffer: &mut [Pixel], now: u32) {
+    fn generate(&mut self, buffer: &mut [Pixel], now: Timestamp) {
         let intensity = self.intensity.sample(now);
         let hue_start = self.hue_start.sample(now);
         let hue_end = self.hue_end.sample(now);
@@ -93,8 +94,8 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
-        now < self.start_time + self.duration
+    fn is_alive(&self, now: Timestamp) -> bool {
+        now < self.start_time.add_millis(self.duration as u64)
     }
 }
 
@@ -106,7 +107,7 @@ where
     Sat: Parameter<u8>,
     Width: Parameter<usize>,
 {
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub duration: u32,
     pub intensity: Intensity,
     pub hue1: Hue1,
@@ -124,7 +125,7 @@ where
     Width: Parameter<usize>,
 {
     #[inline(always)]
-    fn generate(&mut self, buffer: &mut [Pixel], now: u32) {
+    fn generate(&mut self, buffer: &mut [Pixel], now: Timestamp) {
         let intensity = self.intensity.sample(now);
         let hue1 = self.hue1.sample(now);
         let hue2 = self.hue2.sample(now);
@@ -140,7 +141,7 @@ where
     }
 
     #[inline(always)]
-    fn is_alive(&self, now: u32) -> bool {
-        now < self.start_time + self.duration
+    fn is_alive(&self, now: Timestamp) -> bool {
+        now < self.start_time.add_millis(self.duration as u64)
     }
 }
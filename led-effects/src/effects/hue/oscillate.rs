@@ -1,8 +1,8 @@
 // hue/oscillate.rs
-use crate::effects::core::traits::HueParameter;
+use crate::effects::core::{time::Timestamp, traits::HueParameter};
 
 pub struct HueOscillate {
-    pub start_time: u32,
+    pub start_time: Timestamp,
     pub period: u32,
     pub hue1: u8, // 0-255
     pub hue2: u8, // 0-255
@@ -10,9 +10,8 @@ pub struct HueOscillate {
 
 impl HueParameter for HueOscillate {
     #[inline(always)]
-    fn sample(&self, now: u32) -> u8 {
-        let elapsed = now.saturating_sub(self.start_time);
-        let elapsed_in_cycle = (elapsed % self.period) as u64;
+    fn sample(&self, now: Timestamp) -> u8 {
+        let elapsed_in_cycle = now.phase(self.start_time, self.period) as u64;
 
         // Triangle wave: 0 → period → 0
         let phase_doubled = elapsed_in_cycle * 2;
@@ -1,4 +1,4 @@
-use crate::effects::core::traits::HueParameter;
+use crate::effects::core::{time::Timestamp, traits::HueParameter};
 
 pub struct StaticHue {
     hue_normalized: u8, // 0-255
@@ -31,7 +31,7 @@ impl StaticHue {
 
 impl HueParameter for StaticHue {
     #[inline(always)]
-    fn sample(&self, _now: u32) -> u8 {
+    fn sample(&self, _now: Timestamp) -> u8 {
         self.hue_normalized
     }
 }
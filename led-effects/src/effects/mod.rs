@@ -9,12 +9,15 @@ pub mod generators;
 pub mod hue;
 pub mod modifiers;
 pub mod parameters;
+pub mod tempo;
 
 // Re-export core traits and types
-pub use core::{Envelope, Generator, HueParameter, Modifier, Parameter, Pixel};
+pub use core::{
+    Envelope, Generator, HueParameter, Modifier, Parameter, Pixel, StatefulEnvelope, Timestamp,
+};
 
 // Re-export composition utilities
-pub use composition::{EffectBuilder, WithModifier};
+pub use composition::{EffectBuilder, Retrigger, Trigger, Triggered, WithModifier};
 
 // Re-export common envelope types
 pub use envelopes::{
@@ -27,26 +30,40 @@ pub use envelopes::{
     EaseOutQuad,
     // Basic envelopes
     Fade,
+    // Noise
+    Flicker,
     Invert,
     // Easing functions
     Linear,
     LoopCount,
     Max,
     Min,
+    PerlinNoise,
     Product,
     // Utilities
     Pulse as EnvelopePulse,
+    RandomStep,
+    RandomWalk,
+    SampleHold,
     Sawtooth,
     Sine,
+    Slew,
+    Smooth,
     Square,
+    // Stateful adapters
+    Stateful,
     Sum,
     TimeLimited,
     Triangle,
+    ValueNoise,
     VelocityIntegral,
 };
 
+// Re-export tempo clock and beat envelopes
+pub use tempo::{BEAT, BarFade, BeatPulse, BeatSaw, BeatSine, TempoClock, TimeSignature};
+
 // Re-export parameter types
-pub use parameters::{DynamicParam, StaticParam};
+pub use parameters::{DMX_SLOTS, DmxSlot, DmxUniverse, DynamicParam, SharedParam, StaticParam};
 
 // Re-export hue parameters
 pub use hue::{HueOscillate, RotatingHue, StaticHue};
@@ -58,4 +75,6 @@ pub use modifiers::{
 };
 
 // Re-export generators
-pub use generators::{Chase, Gradient, Pulse, SolidColor, Stripes};
+pub use generators::{
+    Boundary, Chase, Emitter, Gradient, ParticlePool, Particles, Pulse, SolidColor, Stripes,
+};
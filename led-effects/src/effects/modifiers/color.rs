@@ -1,5 +1,6 @@
 use crate::effects::core::{
     pixel::Pixel,
+    time::Timestamp,
     traits::{Modifier, Parameter},
 };
 
@@ -31,7 +32,7 @@ where
     Factor: Parameter<u8>,
 {
     #[inline(always)]
-    fn modify(&mut self, buffer: &mut [Pixel], now: u32) {
+    fn modify(&mut self, buffer: &mut [Pixel], now: Timestamp) {
         let factor = self.factor.sample(now);
         for pixel in buffer.iter_mut() {
             *pixel = pixel.scale(factor);
@@ -44,7 +45,7 @@ where
     Factor: Parameter<u8>,
 {
     #[inline(always)]
-    fn modify(&mut self, buffer: &mut [Pixel], now: u32) {
+    fn modify(&mut self, buffer: &mut [Pixel], now: Timestamp) {
         let factor = self.factor.sample(now);
         for pixel in buffer.iter_mut() {
             *pixel = pixel.adjust_saturation(factor);
@@ -57,7 +58,7 @@ where
     Amount: Parameter<u8>,
 {
     #[inline(always)]
-    fn modify(&mut self, buffer: &mut [Pixel], now: u32) {
+    fn modify(&mut self, buffer: &mut [Pixel], now: Timestamp) {
         let shift = self.amount.sample(now);
         for pixel in buffer.iter_mut() {
             *pixel = pixel.shift_hue(shift);
@@ -67,7 +68,7 @@ where
 
 impl Modifier for GammaCorrection {
     #[inline(always)]
-    fn modify(&mut self, buffer: &mut [Pixel], _now: u32) {
+    fn modify(&mut self, buffer: &mut [Pixel], _now: Timestamp) {
         for pixel in buffer.iter_mut() {
             *pixel = pixel.gamma_correct();
         }
@@ -1,7 +1,9 @@
 use crate::effects::core::{
     pixel::Pixel,
+    time::Timestamp,
     traits::{HueParameter, Modifier, Parameter},
 };
+use crate::rng::Rng;
 
 pub struct Trail<DecayRate>
 where
@@ -21,7 +23,7 @@ where
     pub hue: Hue,
     pub saturation: Sat,
     pub intensity: Intensity,
-    pub seed: u32,
+    pub rng: Rng,
 }
 
 impl<DecayRate> Modifier for Trail<DecayRate>
@@ -29,7 +31,7 @@ where
     DecayRate: Parameter<u8>,
 {
     #[inline(always)]
-    fn modify(&mut self, buffer: &mut [Pixel], now: u32) {
+    fn modify(&mut self, buffer: &mut [Pixel], now: Timestamp) {
         let decay = self.decay_rate.sample(now);
         for pixel in buffer.iter_mut() {
             *pixel = pixel.scale(decay);
@@ -45,15 +47,14 @@ where
     Intensity: Parameter<u8>,
 {
     #[inline(always)]
-    fn modify(&mut self, buffer: &mut [Pixel], now: u32) {
+    fn modify(&mut self, buffer: &mut [Pixel], now: Timestamp) {
         let chance = self.chance.sample(now);
         let hue = self.hue.sample(now);
         let saturation = self.saturation.sample(now);
         let intensity = self.intensity.sample(now);
 
         for pixel in buffer.iter_mut() {
-            self.seed = self.seed.wrapping_mul(1103515245).wrapping_add(12345);
-            if ((self.seed >> 24) as u8) < chance {
+            if self.rng.chance(chance) {
                 *pixel = Pixel::from_hsv(hue, saturation, intensity);
             }
         }
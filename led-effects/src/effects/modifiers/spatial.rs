@@ -1,5 +1,6 @@
 use crate::effects::core::{
     pixel::Pixel,
+    time::Timestamp,
     traits::{Modifier, Parameter},
 };
 
@@ -28,7 +29,7 @@ where
     Strength: Parameter<u8>,
 {
     #[inline(always)]
-    fn modify(&mut self, buffer: &mut [Pixel], now: u32) {
+    fn modify(&mut self, buffer: &mut [Pixel], now: Timestamp) {
         if buffer.len() < 2 {
             return;
         }
@@ -37,9 +38,9 @@ where
         let half_strength = strength / 2;
         let mut prev = buffer[0];
 
-        for i in 1..buffer.len() {
-            let current = buffer[i];
-            buffer[i] = current.lerp(&prev, half_strength);
+        for pixel in buffer.iter_mut().skip(1) {
+            let current = *pixel;
+            *pixel = current.lerp(&prev, half_strength);
             prev = current;
         }
     }
@@ -50,7 +51,7 @@ where
     Offset: Parameter<isize>,
 {
     #[inline(always)]
-    fn modify(&mut self, buffer: &mut [Pixel], now: u32) {
+    fn modify(&mut self, buffer: &mut [Pixel], now: Timestamp) {
         let offset = self.offset.sample(now);
         let len = buffer.len();
 
@@ -70,7 +71,7 @@ where
 
 impl Modifier for Mirror {
     #[inline(always)]
-    fn modify(&mut self, buffer: &mut [Pixel], _now: u32) {
+    fn modify(&mut self, buffer: &mut [Pixel], _now: Timestamp) {
         let len = buffer.len();
 
         for i in 0..self.center.min(len) {
@@ -84,7 +85,7 @@ impl Modifier for Mirror {
 
 impl Modifier for Reverse {
     #[inline(always)]
-    fn modify(&mut self, buffer: &mut [Pixel], _now: u32) {
+    fn modify(&mut self, buffer: &mut [Pixel], _now: Timestamp) {
         buffer.reverse();
     }
 }
@@ -1,5 +1,6 @@
 use crate::effects::core::{
     pixel::Pixel,
+    time::Timestamp,
     traits::{Modifier, Parameter},
 };
 
@@ -15,7 +16,7 @@ where
     Rate: Parameter<u8>,
 {
     #[inline(always)]
-    fn modify(&mut self, buffer: &mut [Pixel], now: u32) {
+    fn modify(&mut self, buffer: &mut [Pixel], now: Timestamp) {
         let rate = self.rate.sample(now);
         for pixel in buffer.iter_mut() {
             *pixel = pixel.scale(rate);
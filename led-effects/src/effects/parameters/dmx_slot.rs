@@ -0,0 +1,92 @@
+use crate::effects::core::{
+    time::Timestamp,
+    traits::{Envelope, EnvelopeValue, HueParameter, Parameter},
+};
+use core::sync::atomic::{AtomicU8, AtomicU32, Ordering};
+
+/// Slots in a DMX universe
+pub const DMX_SLOTS: usize = 512;
+
+/// Latest levels of one DMX universe, written by the receiver and read by
+/// effects. Like `AudioFeed`, plain byte atomics, so a `static` can be fed
+/// from an interrupt and read from any task.
+pub struct DmxUniverse {
+    slots: [AtomicU8; DMX_SLOTS],
+    frames: AtomicU32,
+}
+
+impl DmxUniverse {
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { AtomicU8::new(0) }; DMX_SLOTS],
+            frames: AtomicU32::new(0),
+        }
+    }
+
+    /// Store a frame, slot 1 first. Slots past the end of a short frame are
+    /// set to 0, as a frame carries every slot it has.
+    pub fn publish(&self, slots: &[u8]) {
+        let values = slots.iter().copied().chain(core::iter::repeat(0));
+        for (slot, value) in self.slots.iter().zip(values) {
+            slot.store(value, Ordering::Relaxed);
+        }
+        self.frames.fetch_add(1, Ordering::Release);
+    }
+
+    /// Level at a 1-based DMX address; 0 outside 1-512
+    #[inline(always)]
+    pub fn slot(&self, address: u16) -> u8 {
+        (address as usize)
+            .checked_sub(1)
+            .and_then(|index| self.slots.get(index))
+            .map_or(0, |slot| slot.load(Ordering::Relaxed))
+    }
+
+    /// Frames published so far, wrapping; a change means new data
+    #[inline(always)]
+    pub fn frames(&self) -> u32 {
+        self.frames.load(Ordering::Acquire)
+    }
+}
+
+impl Default for DmxUniverse {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One DMX slot as a parameter, envelope or hue, e.g. a master dimmer on
+/// address 1
+pub struct DmxSlot<'a> {
+    pub universe: &'a DmxUniverse,
+    pub address: u16,
+}
+
+impl Parameter<u8> for DmxSlot<'_> {
+    #[inline(always)]
+    fn sample(&self, _now: Timestamp) -> u8 {
+        self.universe.slot(self.address)
+    }
+}
+
+impl HueParameter for DmxSlot<'_> {
+    #[inline(always)]
+    fn sample(&self, _now: Timestamp) -> u8 {
+        self.universe.slot(self.address)
+    }
+}
+
+impl<T> Envelope<T> for DmxSlot<'_>
+where
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn sample(&self, _now: Timestamp) -> T {
+        T::from_progress(self.universe.slot(self.address) as u32, 255)
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, _now: Timestamp) -> bool {
+        true
+    }
+}
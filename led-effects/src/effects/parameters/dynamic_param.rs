@@ -1,4 +1,7 @@
-use crate::effects::core::traits::{Envelope, EnvelopeValue, Parameter};
+use crate::effects::core::{
+    time::Timestamp,
+    traits::{Envelope, EnvelopeValue, Parameter},
+};
 use core::marker::PhantomData;
 
 pub struct DynamicParam<E, T> {
@@ -25,7 +28,7 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> u8 {
+    fn sample(&self, now: Timestamp) -> u8 {
         let t = self.envelope.sample(now);
         let t_normalized = t.to_u32() as f32 / T::MAX.to_u32() as f32;
         let range = self.max - self.min;
@@ -39,7 +42,7 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> u16 {
+    fn sample(&self, now: Timestamp) -> u16 {
         let t = self.envelope.sample(now);
         let t_normalized = t.to_u32() as f32 / T::MAX.to_u32() as f32;
         let range = self.max - self.min;
@@ -53,7 +56,7 @@ where
     T: EnvelopeValue,
 {
     #[inline(always)]
-    fn sample(&self, now: u32) -> f32 {
+    fn sample(&self, now: Timestamp) -> f32 {
         let t = self.envelope.sample(now);
         let t_normalized = t.to_u32() as f32 / T::MAX.to_u32() as f32;
         self.min + (self.max - self.min) * t_normalized
@@ -1,5 +1,9 @@
+pub mod dmx_slot;
 pub mod dynamic_param;
+pub mod shared_param;
 pub mod static_param;
 
+pub use dmx_slot::{DMX_SLOTS, DmxSlot, DmxUniverse};
 pub use dynamic_param::DynamicParam;
+pub use shared_param::SharedParam;
 pub use static_param::StaticParam;
@@ -0,0 +1,40 @@
+use crate::effects::core::{
+    time::Timestamp,
+    traits::{HueParameter, Parameter},
+};
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Value that can be changed while an effect is running, e.g. a `static`
+/// set from a control interface. Effects hold a `&SharedParam`, which works
+/// as a `Parameter<u8>` or a hue.
+pub struct SharedParam(AtomicU8);
+
+impl SharedParam {
+    pub const fn new(value: u8) -> Self {
+        Self(AtomicU8::new(value))
+    }
+
+    #[inline(always)]
+    pub fn get(&self) -> u8 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    pub fn set(&self, value: u8) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+}
+
+impl Parameter<u8> for &SharedParam {
+    #[inline(always)]
+    fn sample(&self, _now: Timestamp) -> u8 {
+        self.get()
+    }
+}
+
+impl HueParameter for &SharedParam {
+    #[inline(always)]
+    fn sample(&self, _now: Timestamp) -> u8 {
+        self.get()
+    }
+}
@@ -1,10 +1,10 @@
-use crate::effects::core::traits::Parameter;
+use crate::effects::core::{time::Timestamp, traits::Parameter};
 
 pub struct StaticParam<T>(pub T);
 
 impl<T: Copy> Parameter<T> for StaticParam<T> {
     #[inline(always)]
-    fn sample(&self, _now: u32) -> T {
+    fn sample(&self, _now: Timestamp) -> T {
         self.0
     }
 }
@@ -0,0 +1,190 @@
+use crate::effects::core::time::Timestamp;
+use core::cell::Cell;
+use critical_section::Mutex;
+
+/// One beat in the fixed-point beat units (1/65536 beat) used by
+/// [`TempoClock`] and the beat envelopes, e.g. `period: BEAT / 2`
+pub const BEAT: u32 = 1 << 16;
+
+/// Taps further apart than this start a new tap-tempo measurement
+const TAP_TIMEOUT_MS: u64 = 2000;
+
+const MILLI_BPM_MS: u128 = 60_000 * 1000;
+
+/// Bar length for [`TempoClock::bar`] and the bar envelopes. Only the beats
+/// per bar matter: the tempo counts beats, whichever note value gets them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeSignature {
+    pub beats_per_bar: u8,
+}
+
+impl TimeSignature {
+    pub const FOUR_FOUR: Self = Self { beats_per_bar: 4 };
+    pub const THREE_FOUR: Self = Self { beats_per_bar: 3 };
+    pub const SIX_EIGHT: Self = Self { beats_per_bar: 6 };
+}
+
+/// Shared musical clock: converts time into a beat position.
+///
+/// Tempo changes re-anchor the clock at the current beat position, so
+/// everything following it stays in phase. The state sits behind a
+/// critical section and the setters take `&self`, so one clock can be a
+/// `static` shared by reference between many envelopes, the render task and
+/// the tasks that tap or set the tempo.
+pub struct TempoClock(Mutex<Cell<ClockState>>);
+
+#[derive(Clone, Copy)]
+struct ClockState {
+    milli_bpm: u32,
+    signature: TimeSignature,
+    anchor_time: Timestamp,
+    anchor_beat: u64,
+    phase_offset: i64,
+    first_tap: Timestamp,
+    last_tap: Option<Timestamp>,
+    taps: u32,
+}
+
+impl TempoClock {
+    /// Beat 0 falls on `start_time`. Tempo is in thousandths of a BPM
+    /// (120 BPM = `120_000`).
+    pub const fn new(start_time: Timestamp, milli_bpm: u32, signature: TimeSignature) -> Self {
+        Self(Mutex::new(Cell::new(ClockState {
+            milli_bpm,
+            signature,
+            anchor_time: start_time,
+            anchor_beat: 0,
+            phase_offset: 0,
+            first_tap: start_time,
+            last_tap: None,
+            taps: 0,
+        })))
+    }
+
+    #[inline(always)]
+    pub fn milli_bpm(&self) -> u32 {
+        self.state().milli_bpm
+    }
+
+    #[inline(always)]
+    pub fn time_signature(&self) -> TimeSignature {
+        self.state().signature
+    }
+
+    /// Bars are counted from beat 0, so this moves later bar lines
+    pub fn set_time_signature(&self, signature: TimeSignature) {
+        self.update(|state| state.signature = signature);
+    }
+
+    /// Change tempo from `now` on without jumping the beat position
+    pub fn set_tempo(&self, now: Timestamp, milli_bpm: u32) {
+        self.update(|state| {
+            state.anchor_beat = state.raw_position(now);
+            state.anchor_time = now;
+            state.milli_bpm = milli_bpm;
+        });
+    }
+
+    /// Shift every beat by `offset` beat units (positive = earlier), e.g. to
+    /// line up with the music
+    pub fn set_phase_offset(&self, offset: i32) {
+        self.update(|state| state.phase_offset = offset as i64);
+    }
+
+    /// Register a tap. Two or more taps less than 2s apart set the tempo to
+    /// their average interval and put a beat exactly on the latest tap.
+    pub fn tap(&self, now: Timestamp) {
+        self.update(|state| state.tap(now));
+    }
+
+    /// Beats since beat 0, in [`BEAT`] units
+    #[inline(always)]
+    pub fn beat_position(&self, now: Timestamp) -> u64 {
+        self.state().beat_position(now)
+    }
+
+    /// Whole beats since beat 0
+    #[inline(always)]
+    pub fn beat(&self, now: Timestamp) -> u64 {
+        self.beat_position(now) / BEAT as u64
+    }
+
+    /// Whole bars since beat 0
+    #[inline(always)]
+    pub fn bar(&self, now: Timestamp) -> u64 {
+        let state = self.state();
+        state.beat_position(now) / BEAT as u64 / state.beats_per_bar()
+    }
+
+    /// Length of one bar in [`BEAT`] units
+    #[inline(always)]
+    pub fn bar_length(&self) -> u64 {
+        self.state().beats_per_bar() * BEAT as u64
+    }
+
+    /// A consistent copy of the state, even with a tap or tempo change
+    /// coming from an interrupt
+    #[inline(always)]
+    fn state(&self) -> ClockState {
+        critical_section::with(|cs| self.0.borrow(cs).get())
+    }
+
+    fn update(&self, f: impl FnOnce(&mut ClockState)) {
+        critical_section::with(|cs| {
+            let cell = self.0.borrow(cs);
+            let mut state = cell.get();
+            f(&mut state);
+            cell.set(state);
+        });
+    }
+}
+
+impl ClockState {
+    fn tap(&mut self, now: Timestamp) {
+        let continues = self
+            .last_tap
+            .is_some_and(|last| now >= last && now.millis_since(last) <= TAP_TIMEOUT_MS);
+        self.last_tap = Some(now);
+
+        if !continues {
+            self.first_tap = now;
+            self.taps = 1;
+            return;
+        }
+
+        self.taps += 1;
+
+        let span = now.millis_since(self.first_tap);
+        if span == 0 {
+            return;
+        }
+
+        // Snap to the nearest whole beat, folding the offset into the anchor
+        let position = self.beat_position(now);
+        let snapped = (position + BEAT as u64 / 2) & !(BEAT as u64 - 1);
+        self.phase_offset = 0;
+        self.anchor_beat = snapped;
+        self.anchor_time = now;
+
+        let milli_bpm = (MILLI_BPM_MS * (self.taps - 1) as u128) / span as u128;
+        self.milli_bpm = milli_bpm.min(u32::MAX as u128) as u32;
+    }
+
+    #[inline(always)]
+    fn beat_position(&self, now: Timestamp) -> u64 {
+        let position = self.raw_position(now) as i64 + self.phase_offset;
+        position.max(0) as u64
+    }
+
+    #[inline(always)]
+    fn beats_per_bar(&self) -> u64 {
+        self.signature.beats_per_bar.max(1) as u64
+    }
+
+    #[inline(always)]
+    fn raw_position(&self, now: Timestamp) -> u64 {
+        let elapsed = now.millis_since(self.anchor_time) as u128;
+        let beats = (elapsed * self.milli_bpm as u128 * BEAT as u128) / MILLI_BPM_MS;
+        self.anchor_beat + beats as u64
+    }
+}
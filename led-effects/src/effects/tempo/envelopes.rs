@@ -0,0 +1,144 @@
+use super::clock::TempoClock;
+use crate::effects::core::{
+    time::Timestamp,
+    traits::{Envelope, EnvelopeValue},
+};
+use crate::effects::envelopes::timing::{SINE_LUT, SINE_LUT_SIZE};
+use crate::effects::envelopes::utilities::{DECAY_LUT, DECAY_LUT_SIZE};
+
+// Periods, phases and lengths below are in `BEAT` units (1/65536 beat)
+
+pub struct BeatSine<'a> {
+    pub clock: &'a TempoClock,
+    pub period: u32,
+    pub phase: u32,
+}
+
+pub struct BeatSaw<'a> {
+    pub clock: &'a TempoClock,
+    pub period: u32,
+    pub phase: u32,
+}
+
+/// Hits MAX on every period and decays exponentially over `decay`
+pub struct BeatPulse<'a> {
+    pub clock: &'a TempoClock,
+    pub period: u32,
+    pub phase: u32,
+    pub decay: u32,
+}
+
+/// `Fade` that starts on the downbeat of `start_bar` and lasts `bars` bars
+pub struct BarFade<'a> {
+    pub clock: &'a TempoClock,
+    pub start_bar: u64,
+    pub bars: u32,
+    pub inverted: bool,
+}
+
+/// Position within a cycle of `period` beat units
+#[inline(always)]
+fn cycle_position(clock: &TempoClock, now: Timestamp, period: u32, phase: u32) -> u32 {
+    ((clock.beat_position(now) + phase as u64) % period as u64) as u32
+}
+
+impl<T> Envelope<T> for BeatSine<'_>
+where
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn sample(&self, now: Timestamp) -> T {
+        let position = cycle_position(self.clock, now, self.period, self.phase) as u64;
+
+        let phase_index = ((position * SINE_LUT_SIZE as u64) / self.period as u64) as usize;
+        let sine_u8 = SINE_LUT[phase_index & (SINE_LUT_SIZE - 1)];
+
+        T::from_progress(sine_u8 as u32, 255)
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, _now: Timestamp) -> bool {
+        true
+    }
+}
+
+impl<T> Envelope<T> for BeatSaw<'_>
+where
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn sample(&self, now: Timestamp) -> T {
+        let position = cycle_position(self.clock, now, self.period, self.phase);
+
+        T::from_progress(position, self.period)
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, _now: Timestamp) -> bool {
+        true
+    }
+}
+
+impl<T> Envelope<T> for BeatPulse<'_>
+where
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn sample(&self, now: Timestamp) -> T {
+        let position = cycle_position(self.clock, now, self.period, self.phase);
+
+        if position >= self.decay {
+            return T::ZERO;
+        }
+
+        let decay_progress =
+            ((position as u64 * DECAY_LUT_SIZE as u64) / self.decay as u64) as usize;
+        let decay_value = DECAY_LUT[decay_progress.min(DECAY_LUT_SIZE - 1)];
+
+        T::from_progress(decay_value as u32, 255)
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, _now: Timestamp) -> bool {
+        true
+    }
+}
+
+impl BarFade<'_> {
+    #[inline(always)]
+    fn span(&self) -> (u64, u64) {
+        let bar = self.clock.bar_length();
+        (self.start_bar * bar, self.bars as u64 * bar)
+    }
+}
+
+impl<T> Envelope<T> for BarFade<'_>
+where
+    T: EnvelopeValue,
+{
+    #[inline(always)]
+    fn sample(&self, now: Timestamp) -> T {
+        let (start, length) = self.span();
+        let elapsed = self.clock.beat_position(now).saturating_sub(start);
+
+        let progress = if elapsed >= length {
+            T::MAX
+        } else {
+            // Bar lengths in beat units can exceed u32, so scale in u128
+            let scaled = (elapsed as u128 * T::MAX.to_u32() as u128) / length as u128;
+            T::from_u32(scaled as u32)
+        };
+
+        if self.inverted {
+            T::MAX.saturating_sub(progress)
+        } else {
+            progress
+        }
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, now: Timestamp) -> bool {
+        let (start, length) = self.span();
+        self.clock.beat_position(now) < start + length
+    }
+}
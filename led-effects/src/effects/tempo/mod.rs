@@ -0,0 +1,5 @@
+pub mod clock;
+pub mod envelopes;
+
+pub use clock::{BEAT, TempoClock, TimeSignature};
+pub use envelopes::{BarFade, BeatPulse, BeatSaw, BeatSine};
@@ -1,13 +1,18 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod audio;
 pub mod effects;
+pub mod rng;
+
+#[cfg(feature = "std")]
+pub mod testing;
 
 // Reexports
 // Core traits
-pub use effects::{Envelope, Generator, HueParameter, Modifier, Parameter};
+pub use effects::{Envelope, Generator, HueParameter, Modifier, Parameter, StatefulEnvelope};
 
 // Core types
-pub use effects::Pixel;
+pub use effects::{Pixel, Timestamp};
 
 // Envelope types
 pub use effects::{
@@ -23,32 +28,52 @@ pub use effects::{
     // Utilities
     EnvelopePulse,
     Fade,
+    Flicker,
     Invert,
     Linear,
     LoopCount,
     Max,
     Min,
+    PerlinNoise,
     Product,
+    RandomStep,
+    RandomWalk,
+    SampleHold,
     Sawtooth,
     Sine,
+    Slew,
+    Smooth,
     Square,
+    Stateful,
     Sum,
     TimeLimited,
     Triangle,
+    ValueNoise,
     VelocityIntegral,
 };
 
+// Tempo
+pub use effects::{BEAT, BarFade, BeatPulse, BeatSaw, BeatSine, TempoClock, TimeSignature};
+
 // Parameters
-pub use effects::{DynamicParam, StaticParam};
+pub use effects::{DMX_SLOTS, DmxSlot, DmxUniverse, DynamicParam, SharedParam, StaticParam};
+
+// Audio
+pub use audio::{AudioAnalyzer, AudioFeed, AudioLevel, AudioLevels, AudioSignal};
 
 // Hue parameters
 pub use effects::{HueOscillate, RotatingHue, StaticHue};
 
 // Generators
-pub use effects::{Chase, Gradient, Pulse, SolidColor, Stripes};
+pub use effects::{
+    Boundary, Chase, Emitter, Gradient, ParticlePool, Particles, Pulse, SolidColor, Stripes,
+};
 
 // Modifiers
 pub use effects::{
     Blur, Brightness, EffectBuilder, GammaCorrection, HueShift, Mirror, Reverse, Saturation, Shift,
     Trail, WithModifier,
 };
+
+// Triggers
+pub use effects::{Retrigger, Trigger, Triggered};
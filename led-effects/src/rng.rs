@@ -0,0 +1,67 @@
+//! Small deterministic random numbers for effects (no_std, not cryptographic).
+//!
+//! [`Rng`] is a seedable xorshift32 stream for stateful effects. [`hash`]
+//! maps `(seed, index)` straight to a random value, so stateless envelopes
+//! can look up "the random value for time slot N" without keeping state.
+//! Both give the same results on every platform for a given seed.
+
+/// xorshift32 generator
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    /// Any seed is fine; 0 (a fixed point of xorshift) is remapped
+    pub const fn new(seed: u32) -> Self {
+        Self {
+            state: if seed == 0 { 0x9E37_79B9 } else { seed },
+        }
+    }
+
+    #[inline(always)]
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// Uses the high byte, which is better mixed than the low one
+    #[inline(always)]
+    pub fn next_u8(&mut self) -> u8 {
+        (self.next_u32() >> 24) as u8
+    }
+
+    /// Uniform value in `0..bound` (0 when `bound` is 0)
+    #[inline(always)]
+    pub fn below(&mut self, bound: u32) -> u32 {
+        ((self.next_u32() as u64 * bound as u64) >> 32) as u32
+    }
+
+    /// True with probability `chance / 256`
+    #[inline(always)]
+    pub fn chance(&mut self, chance: u8) -> bool {
+        self.next_u8() < chance
+    }
+}
+
+/// Stateless random value for slot `index` of the stream `seed`
+#[inline(always)]
+pub const fn hash(seed: u32, index: u64) -> u32 {
+    let high = mix(seed ^ (index >> 32) as u32);
+    mix(high ^ index as u32)
+}
+
+// lowbias32 by Chris Wellons: a cheap integer hash with good avalanche
+#[inline(always)]
+const fn mix(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    x
+}
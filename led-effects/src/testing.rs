@@ -0,0 +1,207 @@
+//! Golden-frame test harness (requires the `std` feature).
+//!
+//! Effects are rendered at a fixed list of millisecond timestamps into a
+//! plain-text snapshot and compared against a checked-in golden file. Set
+//! `LED_EFFECTS_BLESS=1` to write the current output instead of comparing,
+//! then review the diff before committing it.
+//!
+//! ```ignore
+//! let frames = testing::render_generator(&mut effect, 16, &[0, 100, 200]);
+//! testing::assert_golden(golden_dir, "solid_color", &frames);
+//! ```
+
+use std::fmt::{Display, Write as _};
+use std::path::Path;
+use std::{env, fs};
+
+use crate::effects::core::traits::{Easing, EnvelopeValue, StatefulEnvelope};
+use crate::{Envelope, Generator, HueParameter, Modifier, Parameter, Pixel, Timestamp};
+
+/// Environment variable that switches [`assert_golden`] into bless mode
+pub const BLESS_ENV: &str = "LED_EFFECTS_BLESS";
+
+/// Render a generator into a cleared buffer at every timestamp.
+/// One line per frame: `t=<ms> alive=<bool>: rrggbb rrggbb ...`
+pub fn render_generator<G>(generator: &mut G, num_leds: usize, timestamps: &[u64]) -> String
+where
+    G: Generator + ?Sized,
+{
+    let mut out = String::new();
+    let mut buffer = vec![Pixel::BLACK; num_leds];
+
+    for &now in timestamps {
+        let now = Timestamp::from_millis(now);
+        buffer.fill(Pixel::BLACK);
+        generator.generate(&mut buffer, now);
+        let _ = write!(out, "t={now} alive={}:", generator.is_alive(now));
+        push_pixels(&mut out, &buffer);
+    }
+
+    out
+}
+
+/// Apply a modifier to a fresh copy of `input` at every timestamp.
+/// Stateful modifiers (e.g. `Sparkle`) keep their state between frames.
+pub fn render_modifier<M>(modifier: &mut M, input: &[Pixel], timestamps: &[u64]) -> String
+where
+    M: Modifier + ?Sized,
+{
+    let mut out = String::new();
+    let mut buffer = input.to_vec();
+
+    for &now in timestamps {
+        let now = Timestamp::from_millis(now);
+        buffer.copy_from_slice(input);
+        modifier.modify(&mut buffer, now);
+        let _ = write!(out, "t={now}:");
+        push_pixels(&mut out, &buffer);
+    }
+
+    out
+}
+
+/// Sample an envelope at every timestamp: `t=<ms> alive=<bool>: <value>`
+pub fn sample_envelope<E, T>(envelope: &E, timestamps: &[u64]) -> String
+where
+    E: Envelope<T> + ?Sized,
+    T: EnvelopeValue + Display,
+{
+    let mut out = String::new();
+
+    for &now in timestamps {
+        let now = Timestamp::from_millis(now);
+        let _ = writeln!(
+            out,
+            "t={now} alive={}: {}",
+            envelope.is_alive(now),
+            envelope.sample(now)
+        );
+    }
+
+    out
+}
+
+/// Update a stateful envelope at every timestamp, in order, using the same
+/// format as [`sample_envelope`]
+pub fn update_envelope<E, T>(envelope: &mut E, timestamps: &[u64]) -> String
+where
+    E: StatefulEnvelope<T> + ?Sized,
+    T: EnvelopeValue + Display,
+{
+    let mut out = String::new();
+
+    for &now in timestamps {
+        let now = Timestamp::from_millis(now);
+        let value = envelope.update(now);
+        let _ = writeln!(out, "t={now} alive={}: {value}", envelope.is_alive(now));
+    }
+
+    out
+}
+
+/// Sample a parameter at every timestamp: `t=<ms>: <value>`
+pub fn sample_parameter<P, T>(parameter: &P, timestamps: &[u64]) -> String
+where
+    P: Parameter<T> + ?Sized,
+    T: Display,
+{
+    let mut out = String::new();
+
+    for &now in timestamps {
+        let now = Timestamp::from_millis(now);
+        let _ = writeln!(out, "t={now}: {}", parameter.sample(now));
+    }
+
+    out
+}
+
+/// Sample a hue parameter at every timestamp: `t=<ms>: <hue>`
+pub fn sample_hue<H>(hue: &H, timestamps: &[u64]) -> String
+where
+    H: HueParameter + ?Sized,
+{
+    let mut out = String::new();
+
+    for &now in timestamps {
+        let now = Timestamp::from_millis(now);
+        let _ = writeln!(out, "t={now}: {}", hue.sample(now));
+    }
+
+    out
+}
+
+/// Evaluate an easing curve at `steps + 1` evenly spaced points in 0.0..=1.0
+pub fn sample_easing<E>(easing: &E, steps: u32) -> String
+where
+    E: Easing + ?Sized,
+{
+    let mut out = String::new();
+    let steps = steps.max(1);
+
+    for i in 0..=steps {
+        let t = i as f32 / steps as f32;
+        let _ = writeln!(out, "t={t:.4}: {:.6}", easing.ease(t));
+    }
+
+    out
+}
+
+/// Evenly spaced timestamps `0, step, 2*step, ...` up to and including `end`
+pub fn timestamps(end: u64, step: u64) -> Vec<u64> {
+    (0..=end).step_by(step.max(1) as usize).collect()
+}
+
+/// Compare `actual` with `<dir>/<name>.txt`, or overwrite the golden file when
+/// [`BLESS_ENV`] is set.
+///
+/// # Panics
+///
+/// Panics with the first mismatching line when the output differs from the
+/// golden file, or when the golden file is missing and bless mode is off.
+pub fn assert_golden(dir: impl AsRef<Path>, name: &str, actual: &str) {
+    let path = dir.as_ref().join(format!("{name}.txt"));
+
+    if env::var_os(BLESS_ENV).is_some_and(|value| value != "0") {
+        fs::create_dir_all(dir.as_ref()).expect("failed to create golden directory");
+        fs::write(&path, actual).expect("failed to write golden file");
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!(
+            "missing golden file {} ({err}), run with {BLESS_ENV}=1 to create it",
+            path.display()
+        )
+    });
+
+    if expected == actual {
+        return;
+    }
+
+    let mismatch = expected
+        .lines()
+        .zip(actual.lines())
+        .enumerate()
+        .find(|(_, (e, a))| e != a);
+
+    match mismatch {
+        Some((line, (e, a))) => panic!(
+            "{} differs at line {}\n  expected: {e}\n  actual:   {a}\nrun with {BLESS_ENV}=1 to update",
+            path.display(),
+            line + 1
+        ),
+        None => panic!(
+            "{} differs in length: expected {} lines, got {}\nrun with {BLESS_ENV}=1 to update",
+            path.display(),
+            expected.lines().count(),
+            actual.lines().count()
+        ),
+    }
+}
+
+fn push_pixels(out: &mut String, pixels: &[Pixel]) {
+    for pixel in pixels {
+        let _ = write!(out, " {:02x}{:02x}{:02x}", pixel.r, pixel.g, pixel.b);
+    }
+    out.push('\n');
+}
@@ -0,0 +1,150 @@
+//! Audio analysis against WAV fixtures (16 kHz mono, 16-bit, 250 ms).
+//!
+//! The fixtures are plain synthesized tones so the expected levels can be
+//! worked out by hand: a full scale sine has an RMS of -3 dBFS.
+
+use led_effects::audio::{
+    AudioAnalyzer, AudioFeed, AudioLevel, AudioLevels, AudioSignal, FLOOR_DB,
+};
+use led_effects::{Envelope, Parameter, Timestamp};
+
+const BLOCK: usize = 512;
+
+fn read_fixture(name: &str) -> (u32, Vec<i16>) {
+    let path = format!("{}/tests/fixtures/{name}.wav", env!("CARGO_MANIFEST_DIR"));
+    let mut reader = hound::WavReader::open(&path).unwrap_or_else(|e| panic!("{path}: {e}"));
+    let rate = reader.spec().sample_rate;
+    let samples = reader.samples::<i16>().map(Result::unwrap).collect();
+    (rate, samples)
+}
+
+/// Analyze every full block of a fixture
+fn analyze(name: &str) -> Vec<AudioLevels> {
+    let (rate, samples) = read_fixture(name);
+    let mut analyzer = AudioAnalyzer::<BLOCK>::new(rate);
+    samples
+        .as_chunks::<BLOCK>()
+        .0
+        .iter()
+        .map(|block| analyzer.analyze(block))
+        .collect()
+}
+
+/// Expected level for a dBFS value
+fn level(db: f32) -> u8 {
+    ((db - FLOOR_DB) / -FLOOR_DB * 255.0) as u8
+}
+
+fn loudest_band(levels: &AudioLevels) -> usize {
+    (0..levels.bands.len())
+        .max_by_key(|&band| levels.bands[band])
+        .unwrap()
+}
+
+#[test]
+fn silence_reads_zero() {
+    for levels in analyze("silence") {
+        assert_eq!(levels, AudioLevels::default());
+    }
+}
+
+#[test]
+fn full_scale_sine_levels() {
+    for levels in analyze("sine_1khz") {
+        assert!(levels.peak >= 254, "{levels:?}");
+        assert!(levels.rms.abs_diff(level(-3.0)) <= 2, "{levels:?}");
+        // Nearly all the energy lands in one band
+        assert!(levels.bands[1].abs_diff(levels.rms) <= 3, "{levels:?}");
+    }
+}
+
+#[test]
+fn half_amplitude_is_six_db_down() {
+    let full = analyze("sine_1khz");
+    let half = analyze("sine_1khz_half");
+    let six_db = level(-6.0) as i32 - level(-12.0) as i32;
+
+    for (full, half) in full.iter().zip(&half) {
+        let drop = full.rms as i32 - half.rms as i32;
+        assert!((drop - six_db).abs() <= 2, "{full:?} vs {half:?}");
+        let drop = full.peak as i32 - half.peak as i32;
+        assert!((drop - six_db).abs() <= 2, "{full:?} vs {half:?}");
+    }
+}
+
+#[test]
+fn tones_land_in_their_bands() {
+    for (name, band) in [
+        ("sine_100hz", 0),
+        ("sine_1khz", 1),
+        ("sine_5khz", 2),
+        ("sine_7khz", 3),
+    ] {
+        for levels in analyze(name) {
+            assert_eq!(loudest_band(&levels), band, "{name}: {levels:?}");
+            // Window leakage into the other bands stays well below the tone
+            for (other, &value) in levels.bands.iter().enumerate() {
+                if other != band {
+                    assert!(
+                        value + 60 < levels.bands[band],
+                        "{name}: band {other} leaks {levels:?}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn dc_bias_is_ignored() {
+    let biased = analyze("sine_1khz_biased");
+    let (rate, samples) = read_fixture("sine_1khz_biased");
+    let centered: Vec<i16> = samples.iter().map(|&s| s - 8000).collect();
+    let mut analyzer = AudioAnalyzer::<BLOCK>::new(rate);
+
+    for (levels, block) in biased.iter().zip(centered.as_chunks::<BLOCK>().0.iter()) {
+        let reference = analyzer.analyze(block);
+        assert!(levels.rms.abs_diff(reference.rms) <= 1);
+        assert!(levels.peak.abs_diff(reference.peak) <= 1);
+        assert_eq!(loudest_band(levels), 1);
+    }
+}
+
+#[test]
+fn short_blocks_are_zero_padded() {
+    let (rate, samples) = read_fixture("sine_1khz");
+    let mut analyzer = AudioAnalyzer::<BLOCK>::new(rate);
+
+    let levels = analyzer.analyze(&samples[..BLOCK / 2]);
+    assert!(levels.rms.abs_diff(level(-3.0)) <= 2, "{levels:?}");
+    assert_eq!(loudest_band(&levels), 1);
+    // Windowed over its own length, the band still holds the whole tone
+    assert!(levels.bands[1].abs_diff(levels.rms) <= 3, "{levels:?}");
+    assert_eq!(analyzer.analyze(&[]), AudioLevels::default());
+}
+
+#[test]
+fn feed_publishes_levels_to_envelopes() {
+    static FEED: AudioFeed = AudioFeed::new();
+    let levels = analyze("sine_100hz")[0];
+    FEED.publish(&levels);
+    assert_eq!(FEED.levels(), levels);
+
+    let bass = AudioLevel {
+        feed: &FEED,
+        signal: AudioSignal::Band(0),
+    };
+    let now = Timestamp::ZERO;
+    assert_eq!(Parameter::<u8>::sample(&bass, now), levels.bands[0]);
+    assert_eq!(
+        Envelope::<u16>::sample(&bass, now),
+        levels.bands[0] as u16 * 257
+    );
+    assert!(Envelope::<u8>::is_alive(&bass, now));
+
+    let missing = AudioLevel {
+        feed: &FEED,
+        signal: AudioSignal::Band(9),
+    };
+    assert_eq!(Parameter::<u8>::sample(&missing, now), 0);
+}
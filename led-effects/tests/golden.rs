@@ -0,0 +1,941 @@
+//! Golden-frame regression tests for every generator, modifier and envelope.
+//!
+//! Regenerate after an intentional output change with
+//! `LED_EFFECTS_BLESS=1 cargo test -p led-effects --test golden` and review
+//! the diff of `tests/golden/`.
+
+use led_effects::effects::envelopes::{
+    BackIn, BackInOut, BackOut, BounceIn, BounceInOut, BounceOut, EaseInCubic, EaseInExpo,
+    EaseInOutCubic, EaseInOutExpo, EaseInOutQuad, EaseInOutQuart, EaseInQuad, EaseInQuart,
+    EaseOutCubic, EaseOutExpo, EaseOutQuad, EaseOutQuart, ElasticIn, ElasticInOut, ElasticOut,
+    Linear,
+};
+use led_effects::effects::modifiers::{Decay, Sparkle};
+use led_effects::rng::Rng;
+use led_effects::testing::{self, timestamps};
+use led_effects::{
+    ADSR, BEAT, BarFade, BeatPulse, BeatSaw, BeatSine, Blur, Brightness, Chase, Clamp, Constant,
+    DynamicParam, EffectBuilder, EnvelopePulse, Fade, Flicker, GammaCorrection, Gradient,
+    HueOscillate, HueShift, Invert, LoopCount, Max, Min, Mirror, PerlinNoise, Pixel, Product,
+    Pulse, RandomStep, RandomWalk, Reverse, RotatingHue, SampleHold, Saturation, Sawtooth, Shift,
+    Sine, Slew, Smooth, SolidColor, Square, Stateful, StaticHue, StaticParam, Stripes, Sum,
+    TempoClock, TimeLimited, TimeSignature, Timestamp, Trail, Triangle, ValueNoise,
+    VelocityIntegral,
+};
+use led_effects::{Boundary, Emitter, ParticlePool, Particles};
+
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+const NUM_LEDS: usize = 16;
+
+fn check(name: &str, actual: &str) {
+    testing::assert_golden(GOLDEN_DIR, name, actual);
+}
+
+/// Modifier input: a hue ramp with varying brightness, including black and white
+fn ramp() -> Vec<Pixel> {
+    let mut pixels: Vec<Pixel> = (0..NUM_LEDS - 2)
+        .map(|i| Pixel::from_hsv((i * 18) as u8, 255 - (i * 8) as u8, 64 + (i * 13) as u8))
+        .collect();
+    pixels.insert(0, Pixel::BLACK);
+    pixels.push(Pixel::new(255, 255, 255));
+    pixels
+}
+
+fn fade(duration: u32, inverted: bool) -> Fade {
+    Fade {
+        start_time: Timestamp::ZERO,
+        duration,
+        inverted,
+    }
+}
+
+// ============================================================================
+// GENERATORS
+// ============================================================================
+
+#[test]
+fn solid_color() {
+    let mut effect = SolidColor {
+        start_time: Timestamp::ZERO,
+        duration: 4000,
+        intensity: DynamicParam::<_, u8>::new(fade(4000, false), 0.0, 255.0),
+        hue: StaticHue::ORANGE,
+        saturation: StaticParam(200),
+    };
+    check(
+        "solid_color",
+        &testing::render_generator(&mut effect, NUM_LEDS, &timestamps(4500, 250)),
+    );
+}
+
+#[test]
+fn gradient() {
+    let mut effect = Gradient {
+        start_time: Timestamp::ZERO,
+        duration: 4000,
+        intensity: StaticParam(255),
+        hue_start: RotatingHue {
+            start_time: Timestamp::ZERO,
+            degrees_per_ms: 0.09,
+        },
+        hue_end: StaticHue::from_normalized(10),
+        saturation: StaticParam(255),
+    };
+    check(
+        "gradient",
+        &testing::render_generator(&mut effect, NUM_LEDS, &timestamps(4000, 250)),
+    );
+}
+
+#[test]
+fn stripes() {
+    let mut effect = Stripes {
+        start_time: Timestamp::ZERO,
+        duration: 1000,
+        intensity: StaticParam(180),
+        hue1: StaticHue::RED,
+        hue2: StaticHue::CYAN,
+        saturation: StaticParam(255),
+        stripe_width: StaticParam(3),
+    };
+    check(
+        "stripes",
+        &testing::render_generator(&mut effect, NUM_LEDS, &timestamps(1000, 500)),
+    );
+}
+
+#[test]
+fn chase() {
+    let mut effect = Chase {
+        start_time: Timestamp::ZERO,
+        duration: 2000,
+        position: DynamicParam::<_, u8>::new(
+            Triangle {
+                start_time: Timestamp::ZERO,
+                period: 2000,
+            },
+            0.0,
+            NUM_LEDS as f32,
+        ),
+        width: StaticParam(3),
+        intensity: StaticParam(255),
+        hue: HueOscillate {
+            start_time: Timestamp::ZERO,
+            period: 1000,
+            hue1: 0,
+            hue2: 170,
+        },
+        saturation: StaticParam(255),
+    };
+    check(
+        "chase",
+        &testing::render_generator(&mut effect, NUM_LEDS, &timestamps(2200, 100)),
+    );
+}
+
+#[test]
+fn pulse() {
+    let mut effect = Pulse {
+        start_time: Timestamp::ZERO,
+        duration: 1500,
+        position: NUM_LEDS / 2,
+        spread_speed: 0.01,
+        width: StaticParam(2),
+        intensity: DynamicParam::<_, u8>::new(fade(1500, true), 0.0, 255.0),
+        hue: StaticHue::GREEN,
+        saturation: StaticParam(255),
+    };
+    check(
+        "pulse",
+        &testing::render_generator(&mut effect, NUM_LEDS, &timestamps(1600, 100)),
+    );
+}
+
+#[test]
+fn particles_fountain() {
+    let mut effect = Particles::<16, _, _> {
+        start_time: Timestamp::ZERO,
+        duration: 1000,
+        emitter: Emitter::Point {
+            position: 0.0,
+            rate: 20,
+        },
+        min_velocity: 10.0,
+        max_velocity: 20.0,
+        gravity: -15.0,
+        drag: 0.2,
+        lifetime: 1200,
+        lifetime_jitter: 300,
+        boundary: Boundary::Bounce { restitution: 0.5 },
+        hue: StaticHue::BLUE,
+        hue_spread: 20,
+        hue_shift: 0,
+        saturation: 255,
+        intensity: fade(1200, true),
+        rng: Rng::new(7),
+        pool: ParticlePool::new(),
+    };
+    check(
+        "particles_fountain",
+        &testing::render_generator(&mut effect, NUM_LEDS, &timestamps(2000, 50)),
+    );
+}
+
+#[test]
+fn particles_firework() {
+    let mut effect = Particles::<24, _, _> {
+        start_time: Timestamp::ZERO,
+        duration: 0,
+        emitter: Emitter::Burst {
+            position: 7.5,
+            count: 12,
+        },
+        min_velocity: -12.0,
+        max_velocity: 12.0,
+        gravity: 2.0,
+        drag: 1.5,
+        lifetime: 800,
+        lifetime_jitter: 400,
+        boundary: Boundary::Kill,
+        hue: StaticHue::ORANGE,
+        hue_spread: 40,
+        hue_shift: -20,
+        saturation: 200,
+        intensity: fade(800, true),
+        rng: Rng::new(42),
+        pool: ParticlePool::new(),
+    };
+    effect.fire();
+    check(
+        "particles_firework",
+        &testing::render_generator(&mut effect, NUM_LEDS, &timestamps(1400, 50)),
+    );
+}
+
+#[test]
+fn particles_rain() {
+    let mut effect = Particles::<32, _, _> {
+        start_time: Timestamp::ZERO,
+        duration: 1500,
+        emitter: Emitter::Random { rate: 15 },
+        min_velocity: -2.0,
+        max_velocity: -1.0,
+        gravity: -20.0,
+        drag: 0.0,
+        lifetime: 2000,
+        lifetime_jitter: 0,
+        boundary: Boundary::Kill,
+        hue: StaticHue::CYAN,
+        hue_spread: 0,
+        hue_shift: 0,
+        saturation: 128,
+        intensity: fade(2000, true),
+        rng: Rng::new(3),
+        pool: ParticlePool::new(),
+    };
+    check(
+        "particles_rain",
+        &testing::render_generator(&mut effect, NUM_LEDS, &timestamps(2000, 100)),
+    );
+}
+
+#[test]
+fn particles_comet() {
+    let mut effect = Particles::<8, _, _> {
+        start_time: Timestamp::ZERO,
+        duration: 200,
+        emitter: Emitter::Point {
+            position: 12.0,
+            rate: 10,
+        },
+        min_velocity: 25.0,
+        max_velocity: 25.0,
+        gravity: 0.0,
+        drag: 0.0,
+        lifetime: 1500,
+        lifetime_jitter: 0,
+        boundary: Boundary::Wrap,
+        hue: StaticHue::MAGENTA,
+        hue_spread: 0,
+        hue_shift: 60,
+        saturation: 255,
+        intensity: fade(1500, true),
+        rng: Rng::new(1),
+        pool: ParticlePool::new(),
+    };
+    check(
+        "particles_comet",
+        &testing::render_generator(&mut effect, NUM_LEDS, &timestamps(1600, 40)),
+    );
+}
+
+#[test]
+fn firmware_chase() {
+    // The effect running in the firmware `led_effects` task
+    let mut effect = EffectBuilder::new(Chase {
+        start_time: Timestamp::ZERO,
+        duration: 5000,
+        position: DynamicParam::<_, u8>::new(
+            VelocityIntegral::new(Timestamp::ZERO, fade(5000, true), 0, 16, 20),
+            0.0,
+            60.0,
+        ),
+        width: DynamicParam::<_, u8>::new(fade(5000, false), 2.0, 8.0),
+        intensity: DynamicParam::<_, u8>::new(fade(5000, true), 0.0, 255.0),
+        hue: RotatingHue {
+            start_time: Timestamp::ZERO,
+            degrees_per_ms: 0.05,
+        },
+        saturation: StaticParam(255),
+    })
+    .with_modifier(Trail {
+        decay_rate: DynamicParam::<_, u8>::new(fade(5000, false), 204.0, 242.0),
+    })
+    .with_modifier(Brightness {
+        factor: StaticParam(230),
+    })
+    .build();
+    check(
+        "firmware_chase",
+        &testing::render_generator(&mut effect, 60, &timestamps(5000, 125)),
+    );
+}
+
+// ============================================================================
+// MODIFIERS
+// ============================================================================
+
+#[test]
+fn brightness() {
+    let mut modifier = Brightness {
+        factor: DynamicParam::<_, u8>::new(fade(1000, true), 0.0, 255.0),
+    };
+    check(
+        "brightness",
+        &testing::render_modifier(&mut modifier, &ramp(), &timestamps(1000, 250)),
+    );
+}
+
+#[test]
+fn saturation() {
+    let mut modifier = Saturation {
+        factor: DynamicParam::<_, u8>::new(fade(1000, true), 0.0, 255.0),
+    };
+    check(
+        "saturation",
+        &testing::render_modifier(&mut modifier, &ramp(), &timestamps(1000, 250)),
+    );
+}
+
+#[test]
+fn hue_shift() {
+    let mut modifier = HueShift {
+        amount: DynamicParam::<_, u8>::new(fade(1000, false), 0.0, 255.0),
+    };
+    check(
+        "hue_shift",
+        &testing::render_modifier(&mut modifier, &ramp(), &timestamps(1000, 250)),
+    );
+}
+
+#[test]
+fn gamma_correction() {
+    check(
+        "gamma_correction",
+        &testing::render_modifier(&mut GammaCorrection, &ramp(), &[0]),
+    );
+}
+
+#[test]
+fn trail() {
+    let mut modifier = Trail {
+        decay_rate: StaticParam(200),
+    };
+    check(
+        "trail",
+        &testing::render_modifier(&mut modifier, &ramp(), &[0]),
+    );
+}
+
+#[test]
+fn decay() {
+    let mut modifier = Decay {
+        rate: DynamicParam::<_, u8>::new(fade(1000, false), 0.0, 255.0),
+    };
+    check(
+        "decay",
+        &testing::render_modifier(&mut modifier, &ramp(), &timestamps(1000, 250)),
+    );
+}
+
+#[test]
+fn sparkle() {
+    let mut modifier = Sparkle {
+        chance: StaticParam(40),
+        hue: StaticHue::BLUE,
+        saturation: StaticParam(128),
+        intensity: StaticParam(255),
+        rng: Rng::new(0xC0FF_EE00),
+    };
+    check(
+        "sparkle",
+        &testing::render_modifier(
+            &mut modifier,
+            &[Pixel::BLACK; NUM_LEDS],
+            &timestamps(500, 50),
+        ),
+    );
+}
+
+#[test]
+fn blur() {
+    let mut modifier = Blur {
+        strength: DynamicParam::<_, u8>::new(fade(1000, false), 0.0, 255.0),
+    };
+    check(
+        "blur",
+        &testing::render_modifier(&mut modifier, &ramp(), &timestamps(1000, 250)),
+    );
+}
+
+#[test]
+fn shift() {
+    let mut modifier = Shift {
+        offset: StaticParam(5isize),
+    };
+    let mut reverse_shift = Shift {
+        offset: StaticParam(-21isize),
+    };
+    let forward = testing::render_modifier(&mut modifier, &ramp(), &[0]);
+    let backward = testing::render_modifier(&mut reverse_shift, &ramp(), &[0]);
+    check("shift", &(forward + &backward));
+}
+
+#[test]
+fn mirror() {
+    let mut modifier = Mirror { center: 6 };
+    check(
+        "mirror",
+        &testing::render_modifier(&mut modifier, &ramp(), &[0]),
+    );
+}
+
+#[test]
+fn reverse() {
+    check(
+        "reverse",
+        &testing::render_modifier(&mut Reverse, &ramp(), &[0]),
+    );
+}
+
+// ============================================================================
+// ENVELOPES
+// ============================================================================
+
+#[test]
+fn constant() {
+    check(
+        "constant",
+        &testing::sample_envelope::<_, u8>(&Constant, &timestamps(1000, 500)),
+    );
+}
+
+#[test]
+fn fade_envelope() {
+    let ts = timestamps(1200, 100);
+    let up = testing::sample_envelope::<_, u8>(&fade(1000, false), &ts);
+    let down = testing::sample_envelope::<_, u8>(&fade(1000, true), &ts);
+    let wide = testing::sample_envelope::<_, u16>(&fade(60_000, false), &timestamps(60_000, 5000));
+    check("fade", &(up + &down + &wide));
+}
+
+#[test]
+fn triangle() {
+    let envelope = Triangle {
+        start_time: Timestamp::from_millis(100),
+        period: 800,
+    };
+    check(
+        "triangle",
+        &testing::sample_envelope::<_, u8>(&envelope, &timestamps(2000, 50)),
+    );
+}
+
+#[test]
+fn sine() {
+    let envelope = Sine {
+        start_time: Timestamp::ZERO,
+        period: 1000,
+    };
+    let narrow = testing::sample_envelope::<_, u8>(&envelope, &timestamps(1500, 50));
+    let wide = testing::sample_envelope::<_, u16>(&envelope, &timestamps(1000, 125));
+    check("sine", &(narrow + &wide));
+}
+
+#[test]
+fn square() {
+    let envelope = Square {
+        start_time: Timestamp::ZERO,
+        period: 500,
+        duty_cycle: 64,
+    };
+    check(
+        "square",
+        &testing::sample_envelope::<_, u8>(&envelope, &timestamps(1000, 25)),
+    );
+}
+
+#[test]
+fn sawtooth() {
+    let envelope = Sawtooth {
+        start_time: Timestamp::from_millis(50),
+        period: 400,
+    };
+    check(
+        "sawtooth",
+        &testing::sample_envelope::<_, u8>(&envelope, &timestamps(1000, 25)),
+    );
+}
+
+#[test]
+fn adsr() {
+    let envelope = ADSR {
+        start_time: Timestamp::from_millis(100),
+        attack: 200,
+        decay: 300,
+        sustain_level: 128,
+        sustain_duration: 400,
+        release: 500,
+    };
+    check(
+        "adsr",
+        &testing::sample_envelope::<_, u8>(&envelope, &timestamps(1600, 50)),
+    );
+}
+
+#[test]
+fn envelope_pulse() {
+    let envelope = EnvelopePulse {
+        start_time: Timestamp::ZERO,
+        attack: 100,
+        decay: 900,
+    };
+    check(
+        "envelope_pulse",
+        &testing::sample_envelope::<_, u8>(&envelope, &timestamps(1100, 50)),
+    );
+}
+
+#[test]
+fn time_limited() {
+    let envelope = TimeLimited {
+        inner: Sawtooth {
+            start_time: Timestamp::ZERO,
+            period: 300,
+        },
+        start_time: Timestamp::ZERO,
+        duration: 700,
+    };
+    check(
+        "time_limited",
+        &testing::sample_envelope::<_, u8>(&envelope, &timestamps(1000, 100)),
+    );
+}
+
+#[test]
+fn loop_count() {
+    let envelope = LoopCount {
+        inner: Sawtooth {
+            start_time: Timestamp::ZERO,
+            period: 300,
+        },
+        start_time: Timestamp::ZERO,
+        period: 300,
+        max_loops: 2,
+    };
+    check(
+        "loop_count",
+        &testing::sample_envelope::<_, u8>(&envelope, &timestamps(1000, 100)),
+    );
+}
+
+#[test]
+fn velocity_integral() {
+    let envelope = VelocityIntegral::new(
+        Timestamp::ZERO,
+        Sine {
+            start_time: Timestamp::ZERO,
+            period: 1000,
+        },
+        10,
+        16,
+        20,
+    );
+    check(
+        "velocity_integral",
+        &testing::sample_envelope::<_, u8>(&envelope, &timestamps(3000, 100)),
+    );
+}
+
+#[test]
+fn combinators() {
+    let ts = timestamps(1000, 100);
+    let sine = || Sine {
+        start_time: Timestamp::ZERO,
+        period: 500,
+    };
+
+    let mut out = String::new();
+    out += &testing::sample_envelope::<_, u8>(
+        &Product {
+            env1: sine(),
+            env2: fade(1000, true),
+        },
+        &ts,
+    );
+    out += &testing::sample_envelope::<_, u8>(
+        &Sum {
+            env1: sine(),
+            env2: fade(1000, false),
+        },
+        &ts,
+    );
+    out += &testing::sample_envelope::<_, u8>(
+        &Min {
+            env1: sine(),
+            env2: fade(1000, false),
+        },
+        &ts,
+    );
+    out += &testing::sample_envelope::<_, u8>(
+        &Max {
+            env1: sine(),
+            env2: fade(1000, true),
+        },
+        &ts,
+    );
+    out += &testing::sample_envelope::<_, u8>(&Invert { inner: sine() }, &ts);
+    out += &testing::sample_envelope::<_, u8>(
+        &Clamp {
+            inner: sine(),
+            min: 64u8,
+            max: 192u8,
+        },
+        &ts,
+    );
+    check("combinators", &out);
+}
+
+// ============================================================================
+// STATEFUL ENVELOPES
+// ============================================================================
+
+#[test]
+fn smooth() {
+    // Square steps are easy to follow through the filter
+    let square = || Square {
+        start_time: Timestamp::ZERO,
+        period: 1000,
+        duty_cycle: 128,
+    };
+
+    let mut out =
+        testing::update_envelope::<_, u8>(&mut Smooth::new(square(), 100), &timestamps(2000, 25));
+    // Uneven frame times settle at the same rate
+    out += &testing::update_envelope::<_, u8>(
+        &mut Smooth::new(square(), 100),
+        &[0, 10, 60, 70, 200, 250, 480, 500, 510, 700, 990, 1000],
+    );
+    check("smooth", &out);
+}
+
+#[test]
+fn slew() {
+    let mut envelope = Slew::new(
+        Square {
+            start_time: Timestamp::ZERO,
+            period: 2000,
+            duty_cycle: 128,
+        },
+        1000,
+        400,
+    );
+    check(
+        "slew",
+        &testing::update_envelope::<_, u8>(&mut envelope, &timestamps(4000, 50)),
+    );
+}
+
+#[test]
+fn sample_hold() {
+    let mut envelope = SampleHold::new(
+        Sawtooth {
+            start_time: Timestamp::ZERO,
+            period: 1000,
+        },
+        Timestamp::ZERO,
+        150,
+    );
+    check(
+        "sample_hold",
+        &testing::update_envelope::<_, u8>(&mut envelope, &timestamps(1500, 25)),
+    );
+}
+
+#[test]
+fn stateful_parameter() {
+    // Stateful envelopes drive parameters through the `Stateful` adapter
+    let param = DynamicParam::<_, u8>::new(
+        Stateful::new(Slew::new(fade(500, false), 200, 200)),
+        0.0,
+        100.0,
+    );
+    check(
+        "stateful_parameter",
+        &testing::sample_parameter::<_, u8>(&param, &timestamps(2000, 100)),
+    );
+}
+
+// ============================================================================
+// RANDOM AND NOISE
+// ============================================================================
+
+#[test]
+fn rng() {
+    // Pins the generator so seeded effects replay identically across versions
+    let mut rng = Rng::new(1);
+    let mut out = String::new();
+    for _ in 0..16 {
+        out += &format!("{:08x}\n", rng.next_u32());
+    }
+    let mut rng = Rng::new(0);
+    out += &format!("seed 0: {:08x}\n", rng.next_u32());
+    for index in [0, 1, 2, u32::MAX as u64 + 1] {
+        out += &format!("hash {index}: {:08x}\n", led_effects::rng::hash(7, index));
+    }
+    check("rng", &out);
+}
+
+#[test]
+fn value_noise() {
+    let envelope = ValueNoise {
+        start_time: Timestamp::ZERO,
+        period: 250,
+        seed: 1,
+    };
+    check(
+        "value_noise",
+        &testing::sample_envelope::<_, u8>(&envelope, &timestamps(2000, 25)),
+    );
+}
+
+#[test]
+fn perlin_noise() {
+    let envelope = PerlinNoise {
+        start_time: Timestamp::ZERO,
+        period: 250,
+        seed: 1,
+    };
+    check(
+        "perlin_noise",
+        &testing::sample_envelope::<_, u8>(&envelope, &timestamps(2000, 25)),
+    );
+}
+
+#[test]
+fn random_step() {
+    let envelope = RandomStep {
+        start_time: Timestamp::ZERO,
+        period: 300,
+        transition: 100,
+        seed: 1,
+        easing: EaseInOutQuad,
+    };
+    check(
+        "random_step",
+        &testing::sample_envelope::<_, u8>(&envelope, &timestamps(1500, 25)),
+    );
+}
+
+#[test]
+fn random_walk() {
+    let mut envelope = RandomWalk::<u8>::new(Timestamp::ZERO, 20, 12, 1);
+    check(
+        "random_walk",
+        &testing::update_envelope::<_, u8>(&mut envelope, &timestamps(2000, 50)),
+    );
+}
+
+#[test]
+fn flicker() {
+    let envelope = Flicker {
+        start_time: Timestamp::ZERO,
+        interval: 40,
+        floor: 96,
+        seed: 1,
+    };
+    check(
+        "flicker",
+        &testing::sample_envelope::<_, u8>(&envelope, &timestamps(2000, 20)),
+    );
+}
+
+// ============================================================================
+// TEMPO
+// ============================================================================
+
+#[test]
+fn beat_envelopes() {
+    let clock = TempoClock::new(Timestamp::ZERO, 120_000, TimeSignature::FOUR_FOUR);
+    let sine = BeatSine {
+        clock: &clock,
+        period: 2 * BEAT,
+        phase: 0,
+    };
+    let saw = BeatSaw {
+        clock: &clock,
+        period: BEAT,
+        phase: BEAT / 2,
+    };
+    let pulse = BeatPulse {
+        clock: &clock,
+        period: BEAT,
+        phase: 0,
+        decay: BEAT / 2,
+    };
+    let fade = BarFade {
+        clock: &clock,
+        start_bar: 1,
+        bars: 1,
+        inverted: false,
+    };
+
+    // 120 BPM for two seconds, then slow down to 90 BPM mid-show
+    let before = timestamps(2000, 50);
+    let after: Vec<u64> = (2050..=5000).step_by(50).collect();
+
+    let mut out = String::new();
+    for (times, milli_bpm) in [(&before, 120_000), (&after, 90_000)] {
+        clock.set_tempo(Timestamp::from_millis(times[0]), milli_bpm);
+        out += &format!("tempo {milli_bpm}\n");
+        out += &testing::sample_envelope::<_, u8>(&sine, times);
+        out += &testing::sample_envelope::<_, u8>(&saw, times);
+        out += &testing::sample_envelope::<_, u8>(&pulse, times);
+        out += &testing::sample_envelope::<_, u8>(&fade, times);
+    }
+    check("beat_envelopes", &out);
+}
+
+// ============================================================================
+// PARAMETERS AND HUES
+// ============================================================================
+
+#[test]
+fn parameters() {
+    let ts = timestamps(1000, 100);
+    let param = DynamicParam::<_, u8>::new(fade(1000, false), 20.0, 300.0);
+
+    let mut out = String::new();
+    out += &testing::sample_parameter::<_, u8>(&param, &ts);
+    out += &testing::sample_parameter::<_, u16>(&param, &ts);
+    out += &testing::sample_parameter::<_, f32>(&param, &ts);
+    out += &testing::sample_parameter::<_, u8>(&StaticParam(42u8), &[0, 1000]);
+    check("parameters", &out);
+}
+
+#[test]
+fn hues() {
+    let ts = timestamps(2000, 100);
+
+    let mut out = String::new();
+    out += &testing::sample_hue(&StaticHue::MAGENTA, &[0]);
+    out += &testing::sample_hue(&StaticHue::from_degrees(359), &[0]);
+    out += &testing::sample_hue(
+        &RotatingHue {
+            start_time: Timestamp::ZERO,
+            degrees_per_ms: 0.36,
+        },
+        &ts,
+    );
+    out += &testing::sample_hue(
+        &HueOscillate {
+            start_time: Timestamp::ZERO,
+            period: 1000,
+            hue1: 40,
+            hue2: 200,
+        },
+        &ts,
+    );
+    out += &testing::sample_hue(
+        &HueOscillate {
+            start_time: Timestamp::ZERO,
+            period: 1000,
+            hue1: 200,
+            hue2: 40,
+        },
+        &ts,
+    );
+    check("hues", &out);
+}
+
+// ============================================================================
+// EASING
+// ============================================================================
+
+#[test]
+fn easing() {
+    let mut out = String::new();
+    let mut curve = |name: &str, samples: String| {
+        out += &format!("# {name}\n");
+        out += &samples;
+    };
+
+    curve("linear", testing::sample_easing(&Linear, 10));
+    curve("ease_in_quad", testing::sample_easing(&EaseInQuad, 10));
+    curve("ease_in_cubic", testing::sample_easing(&EaseInCubic, 10));
+    curve("ease_in_quart", testing::sample_easing(&EaseInQuart, 10));
+    curve("ease_in_expo", testing::sample_easing(&EaseInExpo, 10));
+    curve("ease_out_quad", testing::sample_easing(&EaseOutQuad, 10));
+    curve("ease_out_cubic", testing::sample_easing(&EaseOutCubic, 10));
+    curve("ease_out_quart", testing::sample_easing(&EaseOutQuart, 10));
+    curve("ease_out_expo", testing::sample_easing(&EaseOutExpo, 10));
+    curve(
+        "ease_in_out_quad",
+        testing::sample_easing(&EaseInOutQuad, 10),
+    );
+    curve(
+        "ease_in_out_cubic",
+        testing::sample_easing(&EaseInOutCubic, 10),
+    );
+    curve(
+        "ease_in_out_quart",
+        testing::sample_easing(&EaseInOutQuart, 10),
+    );
+    curve(
+        "ease_in_out_expo",
+        testing::sample_easing(&EaseInOutExpo, 10),
+    );
+    curve("bounce_out", testing::sample_easing(&BounceOut, 10));
+    curve("bounce_in", testing::sample_easing(&BounceIn, 10));
+    curve("bounce_in_out", testing::sample_easing(&BounceInOut, 10));
+    curve(
+        "elastic_out",
+        testing::sample_easing(&ElasticOut::standard(), 10),
+    );
+    curve(
+        "elastic_in",
+        testing::sample_easing(&ElasticIn::standard(), 10),
+    );
+    curve(
+        "elastic_in_out",
+        testing::sample_easing(&ElasticInOut::standard(), 10),
+    );
+    curve("back_out", testing::sample_easing(&BackOut::standard(), 10));
+    curve("back_in", testing::sample_easing(&BackIn::standard(), 10));
+    curve(
+        "back_in_out",
+        testing::sample_easing(&BackInOut::standard(), 10),
+    );
+
+    check("easing", &out);
+}
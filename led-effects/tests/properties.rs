@@ -0,0 +1,624 @@
+//! Property-based tests for envelope and `Pixel` arithmetic and particle
+//! bookkeeping.
+
+use led_effects::effects::core::traits::EnvelopeValue;
+use led_effects::rng::Rng;
+use led_effects::{
+    ADSR, Boundary, DmxSlot, DmxUniverse, Emitter, Envelope, EnvelopePulse, Fade, Flicker,
+    Generator, Linear, ParticlePool, Particles, PerlinNoise, Pixel, RandomStep, RandomWalk,
+    SampleHold, Sawtooth, Sine, Slew, Smooth, Square, StatefulEnvelope, StaticHue, TimeLimited,
+    Timestamp, Triangle, ValueNoise, VelocityIntegral,
+};
+use proptest::prelude::*;
+
+fn pixel() -> impl Strategy<Value = Pixel> {
+    any::<(u8, u8, u8)>().prop_map(|(r, g, b)| Pixel::new(r, g, b))
+}
+
+fn channels(pixel: Pixel) -> [u8; 3] {
+    [pixel.r, pixel.g, pixel.b]
+}
+
+// ============================================================================
+// PIXEL
+// ============================================================================
+
+proptest! {
+    #[test]
+    fn lerp_stays_between_endpoints(a in pixel(), b in pixel(), t: u8) {
+        let mixed = a.lerp(&b, t);
+
+        for ((from, to), value) in channels(a).into_iter().zip(channels(b)).zip(channels(mixed)) {
+            prop_assert!(value >= from.min(to) && value <= from.max(to));
+        }
+    }
+
+    #[test]
+    fn lerp_hits_endpoints(a in pixel(), b in pixel()) {
+        prop_assert_eq!(a.lerp(&b, 0), a);
+        prop_assert_eq!(a.lerp(&b, 255), b);
+    }
+
+    #[test]
+    fn scale_never_brightens(p in pixel(), factor: u8) {
+        let scaled = p.scale(factor);
+
+        for (before, after) in channels(p).into_iter().zip(channels(scaled)) {
+            prop_assert!(after <= before);
+        }
+        prop_assert_eq!(p.scale(255), p);
+        prop_assert_eq!(p.scale(0), Pixel::BLACK);
+    }
+
+    #[test]
+    fn add_is_commutative_and_saturating(a in pixel(), b in pixel()) {
+        let sum = a.add(b);
+
+        prop_assert_eq!(sum, b.add(a));
+        for ((x, y), value) in channels(a).into_iter().zip(channels(b)).zip(channels(sum)) {
+            prop_assert_eq!(value, x.saturating_add(y));
+        }
+    }
+
+    #[test]
+    fn to_hsv_value_is_max_channel(p in pixel()) {
+        let (_, saturation, value) = p.to_hsv_u8();
+
+        prop_assert_eq!(value, p.r.max(p.g).max(p.b));
+        if value == p.r.min(p.g).min(p.b) {
+            prop_assert_eq!(saturation, 0);
+        }
+    }
+
+    #[test]
+    fn hsv_round_trip_is_stable(hue: u8, saturation: u8, value: u8) {
+        // Integer HSV can't be exact both ways, but a pixel produced by
+        // from_hsv must come back within one step per channel
+        let pixel = Pixel::from_hsv(hue, saturation, value);
+        let (h, s, v) = pixel.to_hsv_u8();
+
+        for (before, after) in channels(pixel).into_iter().zip(channels(Pixel::from_hsv(h, s, v))) {
+            prop_assert!(before.abs_diff(after) <= 1);
+        }
+    }
+
+    #[test]
+    fn hsv_round_trip_keeps_saturated_hues(hue: u8) {
+        prop_assert_eq!(Pixel::from_hsv(hue, 255, 255).to_hsv_u8(), (hue, 255, 255));
+    }
+
+    #[test]
+    fn hsv_round_trip_preserves_grays(level: u8) {
+        let gray = Pixel::from_hsv(0, 0, level);
+
+        prop_assert_eq!(gray.r, gray.g);
+        prop_assert_eq!(gray.g, gray.b);
+        prop_assert_eq!(Pixel::from_hsv(gray.to_hsv_u8().0, 0, gray.to_hsv_u8().2), gray);
+    }
+}
+
+// ============================================================================
+// ENVELOPE VALUES
+// ============================================================================
+
+fn check_progress<T: EnvelopeValue + core::fmt::Debug>(
+    elapsed: u32,
+    duration: u32,
+) -> Result<(), TestCaseError> {
+    let elapsed = elapsed % (duration + 1);
+    let value = T::from_progress(elapsed, duration);
+
+    prop_assert!(value <= T::MAX);
+    prop_assert_eq!(T::from_progress(0, duration), T::ZERO);
+    prop_assert_eq!(T::from_progress(duration, duration), T::MAX);
+    if elapsed < duration {
+        prop_assert!(T::from_progress(elapsed + 1, duration) >= value);
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn from_progress_u8(elapsed: u32, duration in 1..u32::MAX) {
+        check_progress::<u8>(elapsed, duration)?;
+    }
+
+    #[test]
+    fn from_progress_u16(elapsed: u32, duration in 1..u32::MAX) {
+        check_progress::<u16>(elapsed, duration)?;
+    }
+
+    #[test]
+    fn from_progress_u32(elapsed: u32, duration in 1..u32::MAX) {
+        check_progress::<u32>(elapsed, duration)?;
+    }
+}
+
+// ============================================================================
+// ENVELOPES
+// ============================================================================
+
+/// Any time up to ~8900 years, well past the 49.7 day u32 wrap
+const TIME: core::ops::Range<u64> = 0..1 << 48;
+
+fn ms(millis: u64) -> Timestamp {
+    Timestamp::from_millis(millis)
+}
+
+proptest! {
+    #[test]
+    fn fade_is_monotonic(start in TIME, duration in 1u32.., a in TIME, b in TIME) {
+        let (earlier, later) = (ms(a.min(b)), ms(a.max(b)));
+        let up = Fade { start_time: ms(start), duration, inverted: false };
+        let down = Fade { start_time: ms(start), duration, inverted: true };
+
+        prop_assert!(
+            Envelope::<u16>::sample(&up, earlier) <= Envelope::<u16>::sample(&up, later)
+        );
+        prop_assert!(
+            Envelope::<u16>::sample(&down, earlier) >= Envelope::<u16>::sample(&down, later)
+        );
+    }
+
+    #[test]
+    fn fade_reaches_its_end_value(start in TIME, duration in 1u32.., after in TIME) {
+        let now = ms(start + duration as u64 + after);
+        let up = Fade { start_time: ms(start), duration, inverted: false };
+        let down = Fade { start_time: ms(start), duration, inverted: true };
+
+        prop_assert_eq!(Envelope::<u8>::sample(&up, now), u8::MAX);
+        prop_assert_eq!(Envelope::<u8>::sample(&down, now), 0);
+        prop_assert!(!Envelope::<u8>::is_alive(&up, now));
+    }
+
+    #[test]
+    fn fade_is_alive_until_duration(start in TIME, duration: u32, now in TIME) {
+        let fade = Fade { start_time: ms(start), duration, inverted: false };
+
+        prop_assert_eq!(
+            Envelope::<u8>::is_alive(&fade, ms(now)),
+            now < start + duration as u64
+        );
+    }
+
+    #[test]
+    fn sine_is_periodic(period in 1u32.., start in TIME, offset in TIME) {
+        let sine = Sine { start_time: ms(start), period };
+        let now = start + offset;
+
+        prop_assert_eq!(
+            Envelope::<u16>::sample(&sine, ms(now)),
+            Envelope::<u16>::sample(&sine, ms(now + period as u64))
+        );
+    }
+
+    #[test]
+    fn triangle_is_periodic(period in 1u32.., start in TIME, offset in TIME) {
+        let triangle = Triangle { start_time: ms(start), period };
+        let now = start + offset;
+
+        prop_assert_eq!(
+            Envelope::<u16>::sample(&triangle, ms(now)),
+            Envelope::<u16>::sample(&triangle, ms(now + period as u64))
+        );
+    }
+
+    #[test]
+    fn sawtooth_is_periodic(period in 1u32.., start in TIME, offset in TIME) {
+        let sawtooth = Sawtooth { start_time: ms(start), period };
+        let now = start + offset;
+
+        prop_assert_eq!(
+            Envelope::<u32>::sample(&sawtooth, ms(now)),
+            Envelope::<u32>::sample(&sawtooth, ms(now + period as u64))
+        );
+    }
+
+    #[test]
+    fn sawtooth_rises_within_a_cycle(period in 2u32.., a: u32, b: u32) {
+        let sawtooth = Sawtooth { start_time: Timestamp::ZERO, period };
+        let (earlier, later) = (a.min(b) % period, a.max(b) % period);
+        prop_assume!(earlier <= later);
+
+        prop_assert!(
+            Envelope::<u16>::sample(&sawtooth, ms(earlier as u64))
+                <= Envelope::<u16>::sample(&sawtooth, ms(later as u64))
+        );
+    }
+
+    #[test]
+    fn adsr_phases(
+        start in TIME,
+        attack: u32,
+        decay: u32,
+        sustain_level: u8,
+        sustain_duration: u32,
+        release: u32,
+        now in TIME,
+    ) {
+        let adsr = ADSR {
+            start_time: ms(start),
+            attack,
+            decay,
+            sustain_level,
+            sustain_duration,
+            release,
+        };
+        let value: u16 = adsr.sample(ms(now));
+
+        let elapsed = now.saturating_sub(start);
+        let sustain_start = attack as u64 + decay as u64;
+        let release_start = sustain_start + sustain_duration as u64;
+        let end = release_start + release as u64;
+
+        if (sustain_start..release_start).contains(&elapsed) {
+            prop_assert_eq!(value, u16::from_progress(sustain_level as u32, 255));
+        }
+        if elapsed >= end {
+            prop_assert_eq!(value, 0);
+        }
+        prop_assert_eq!(Envelope::<u16>::is_alive(&adsr, ms(now)), now < start + end);
+    }
+
+    #[test]
+    fn pulse_and_time_limited_handle_any_time(
+        start: u64,
+        attack: u32,
+        decay: u32,
+        duration: u32,
+        now: u64,
+    ) {
+        let start_time = ms(start);
+        let pulse = EnvelopePulse { start_time, attack, decay };
+        let limited = TimeLimited {
+            inner: EnvelopePulse { start_time, attack, decay },
+            start_time,
+            duration,
+        };
+
+        let _: u8 = pulse.sample(ms(now));
+        let _: u8 = limited.sample(ms(now));
+        let _ = Envelope::<u8>::is_alive(&pulse, ms(now));
+        let _ = Envelope::<u8>::is_alive(&limited, ms(now));
+    }
+}
+
+// ============================================================================
+// VELOCITY INTEGRAL
+// ============================================================================
+
+/// The original from-scratch integration, kept as the reference result
+fn integrate_from_start<V: Envelope<u16>>(
+    velocity: &V,
+    start_time: Timestamp,
+    initial_position: u32,
+    dt_ms: u32,
+    velocity_scale: u32,
+    now: Timestamp,
+) -> u16 {
+    let steps = (now.millis_since(start_time) / dt_ms as u64).max(1);
+    let mut position = initial_position;
+
+    for i in 0..steps {
+        let velocity = velocity.sample(start_time.add_millis(i * dt_ms as u64)) as u32;
+        position = position.saturating_add((velocity * velocity_scale) / u16::MAX as u32);
+    }
+
+    u16::from_progress(position % u16::MAX as u32, u16::MAX as u32)
+}
+
+proptest! {
+    #[test]
+    fn velocity_integral_matches_full_integration(
+        start in 0u64..10_000,
+        period in 1u32..5_000,
+        initial_position: u32,
+        dt_ms in 1u32..50,
+        velocity_scale in 0u32..1_000,
+        // Mostly forward steps, with the occasional jump back in time
+        deltas in prop::collection::vec(-2_000i64..5_000, 1..40),
+    ) {
+        let sine = || Sine { start_time: Timestamp::from_millis(start), period };
+        let start_time = Timestamp::from_millis(start);
+        let integral =
+            VelocityIntegral::new(start_time, sine(), initial_position, dt_ms, velocity_scale);
+
+        let mut now = 0i64;
+        for delta in deltas {
+            now = (now + delta).max(0);
+            let at = Timestamp::from_millis(now as u64);
+
+            prop_assert_eq!(
+                Envelope::<u16>::sample(&integral, at),
+                integrate_from_start(&sine(), start_time, initial_position, dt_ms, velocity_scale, at)
+            );
+        }
+    }
+
+    #[test]
+    fn velocity_integral_setters_drop_the_cache(
+        period in 1u32..5_000,
+        dt_ms in 1u32..50,
+        new_dt_ms in 1u32..50,
+        velocity_scale in 0u32..1_000,
+        new_scale in 0u32..1_000,
+        first in 0u64..10_000,
+        second in 0u64..10_000,
+    ) {
+        let sine = || Sine { start_time: Timestamp::ZERO, period };
+        let mut integral = VelocityIntegral::new(Timestamp::ZERO, sine(), 0, dt_ms, velocity_scale);
+        Envelope::<u16>::sample(&integral, Timestamp::from_millis(first));
+
+        integral.set_dt_ms(new_dt_ms);
+        integral.set_velocity_scale(new_scale);
+        let at = Timestamp::from_millis(first.max(second));
+
+        prop_assert_eq!(
+            Envelope::<u16>::sample(&integral, at),
+            integrate_from_start(&sine(), Timestamp::ZERO, 0, new_dt_ms, new_scale, at)
+        );
+    }
+}
+
+#[test]
+fn velocity_integral_treats_zero_dt_as_one() {
+    let sine = || Sine {
+        start_time: Timestamp::ZERO,
+        period: 1000,
+    };
+    let zero = VelocityIntegral::new(Timestamp::ZERO, sine(), 0, 0, 100);
+    let one = VelocityIntegral::new(Timestamp::ZERO, sine(), 0, 1, 100);
+    let mut changed = VelocityIntegral::new(Timestamp::ZERO, sine(), 0, 1, 100);
+    changed.set_dt_ms(0);
+
+    let at = ms(2500);
+    let expected: u16 = one.sample(at);
+    assert_eq!(Envelope::<u16>::sample(&zero, at), expected);
+    assert_eq!(Envelope::<u16>::sample(&changed, at), expected);
+}
+
+#[test]
+fn velocity_integral_reset_picks_up_a_live_source() {
+    let universe = DmxUniverse::new();
+    universe.publish(&[255]);
+    let velocity = DmxSlot {
+        universe: &universe,
+        address: 1,
+    };
+    let integral = VelocityIntegral::new(Timestamp::ZERO, velocity, 0, 10, 100);
+    let at = ms(1000);
+    let moving: u16 = integral.sample(at);
+    assert_ne!(moving, 0);
+
+    // The steps already integrated keep the velocity they had
+    universe.publish(&[0]);
+    assert_eq!(Envelope::<u16>::sample(&integral, at), moving);
+
+    integral.reset();
+    assert_eq!(Envelope::<u16>::sample(&integral, at), 0);
+}
+
+// ============================================================================
+// STATEFUL ENVELOPES
+// ============================================================================
+
+/// Increasing frame times starting at 0
+fn frame_times() -> impl Strategy<Value = Vec<u64>> {
+    prop::collection::vec(0u64..200, 1..60).prop_map(|gaps| {
+        gaps.into_iter()
+            .scan(0, |now, gap| {
+                *now += gap;
+                Some(*now)
+            })
+            .collect()
+    })
+}
+
+fn square(period: u32) -> Square {
+    Square {
+        start_time: Timestamp::ZERO,
+        period,
+        duty_cycle: 128,
+    }
+}
+
+proptest! {
+    #[test]
+    fn stateless_envelopes_update_like_sample(period in 1u32..5_000, times in frame_times()) {
+        let sine = Sine { start_time: Timestamp::ZERO, period };
+        let mut stateful = Sine { start_time: Timestamp::ZERO, period };
+
+        for now in times.into_iter().map(Timestamp::from_millis) {
+            prop_assert_eq!(
+                StatefulEnvelope::<u16>::update(&mut stateful, now),
+                Envelope::<u16>::sample(&sine, now)
+            );
+        }
+    }
+
+    #[test]
+    fn smooth_moves_toward_target(
+        period in 1u32..2_000,
+        time_constant: u16,
+        times in frame_times(),
+    ) {
+        let target = square(period);
+        let mut smooth = Smooth::new(square(period), time_constant as u32);
+        let mut previous: Option<u8> = None;
+
+        for now in times.into_iter().map(Timestamp::from_millis) {
+            let value: u8 = smooth.update(now);
+            let goal: u8 = target.sample(now);
+
+            if let Some(previous) = previous {
+                prop_assert!(value >= previous.min(goal) && value <= previous.max(goal));
+            }
+            previous = Some(value);
+        }
+    }
+
+    #[test]
+    fn slew_respects_its_rates(
+        period in 1u32..2_000,
+        rise_rate in 0u32..100_000,
+        fall_rate in 0u32..100_000,
+        times in frame_times(),
+    ) {
+        let mut slew = Slew::new(square(period), rise_rate, fall_rate);
+        let mut previous: Option<(u64, u16)> = None;
+
+        for now in times {
+            let value: u16 = slew.update(Timestamp::from_millis(now));
+
+            if let Some((then, last)) = previous {
+                let dt = now - then;
+                // +1 for truncating the sub-unit level
+                if value > last {
+                    prop_assert!((value - last) as u64 <= rise_rate as u64 * dt / 1000 + 1);
+                } else {
+                    prop_assert!((last - value) as u64 <= fall_rate as u64 * dt / 1000 + 1);
+                }
+            }
+            previous = Some((now, value));
+        }
+    }
+
+    #[test]
+    fn sample_hold_holds_the_boundary_value(
+        hold in 0u32..500,
+        period in 1u32..2_000,
+        times in frame_times(),
+    ) {
+        let sawtooth = Sawtooth { start_time: Timestamp::ZERO, period };
+        let mut held = SampleHold::new(
+            Sawtooth { start_time: Timestamp::ZERO, period },
+            Timestamp::ZERO,
+            hold,
+        );
+
+        for now in times {
+            // A zero period passes every value through
+            let boundary = now - now.checked_rem(hold as u64).unwrap_or(0);
+            let value: u16 = held.update(Timestamp::from_millis(now));
+
+            prop_assert_eq!(value, sawtooth.sample(Timestamp::from_millis(boundary)));
+        }
+    }
+}
+
+// ============================================================================
+// RANDOM AND NOISE
+// ============================================================================
+
+proptest! {
+    #[test]
+    fn rng_below_stays_in_bounds(seed: u32, bound in 1u32..) {
+        let mut rng = Rng::new(seed);
+        for _ in 0..16 {
+            prop_assert!(rng.below(bound) < bound);
+        }
+    }
+
+    #[test]
+    fn noise_is_continuous(seed: u32, period in 100u32..5_000, now in TIME) {
+        // Neighbouring milliseconds never jump more than a few percent
+        let value = ValueNoise { start_time: Timestamp::ZERO, period, seed };
+        let perlin = PerlinNoise { start_time: Timestamp::ZERO, period, seed };
+        let step = RandomStep {
+            start_time: Timestamp::ZERO,
+            period,
+            transition: period,
+            seed,
+            easing: Linear,
+        };
+        let flicker = Flicker { start_time: Timestamp::ZERO, interval: period, floor: 0, seed };
+
+        let envelopes: [&dyn Envelope<u16>; 4] = [&value, &perlin, &step, &flicker];
+        for envelope in envelopes {
+            let a = envelope.sample(ms(now));
+            let b = envelope.sample(ms(now + 1));
+            prop_assert!(a.abs_diff(b) <= u16::MAX / 50, "{} -> {}", a, b);
+        }
+    }
+
+    #[test]
+    fn flicker_respects_its_floor(seed: u32, floor: u8, interval in 1u32..1_000, now in TIME) {
+        let flicker = Flicker { start_time: Timestamp::ZERO, interval, floor, seed };
+        prop_assert!(Envelope::<u8>::sample(&flicker, ms(now)) >= floor);
+    }
+
+    #[test]
+    fn random_walk_ignores_frame_rate(
+        seed: u32,
+        interval in 1u32..100,
+        max_step: u8,
+        times in frame_times(),
+    ) {
+        // Updating every frame lands where a fresh walk jumping straight there does
+        let walk = || RandomWalk::<u8>::new(Timestamp::ZERO, interval, max_step, seed);
+        let mut every_frame = walk();
+
+        for now in times {
+            prop_assert_eq!(every_frame.update(ms(now)), walk().update(ms(now)));
+        }
+    }
+}
+
+// ============================================================================
+// PARTICLES
+// ============================================================================
+
+fn boundary() -> impl Strategy<Value = Boundary> {
+    prop_oneof![
+        Just(Boundary::Kill),
+        (0.0f32..=1.0).prop_map(|restitution| Boundary::Bounce { restitution }),
+        Just(Boundary::Wrap),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn particles_die_out_after_emission_stops(
+        seed: u32,
+        rate in 0u16..500,
+        velocity in -50.0f32..50.0,
+        gravity in -50.0f32..50.0,
+        lifetime in 1u32..2_000,
+        boundary in boundary(),
+        times in frame_times(),
+    ) {
+        let mut effect = Particles::<8, _, _> {
+            start_time: Timestamp::ZERO,
+            duration: 1_000,
+            emitter: Emitter::Random { rate },
+            min_velocity: -velocity.abs(),
+            max_velocity: velocity.abs(),
+            gravity,
+            drag: 0.5,
+            lifetime,
+            lifetime_jitter: lifetime / 2,
+            boundary,
+            hue: StaticHue::RED,
+            hue_spread: 255,
+            hue_shift: 0,
+            saturation: 255,
+            intensity: Fade { start_time: Timestamp::ZERO, duration: lifetime, inverted: true },
+            rng: Rng::new(seed),
+            pool: ParticlePool::new(),
+        };
+        let mut buffer = [Pixel::BLACK; 12];
+
+        for &now in &times {
+            effect.generate(&mut buffer, ms(now));
+            prop_assert!(effect.pool.alive() <= 8);
+        }
+
+        // Every particle has outlived its lifetime well after the emitter stopped
+        let end = times.last().unwrap().max(&1_000) + 2 * lifetime as u64;
+        buffer.fill(Pixel::BLACK);
+        effect.generate(&mut buffer, ms(end));
+        prop_assert_eq!(effect.pool.alive(), 0);
+        prop_assert!(!effect.is_alive(ms(end)));
+        prop_assert!(buffer.iter().all(|&p| p == Pixel::BLACK));
+    }
+}
@@ -0,0 +1,164 @@
+//! Tempo clock behaviour: beat math, tempo changes, phase offset and tap tempo.
+
+use led_effects::{
+    BEAT, BarFade, BeatSaw, BeatSine, Envelope, TempoClock, TimeSignature, Timestamp,
+};
+
+fn ms(millis: u64) -> Timestamp {
+    Timestamp::from_millis(millis)
+}
+
+fn clock(milli_bpm: u32) -> TempoClock {
+    TempoClock::new(Timestamp::ZERO, milli_bpm, TimeSignature::FOUR_FOUR)
+}
+
+#[test]
+fn counts_beats_and_bars() {
+    let clock = clock(120_000);
+
+    assert_eq!(clock.beat(ms(0)), 0);
+    assert_eq!(clock.beat(ms(499)), 0);
+    assert_eq!(clock.beat(ms(500)), 1);
+    assert_eq!(clock.beat_position(ms(250)), BEAT as u64 / 2);
+    assert_eq!(clock.bar(ms(1999)), 0);
+    assert_eq!(clock.bar(ms(2000)), 1);
+
+    clock.set_time_signature(TimeSignature::THREE_FOUR);
+    assert_eq!(clock.bar(ms(1500)), 1);
+}
+
+#[test]
+fn tempo_changes_keep_the_beat_position() {
+    let clock = clock(120_000);
+    let saw = BeatSaw {
+        clock: &clock,
+        period: BEAT,
+        phase: 0,
+    };
+
+    let before: u16 = saw.sample(ms(1125));
+    clock.set_tempo(ms(1125), 60_000);
+    let after: u16 = saw.sample(ms(1125));
+    assert_eq!(before, after);
+
+    // A quarter beat in at 120 BPM, the next beat is 750 ms away at 60 BPM
+    assert_eq!(clock.beat(ms(1874)), 2);
+    assert_eq!(clock.beat(ms(1875)), 3);
+}
+
+#[test]
+fn envelopes_follow_tempo_changes_without_jumps() {
+    let clock = clock(128_000);
+    let sine = BeatSine {
+        clock: &clock,
+        period: BEAT,
+        phase: 0,
+    };
+
+    let mut previous: u8 = sine.sample(ms(0));
+    for now in 1..20_000 {
+        // Ramp the tempo every 100 ms, as a DJ pitch fader would
+        if now % 100 == 0 {
+            clock.set_tempo(ms(now), 128_000 + (now as u32 / 100) * 250);
+        }
+        let value: u8 = sine.sample(ms(now));
+        assert!(
+            value.abs_diff(previous) <= 8,
+            "jump {previous} -> {value} at {now}"
+        );
+        previous = value;
+    }
+}
+
+#[test]
+fn phase_offset_shifts_beats() {
+    let clock = clock(120_000);
+
+    clock.set_phase_offset(BEAT as i32 / 4);
+    assert_eq!(clock.beat_position(ms(0)), BEAT as u64 / 4);
+    assert_eq!(clock.beat(ms(375)), 1);
+
+    // Negative offsets hold at beat 0 until the clock catches up
+    clock.set_phase_offset(-(BEAT as i32));
+    assert_eq!(clock.beat_position(ms(250)), 0);
+    assert_eq!(clock.beat(ms(1000)), 1);
+}
+
+#[test]
+fn tap_tempo_sets_tempo_and_phase() {
+    let clock = clock(120_000);
+
+    for tap in [10_000, 10_600, 11_200, 11_800] {
+        clock.tap(ms(tap));
+    }
+
+    assert_eq!(clock.milli_bpm(), 100_000);
+    // The last tap lands exactly on a beat
+    assert_eq!(clock.beat_position(ms(11_800)) % BEAT as u64, 0);
+    assert_eq!(
+        clock.beat(ms(12_400)),
+        clock.beat(ms(11_800)) + 1,
+        "next beat one interval after the last tap"
+    );
+}
+
+#[test]
+fn tap_tempo_restarts_after_a_pause() {
+    let clock = clock(120_000);
+
+    clock.tap(ms(0));
+    clock.tap(ms(400));
+    assert_eq!(clock.milli_bpm(), 150_000);
+
+    // A long gap starts a new measurement instead of averaging across it
+    clock.tap(ms(10_000));
+    assert_eq!(clock.milli_bpm(), 150_000);
+    clock.tap(ms(10_500));
+    assert_eq!(clock.milli_bpm(), 120_000);
+}
+
+#[test]
+fn bar_fade_is_bar_aligned() {
+    let clock = clock(120_000);
+    let fade = BarFade {
+        clock: &clock,
+        start_bar: 2,
+        bars: 2,
+        inverted: false,
+    };
+
+    assert_eq!(Envelope::<u8>::sample(&fade, ms(3999)), 0);
+    assert_eq!(Envelope::<u8>::sample(&fade, ms(6000)), 127);
+    assert!(Envelope::<u8>::is_alive(&fade, ms(7999)));
+    assert!(!Envelope::<u8>::is_alive(&fade, ms(8000)));
+    assert_eq!(Envelope::<u8>::sample(&fade, ms(8000)), 255);
+
+    // Halving the tempo halfway through stretches the rest of the fade
+    clock.set_tempo(ms(6000), 60_000);
+    assert_eq!(Envelope::<u8>::sample(&fade, ms(6000)), 127);
+    assert!(Envelope::<u8>::is_alive(&fade, ms(9999)));
+    assert!(!Envelope::<u8>::is_alive(&fade, ms(10_000)));
+}
+
+#[test]
+fn static_clock_is_shared_between_threads() {
+    static CLOCK: TempoClock = TempoClock::new(Timestamp::ZERO, 120_000, TimeSignature::FOUR_FOUR);
+    let saw = BeatSaw {
+        clock: &CLOCK,
+        period: BEAT,
+        phase: 0,
+    };
+
+    // Taps from another task, as the button would send them
+    std::thread::spawn(|| {
+        for tap in 0..4 {
+            CLOCK.tap(ms(1000 + tap * 400));
+        }
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(CLOCK.milli_bpm(), 150_000);
+    // A beat on the last tap, at 2200 ms
+    assert_eq!(Envelope::<u16>::sample(&saw, ms(2200)), 0);
+}
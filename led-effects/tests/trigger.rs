@@ -0,0 +1,179 @@
+//! Triggered one-shot effects: retrigger policies, voice stacking and firing
+//! from another thread (standing in for an interrupt).
+
+use led_effects::rng::Rng;
+use led_effects::{
+    Boundary, Emitter, Envelope, Fade, Generator, ParticlePool, Particles, Pixel, Pulse, Retrigger,
+    StatefulEnvelope, StaticHue, StaticParam, Timestamp, Trigger, Triggered,
+};
+
+fn ms(millis: u64) -> Timestamp {
+    Timestamp::from_millis(millis)
+}
+
+fn level(envelope: &mut impl StatefulEnvelope<u8>, now: Timestamp) -> u8 {
+    envelope.update(now)
+}
+
+/// Falls from MAX to 0 over 100 ms after each fire
+fn blip() -> Fade {
+    Fade {
+        start_time: Timestamp::ZERO,
+        duration: 100,
+        inverted: true,
+    }
+}
+
+#[test]
+fn idle_until_fired() {
+    let trigger = Trigger::new();
+    trigger.fire(); // Before construction, ignored
+    let mut envelope = Triggered::new(&trigger, Retrigger::Restart, [blip()]);
+
+    assert_eq!(level(&mut envelope, ms(1000)), 0);
+    trigger.fire();
+    assert_eq!(level(&mut envelope, ms(2000)), 255);
+    assert_eq!(level(&mut envelope, ms(2050)), 128);
+    assert_eq!(level(&mut envelope, ms(2100)), 0);
+    assert_eq!(envelope.active(), 0);
+    assert!(StatefulEnvelope::<u8>::is_alive(&envelope, ms(5000)));
+}
+
+#[test]
+fn restart_starts_over() {
+    let trigger = Trigger::new();
+    let mut envelope = Triggered::new(&trigger, Retrigger::Restart, [blip()]);
+
+    trigger.fire();
+    assert_eq!(level(&mut envelope, ms(0)), 255);
+    assert_eq!(level(&mut envelope, ms(50)), 128);
+    trigger.fire();
+    assert_eq!(level(&mut envelope, ms(60)), 255);
+    assert_eq!(level(&mut envelope, ms(110)), 128);
+}
+
+#[test]
+fn ignore_while_alive_drops_early_fires() {
+    let trigger = Trigger::new();
+    let mut envelope = Triggered::new(&trigger, Retrigger::IgnoreWhileAlive, [blip()]);
+
+    trigger.fire();
+    assert_eq!(level(&mut envelope, ms(0)), 255);
+    trigger.fire();
+    assert_eq!(level(&mut envelope, ms(50)), 128);
+
+    // Once the fade has finished the next fire starts it again
+    assert_eq!(level(&mut envelope, ms(100)), 0);
+    trigger.fire();
+    assert_eq!(level(&mut envelope, ms(120)), 255);
+}
+
+#[test]
+fn stack_plays_voices_side_by_side() {
+    let trigger = Trigger::new();
+    let mut envelope = Triggered::new(&trigger, Retrigger::Stack, [blip(), blip()]);
+
+    trigger.fire();
+    assert_eq!(level(&mut envelope, ms(0)), 255);
+    trigger.fire();
+    level(&mut envelope, ms(40));
+    assert_eq!(envelope.active(), 2);
+
+    // A third fire takes over the oldest voice
+    trigger.fire();
+    level(&mut envelope, ms(60));
+    assert_eq!(envelope.active(), 2);
+    assert_eq!(level(&mut envelope, ms(120)), 102); // Only the 60 ms voice is left
+    assert_eq!(envelope.active(), 2);
+    assert_eq!(level(&mut envelope, ms(150)), 26);
+    assert_eq!(envelope.active(), 1);
+}
+
+#[test]
+fn stacked_pulses_render_together() {
+    let trigger = Trigger::new();
+    let pulse = || Pulse {
+        start_time: Timestamp::ZERO,
+        duration: 300,
+        position: 8,
+        spread_speed: 0.02,
+        width: StaticParam(1),
+        intensity: StaticParam(255),
+        hue: StaticHue::RED,
+        saturation: StaticParam(255),
+    };
+    let mut effect = Triggered::new(&trigger, Retrigger::Stack, [pulse(), pulse()]);
+    let mut buffer = [Pixel::BLACK; 16];
+
+    effect.generate(&mut buffer, ms(0));
+    assert!(buffer.iter().all(|&p| p == Pixel::BLACK));
+
+    trigger.fire();
+    effect.generate(&mut buffer, ms(0));
+    trigger.fire();
+    buffer.fill(Pixel::BLACK);
+    effect.generate(&mut buffer, ms(100));
+
+    // The first pulse has spread 2 pixels, the second is still at the center
+    assert_ne!(buffer[8], Pixel::BLACK);
+    assert_ne!(buffer[6], Pixel::BLACK);
+    assert_ne!(buffer[10], Pixel::BLACK);
+    assert_eq!(buffer[0], Pixel::BLACK);
+    assert!(effect.is_alive(ms(10_000)));
+}
+
+#[test]
+fn fire_sets_off_a_burst() {
+    let trigger = Trigger::new();
+    let burst = Particles::<8, _, _> {
+        start_time: Timestamp::ZERO,
+        duration: 0,
+        emitter: Emitter::Burst {
+            position: 8.0,
+            count: 8,
+        },
+        min_velocity: -10.0,
+        max_velocity: 10.0,
+        gravity: 0.0,
+        drag: 0.0,
+        lifetime: 100,
+        lifetime_jitter: 0,
+        boundary: Boundary::Kill,
+        hue: StaticHue::ORANGE,
+        hue_spread: 0,
+        hue_shift: 0,
+        saturation: 255,
+        intensity: blip(),
+        rng: Rng::new(7),
+        pool: ParticlePool::new(),
+    };
+    let mut effect = Triggered::new(&trigger, Retrigger::Restart, [burst]);
+    let mut buffer = [Pixel::BLACK; 16];
+    let mut lit = |effect: &mut Triggered<_, 1>, now| {
+        buffer.fill(Pixel::BLACK);
+        effect.generate(&mut buffer, now);
+        buffer.iter().filter(|&&p| p != Pixel::BLACK).count()
+    };
+
+    assert_eq!(lit(&mut effect, ms(0)), 0);
+    trigger.fire();
+    assert!(lit(&mut effect, ms(1000)) > 0);
+    assert!(lit(&mut effect, ms(1050)) > 0);
+
+    // A fire mid-burst starts the voice over with a fresh burst
+    trigger.fire();
+    assert!(lit(&mut effect, ms(1060)) > 0);
+    assert!(lit(&mut effect, ms(1100)) > 0);
+    assert_eq!(lit(&mut effect, ms(1200)), 0);
+}
+
+#[test]
+fn fires_from_another_thread() {
+    static TRIGGER: Trigger = Trigger::new();
+    let mut envelope = Triggered::new(&TRIGGER, Retrigger::Restart, [blip()]);
+
+    std::thread::spawn(|| TRIGGER.fire()).join().unwrap();
+    assert_eq!(level(&mut envelope, ms(10)), 255);
+    assert_eq!(TRIGGER.count(), 1);
+    assert!(Envelope::<u8>::is_alive(&blip(), ms(99)));
+}
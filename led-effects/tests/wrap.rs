@@ -0,0 +1,151 @@
+//! Effects keep working across the point where a u32 millisecond clock
+//! would wrap (~49.7 days of uptime).
+
+use led_effects::{
+    Envelope, Fade, Generator, HueOscillate, HueParameter, LoopCount, Pixel, RotatingHue, Sawtooth,
+    Sine, SolidColor, Square, StaticHue, StaticParam, TimeLimited, Timestamp, Triangle,
+};
+
+/// First millisecond a u32 clock can't represent
+const WRAP: u64 = 1 << 32;
+
+fn ms(millis: u64) -> Timestamp {
+    Timestamp::from_millis(millis)
+}
+
+#[test]
+fn timestamp_arithmetic() {
+    let before = ms(WRAP - 10);
+    let after = ms(WRAP + 10);
+
+    assert_eq!(after.millis_since(before), 20);
+    assert_eq!(before.millis_since(after), 0);
+    assert_eq!(ms(WRAP + 5).elapsed_since(Timestamp::ZERO), u32::MAX);
+    assert_eq!(after.phase(before, 7), 6);
+    assert_eq!(before.add_millis(20), after);
+    assert_eq!(ms(u64::MAX).add_millis(1), ms(u64::MAX));
+}
+
+#[test]
+fn fade_spans_the_wrap() {
+    let fade = Fade {
+        start_time: ms(WRAP - 500),
+        duration: 1000,
+        inverted: false,
+    };
+
+    let before: u8 = fade.sample(ms(WRAP - 1));
+    let after: u8 = fade.sample(ms(WRAP + 1));
+    assert!((127..=128).contains(&before) && after >= before);
+    assert!(Envelope::<u8>::is_alive(&fade, ms(WRAP + 499)));
+    assert!(!Envelope::<u8>::is_alive(&fade, ms(WRAP + 500)));
+    assert_eq!(Envelope::<u8>::sample(&fade, ms(WRAP + 500)), 255);
+}
+
+#[test]
+fn waves_stay_in_phase_across_the_wrap() {
+    // 3000 doesn't divide 2^32, so a wrapping clock would jump mid-cycle
+    let period = 3000;
+    let start_time = Timestamp::ZERO;
+
+    let sine = Sine { start_time, period };
+    let triangle = Triangle { start_time, period };
+    let sawtooth = Sawtooth { start_time, period };
+    let square = Square {
+        start_time,
+        period,
+        duty_cycle: 128,
+    };
+
+    for now in (WRAP - 2 * period as u64..WRAP + 2 * period as u64).step_by(37) {
+        let reduced = ms(now % period as u64);
+        let now = ms(now);
+
+        assert_eq!(
+            Envelope::<u16>::sample(&sine, now),
+            Envelope::<u16>::sample(&sine, reduced)
+        );
+        assert_eq!(
+            Envelope::<u16>::sample(&triangle, now),
+            Envelope::<u16>::sample(&triangle, reduced)
+        );
+        assert_eq!(
+            Envelope::<u16>::sample(&sawtooth, now),
+            Envelope::<u16>::sample(&sawtooth, reduced)
+        );
+        assert_eq!(
+            Envelope::<u16>::sample(&square, now),
+            Envelope::<u16>::sample(&square, reduced)
+        );
+    }
+}
+
+#[test]
+fn limits_count_past_the_wrap() {
+    let limited = TimeLimited {
+        inner: Sine {
+            start_time: ms(WRAP - 1000),
+            period: 500,
+        },
+        start_time: ms(WRAP - 1000),
+        duration: 2000,
+    };
+    assert!(Envelope::<u8>::is_alive(&limited, ms(WRAP + 999)));
+    assert!(!Envelope::<u8>::is_alive(&limited, ms(WRAP + 1000)));
+
+    let looped = LoopCount {
+        inner: Sine {
+            start_time: Timestamp::ZERO,
+            period: 1000,
+        },
+        start_time: Timestamp::ZERO,
+        period: 1000,
+        max_loops: u32::MAX,
+    };
+    // u32::MAX loops of 1s last far longer than u32::MAX ms
+    assert!(Envelope::<u8>::is_alive(&looped, ms(WRAP * 100)));
+}
+
+#[test]
+fn hues_move_smoothly_across_the_wrap() {
+    let rotating = RotatingHue {
+        start_time: Timestamp::ZERO,
+        degrees_per_ms: 0.36,
+    };
+    let oscillate = HueOscillate {
+        start_time: Timestamp::ZERO,
+        period: 3000,
+        hue1: 0,
+        hue2: 255,
+    };
+
+    for hue in [&rotating as &dyn HueParameter, &oscillate] {
+        let mut previous = hue.sample(ms(WRAP - 50));
+        for now in WRAP - 49..WRAP + 50 {
+            let current = hue.sample(ms(now));
+            // One step per ms at most (wrapping around the hue circle)
+            let step = current
+                .wrapping_sub(previous)
+                .min(previous.wrapping_sub(current));
+            assert!(step <= 1, "hue jumped {previous} -> {current} at {now}");
+            previous = current;
+        }
+    }
+}
+
+#[test]
+fn generators_outlive_the_wrap() {
+    let mut effect = SolidColor {
+        start_time: ms(WRAP - 1000),
+        duration: 5000,
+        intensity: StaticParam(255u8),
+        hue: StaticHue::RED,
+        saturation: StaticParam(255u8),
+    };
+    let mut buffer = [Pixel::BLACK; 4];
+
+    effect.generate(&mut buffer, ms(WRAP + 1000));
+    assert!(effect.is_alive(ms(WRAP + 1000)));
+    assert!(!effect.is_alive(ms(WRAP + 4000)));
+    assert_eq!(buffer, [Pixel::from_hsv(0, 255, 255); 4]);
+}
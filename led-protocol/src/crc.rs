@@ -0,0 +1,9 @@
+use ::crc::{CRC_16_IBM_3740, Crc};
+
+/// CRC-16/CCITT-FALSE (IBM-3740 in the `crc` catalogue): poly 0x1021, init
+/// 0xFFFF, no reflection.
+pub(crate) const CRC: Crc<u16> = Crc::<u16>::new(&CRC_16_IBM_3740);
+
+pub const fn crc16(data: &[u8]) -> u16 {
+    CRC.checksum(data)
+}
@@ -0,0 +1,195 @@
+use crate::{HEADER_LEN, Packet, SYNC, crc, frame::Frame};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The length field is larger than the decoder's buffer
+    TooLong(u16),
+    /// Checksum mismatch; the packet was dropped
+    Crc,
+    UnknownKind(u8),
+    /// Known kind with a payload that doesn't fit it
+    Malformed(u8),
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooLong(len) => write!(f, "payload of {len} bytes is too long"),
+            Self::Crc => write!(f, "CRC mismatch"),
+            Self::UnknownKind(kind) => write!(f, "unknown packet kind {kind:#04x}"),
+            Self::Malformed(kind) => write!(f, "malformed packet of kind {kind:#04x}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Looking for the first sync byte
+    Sync,
+    /// Seen the first sync byte
+    Sync2,
+    /// Past a sync word, holding the bytes that follow it
+    Packet,
+}
+
+/// Incremental packet decoder with room for payloads of up to `N` bytes.
+///
+/// Feed it bytes as they arrive, in chunks of any size. The bytes after a
+/// sync word are held until the packet checks out; if it fails its CRC or
+/// claims more than `N` bytes, the decoder rescans them for the next sync
+/// word. A dropped byte costs the packet it hit and nothing after it, even
+/// when the bad length swallowed the packets behind it. Those come out one
+/// per byte pushed after the error.
+pub struct Decoder<const N: usize> {
+    state: State,
+    /// Kind, seq and length
+    header: [u8; HEADER_LEN - 2],
+    /// Payload then CRC; `tail` takes the CRC of a full-size payload
+    payload: [u8; N],
+    tail: [u8; 2],
+    /// Bytes held across `header`, `payload` and `tail`
+    held: usize,
+    /// Length of the packet last returned, dropped on the next push
+    done: usize,
+}
+
+impl<const N: usize> Decoder<N> {
+    pub const fn new() -> Self {
+        Self {
+            state: State::Sync,
+            header: [0; HEADER_LEN - 2],
+            payload: [0; N],
+            tail: [0; 2],
+            held: 0,
+            done: 0,
+        }
+    }
+
+    /// Drop any partial packet and whatever was held behind it
+    pub fn reset(&mut self) {
+        self.state = State::Sync;
+        self.held = 0;
+        self.done = 0;
+    }
+
+    /// `true` between packets
+    pub fn is_idle(&self) -> bool {
+        match self.state {
+            State::Sync => true,
+            State::Sync2 => false,
+            State::Packet => self.done > 0 && self.held == self.done,
+        }
+    }
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        u16::from_le_bytes([self.header[2], self.header[3]]) as usize
+    }
+
+    /// Byte `i` after the sync word
+    fn get(&self, i: usize) -> u8 {
+        let h = self.header.len();
+        if i < h {
+            self.header[i]
+        } else if i < h + N {
+            self.payload[i - h]
+        } else {
+            self.tail[i - h - N]
+        }
+    }
+
+    fn set(&mut self, i: usize, byte: u8) {
+        let h = self.header.len();
+        if i < h {
+            self.header[i] = byte;
+        } else if i < h + N {
+            self.payload[i - h] = byte;
+        } else {
+            self.tail[i - h - N] = byte;
+        }
+    }
+
+    /// Forget the first `n` held bytes
+    fn drop_front(&mut self, n: usize) {
+        for i in n..self.held {
+            self.set(i - n, self.get(i));
+        }
+        self.held -= n;
+    }
+
+    /// Look for a sync word in the held bytes and keep what follows it
+    fn resync(&mut self) {
+        match (1..self.held).find(|&i| [self.get(i - 1), self.get(i)] == SYNC) {
+            Some(i) => {
+                self.drop_front(i + 1);
+                self.state = State::Packet;
+            }
+            None => {
+                // The last byte may be the first half of the next sync word
+                self.state = if self.held > 0 && self.get(self.held - 1) == SYNC[0] {
+                    State::Sync2
+                } else {
+                    State::Sync
+                };
+                self.held = 0;
+            }
+        }
+    }
+
+    /// Feed one byte. Returns a packet or an error once the last byte of a
+    /// packet is in.
+    pub fn push(&mut self, byte: u8) -> Option<Result<Packet<'_>, DecodeError>> {
+        if self.done > 0 {
+            let done = core::mem::take(&mut self.done);
+            self.drop_front(done);
+            self.resync();
+        }
+        match self.state {
+            State::Sync if byte == SYNC[0] => self.state = State::Sync2,
+            State::Sync => {}
+            State::Sync2 if byte == SYNC[1] => self.state = State::Packet,
+            // A repeated first byte may still start the real sync word
+            State::Sync2 if byte == SYNC[0] => {}
+            State::Sync2 => self.state = State::Sync,
+            State::Packet => {
+                self.set(self.held, byte);
+                self.held += 1;
+            }
+        }
+        self.check()
+    }
+
+    /// Report the held packet once all of it is in
+    fn check(&mut self) -> Option<Result<Packet<'_>, DecodeError>> {
+        if self.state != State::Packet || self.held < self.header.len() {
+            return None;
+        }
+        let len = self.len();
+        if len > N {
+            self.resync();
+            return Some(Err(DecodeError::TooLong(len as u16)));
+        }
+        let end = self.header.len() + len + 2;
+        if self.held < end {
+            return None;
+        }
+
+        let mut digest = crc::CRC.digest();
+        digest.update(&self.header);
+        digest.update(&self.payload[..len]);
+        if digest.finalize() != u16::from_le_bytes([self.get(end - 2), self.get(end - 1)]) {
+            self.resync();
+            return Some(Err(DecodeError::Crc));
+        }
+
+        self.done = end;
+        let [kind, seq, ..] = self.header;
+        Some(Frame::from_payload(kind, &self.payload[..len]).map(|frame| Packet { seq, frame }))
+    }
+}
+
+impl<const N: usize> Default for Decoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{CHANNELS, OVERHEAD, SYNC, crc, decoder::DecodeError};
+
+// Packet kinds. Host to device below 0x80, device to host above.
+const PIXELS: u8 = 0x01;
+const SHOW: u8 = 0x02;
+const STOP: u8 = 0x03;
+const GET_STATS: u8 = 0x04;
+const ACK: u8 = 0x81;
+const NACK: u8 = 0x82;
+const STATS: u8 = 0x83;
+
+/// Five little-endian u32
+pub(crate) const STATS_LEN: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frame<'a> {
+    /// RGB bytes, three per pixel, for `channel` starting at pixel
+    /// `offset`. Shown on the next [`Frame::Show`]; only answered if
+    /// rejected.
+    Pixels {
+        channel: u8,
+        offset: u16,
+        data: &'a [u8],
+    },
+    /// Send everything received so far to the strips
+    Show,
+    /// Leave streaming mode and go back to text commands
+    Stop,
+    /// Ask for the driver counters, answered with [`Frame::Stats`]
+    GetStats,
+    /// Device: `Show` or `Stop` with this sequence number was handled
+    Ack,
+    /// Device: the packet was rejected (bad CRC, channel or range). Packets
+    /// that fail the CRC are answered with sequence number 0.
+    Nack,
+    /// Device: the driver counters
+    Stats(Stats),
+}
+
+/// LED driver counters since boot
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub frames_sent: u32,
+    pub dma_errors: u32,
+    /// Buffer refills that came too late
+    pub underruns: u32,
+    /// Wire time of the last frame in µs, reset included
+    pub frame_us: u32,
+    /// Achieved frame rate in hundredths of a frame per second
+    pub fps_centi: u32,
+}
+
+impl Stats {
+    fn to_bytes(self) -> [u8; STATS_LEN] {
+        let mut bytes = [0; STATS_LEN];
+        let fields = [
+            self.frames_sent,
+            self.dma_errors,
+            self.underruns,
+            self.frame_us,
+            self.fps_centi,
+        ];
+        let (words, _) = bytes.as_chunks_mut::<4>();
+        for (word, field) in words.iter_mut().zip(fields) {
+            *word = field.to_le_bytes();
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8; STATS_LEN]) -> Self {
+        let (words, _) = bytes.as_chunks::<4>();
+        let [frames_sent, dma_errors, underruns, frame_us, fps_centi] =
+            core::array::from_fn(|i| u32::from_le_bytes(words[i]));
+        Self {
+            frames_sent,
+            dma_errors,
+            underruns,
+            frame_us,
+            fps_centi,
+        }
+    }
+}
+
+/// A frame and the sequence number the host chose for it. Replies echo the
+/// sequence number of the packet they answer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Packet<'a> {
+    pub seq: u8,
+    pub frame: Frame<'a>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    /// `out` needs at least this many bytes
+    BufferTooSmall(usize),
+    /// More pixel data than a length field can describe
+    PayloadTooLong,
+}
+
+impl Frame<'_> {
+    fn kind(&self) -> u8 {
+        match self {
+            Self::Pixels { .. } => PIXELS,
+            Self::Show => SHOW,
+            Self::Stop => STOP,
+            Self::GetStats => GET_STATS,
+            Self::Ack => ACK,
+            Self::Nack => NACK,
+            Self::Stats(_) => STATS,
+        }
+    }
+
+    fn payload_len(&self) -> usize {
+        match self {
+            Self::Pixels { data, .. } => 3 + data.len(),
+            Self::Stats(_) => STATS_LEN,
+            _ => 0,
+        }
+    }
+
+    /// Bytes the frame takes on the wire
+    pub fn encoded_len(&self) -> usize {
+        OVERHEAD + self.payload_len()
+    }
+}
+
+impl<'a> Frame<'a> {
+    pub(crate) fn from_payload(kind: u8, payload: &'a [u8]) -> Result<Self, DecodeError> {
+        let frame = match kind {
+            PIXELS => {
+                let [channel, lo, hi, data @ ..] = payload else {
+                    return Err(DecodeError::Malformed(kind));
+                };
+                if *channel >= CHANNELS || data.len() % 3 != 0 {
+                    return Err(DecodeError::Malformed(kind));
+                }
+                return Ok(Self::Pixels {
+                    channel: *channel,
+                    offset: u16::from_le_bytes([*lo, *hi]),
+                    data,
+                });
+            }
+            STATS => {
+                let stats = payload
+                    .try_into()
+                    .map_err(|_| DecodeError::Malformed(kind))?;
+                return Ok(Self::Stats(Stats::from_bytes(stats)));
+            }
+            SHOW => Self::Show,
+            STOP => Self::Stop,
+            GET_STATS => Self::GetStats,
+            ACK => Self::Ack,
+            NACK => Self::Nack,
+            _ => return Err(DecodeError::UnknownKind(kind)),
+        };
+
+        if payload.is_empty() {
+            Ok(frame)
+        } else {
+            Err(DecodeError::Malformed(kind))
+        }
+    }
+}
+
+/// Write `packet` to the start of `out` and return its length
+pub fn encode(packet: &Packet<'_>, out: &mut [u8]) -> Result<usize, EncodeError> {
+    let frame = &packet.frame;
+    let len = u16::try_from(frame.payload_len()).map_err(|_| EncodeError::PayloadTooLong)?;
+    let total = frame.encoded_len();
+    if out.len() < total {
+        return Err(EncodeError::BufferTooSmall(total));
+    }
+
+    out[..2].copy_from_slice(&SYNC);
+    out[2] = frame.kind();
+    out[3] = packet.seq;
+    out[4..6].copy_from_slice(&len.to_le_bytes());
+    match frame {
+        Frame::Pixels {
+            channel,
+            offset,
+            data,
+        } => {
+            out[6] = *channel;
+            out[7..9].copy_from_slice(&offset.to_le_bytes());
+            out[9..9 + data.len()].copy_from_slice(data);
+        }
+        Frame::Stats(stats) => out[6..6 + STATS_LEN].copy_from_slice(&stats.to_bytes()),
+        _ => {}
+    }
+
+    let end = total - 2;
+    let crc = crc::crc16(&out[2..end]);
+    out[end..total].copy_from_slice(&crc.to_le_bytes());
+    Ok(total)
+}
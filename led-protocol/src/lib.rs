@@ -0,0 +1,37 @@
+//! Binary framing shared by the firmware and PC software (no_std, no alloc).
+//!
+//! Every packet on the wire is
+//!
+//! ```text
+//! A5 5A | kind u8 | seq u8 | len u16 LE | payload[len] | crc u16 LE
+//! ```
+//!
+//! The CRC (CRC-16/CCITT-FALSE) covers `kind` through the payload. A
+//! [`Decoder`] resynchronises on the next `A5 5A` after garbage or a bad
+//! packet, rescanning the bad packet's own bytes, so a dropped byte costs
+//! one packet, not the stream.
+
+#![no_std]
+
+mod crc;
+mod decoder;
+mod frame;
+
+pub use crc::crc16;
+pub use decoder::{DecodeError, Decoder};
+pub use frame::{EncodeError, Frame, Packet, Stats, encode};
+
+/// Start of every packet
+pub const SYNC: [u8; 2] = [0xA5, 0x5A];
+
+/// Sync, kind, seq and length
+pub const HEADER_LEN: usize = 6;
+
+/// Bytes around the payload: header and CRC
+pub const OVERHEAD: usize = HEADER_LEN + 2;
+
+/// Longest device-to-host packet, a [`Frame::Stats`]
+pub const MAX_REPLY_LEN: usize = OVERHEAD + frame::STATS_LEN;
+
+/// Number of LED channels a [`Frame::Pixels`] can address
+pub const CHANNELS: u8 = 8;
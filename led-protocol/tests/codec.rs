@@ -0,0 +1,223 @@
+//! Encoding, decoding and resynchronisation.
+
+use led_protocol::{DecodeError, Decoder, EncodeError, Frame, Packet, Stats, crc16, encode};
+
+fn encoded(packet: Packet<'_>) -> Vec<u8> {
+    let mut out = vec![0; packet.frame.encoded_len()];
+    let len = encode(&packet, &mut out).unwrap();
+    assert_eq!(len, out.len());
+    out
+}
+
+/// Feed `bytes` and collect everything the decoder reports, with the pixel
+/// data copied out
+fn decode_all<const N: usize>(
+    decoder: &mut Decoder<N>,
+    bytes: &[u8],
+) -> Vec<Result<(u8, String), DecodeError>> {
+    let mut out = Vec::new();
+    for &byte in bytes {
+        if let Some(result) = decoder.push(byte) {
+            out.push(result.map(|packet| (packet.seq, format!("{:?}", packet.frame))));
+        }
+    }
+    out
+}
+
+#[test]
+fn crc_matches_the_ccitt_false_check_value() {
+    assert_eq!(crc16(b"123456789"), 0x29B1);
+}
+
+#[test]
+fn every_frame_round_trips() {
+    let data = [1, 2, 3, 4, 5, 6];
+    let frames = [
+        Frame::Pixels {
+            channel: 7,
+            offset: 300,
+            data: &data,
+        },
+        Frame::Show,
+        Frame::Stop,
+        Frame::GetStats,
+        Frame::Ack,
+        Frame::Nack,
+        Frame::Stats(Stats {
+            frames_sent: 123_456,
+            dma_errors: 1,
+            underruns: 2,
+            frame_us: 2100,
+            fps_centi: 3998,
+        }),
+    ];
+
+    let mut decoder = Decoder::<64>::new();
+    for (seq, frame) in frames.into_iter().enumerate() {
+        let packet = Packet {
+            seq: seq as u8,
+            frame,
+        };
+        let bytes = encoded(packet);
+        let (last, head) = bytes.split_last().unwrap();
+        for &byte in head {
+            assert!(decoder.push(byte).is_none());
+        }
+        assert_eq!(decoder.push(*last), Some(Ok(packet)));
+        assert!(decoder.is_idle());
+    }
+}
+
+#[test]
+fn encode_checks_the_buffer() {
+    let packet = Packet {
+        seq: 0,
+        frame: Frame::Pixels {
+            channel: 0,
+            offset: 0,
+            data: &[0; 30],
+        },
+    };
+    let mut small = [0; 20];
+    assert_eq!(
+        encode(&packet, &mut small),
+        Err(EncodeError::BufferTooSmall(41))
+    );
+}
+
+#[test]
+fn garbage_between_packets_is_skipped() {
+    let mut stream = vec![0x00, 0xA5, 0xA5, 0x13, 0x5A];
+    stream.extend(encoded(Packet {
+        seq: 1,
+        frame: Frame::Show,
+    }));
+    stream.extend([0xFF; 7]);
+    stream.extend(encoded(Packet {
+        seq: 2,
+        frame: Frame::Stop,
+    }));
+
+    let mut decoder = Decoder::<64>::new();
+    assert_eq!(
+        decode_all(&mut decoder, &stream),
+        [Ok((1, "Show".to_string())), Ok((2, "Stop".to_string()))]
+    );
+}
+
+#[test]
+fn corruption_costs_only_the_damaged_packet() {
+    let data = [9; 12];
+    let pixels = encoded(Packet {
+        seq: 1,
+        frame: Frame::Pixels {
+            channel: 2,
+            offset: 0,
+            data: &data,
+        },
+    });
+
+    let mut damaged = pixels.clone();
+    damaged[10] ^= 0x40;
+    let mut stream = damaged;
+    stream.extend(&pixels);
+
+    let mut decoder = Decoder::<64>::new();
+    let results = decode_all(&mut decoder, &stream);
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0], Err(DecodeError::Crc));
+    assert_eq!(results[1].as_ref().map(|(seq, _)| *seq), Ok(1));
+}
+
+fn pixels(seq: u8, channel: u8, data: &[u8]) -> Vec<u8> {
+    encoded(Packet {
+        seq,
+        frame: Frame::Pixels {
+            channel,
+            offset: 0,
+            data,
+        },
+    })
+}
+
+fn seqs(results: &[Result<(u8, String), DecodeError>]) -> Vec<Result<u8, DecodeError>> {
+    results
+        .iter()
+        .map(|result| result.as_ref().map(|(seq, _)| *seq).map_err(|e| *e))
+        .collect()
+}
+
+#[test]
+fn a_dropped_byte_costs_one_packet() {
+    let data = [9; 12];
+    let mut stream = pixels(1, 0, &data);
+    // Packet 1 now reaches into the sync word of packet 2
+    stream.remove(12);
+    stream.extend(pixels(2, 1, &data));
+    stream.extend(pixels(3, 2, &data));
+
+    let mut decoder = Decoder::<64>::new();
+    assert_eq!(
+        seqs(&decode_all(&mut decoder, &stream)),
+        [Err(DecodeError::Crc), Ok(2), Ok(3)]
+    );
+}
+
+#[test]
+fn packets_swallowed_by_a_bad_length_still_decode() {
+    let mut stream = pixels(1, 1, &[9; 12]);
+    // Without the high length byte, channel 1 becomes it: 15 + 256 bytes
+    stream.remove(5);
+    for seq in [2, 3] {
+        stream.extend(encoded(Packet {
+            seq,
+            frame: Frame::Show,
+        }));
+    }
+    stream.extend(pixels(4, 0, &[7; 300]));
+
+    let mut decoder = Decoder::<512>::new();
+    assert_eq!(
+        seqs(&decode_all(&mut decoder, &stream)),
+        [Err(DecodeError::Crc), Ok(2), Ok(3), Ok(4)]
+    );
+    assert!(decoder.is_idle());
+}
+
+#[test]
+fn rejects_what_it_cannot_hold_or_understand() {
+    let data = [0; 30];
+    let long = encoded(Packet {
+        seq: 0,
+        frame: Frame::Pixels {
+            channel: 0,
+            offset: 0,
+            data: &data,
+        },
+    });
+    let mut decoder = Decoder::<16>::new();
+    assert_eq!(
+        decode_all(&mut decoder, &long),
+        [Err(DecodeError::TooLong(33))]
+    );
+
+    // Channel 8 doesn't exist; patch the byte and fix up the CRC
+    let mut bad_channel = encoded(Packet {
+        seq: 0,
+        frame: Frame::Pixels {
+            channel: 0,
+            offset: 0,
+            data: &[1, 2, 3],
+        },
+    });
+    bad_channel[6] = 8;
+    let end = bad_channel.len() - 2;
+    let crc = crc16(&bad_channel[2..end]).to_le_bytes();
+    bad_channel[end..].copy_from_slice(&crc);
+
+    let mut decoder = Decoder::<16>::new();
+    assert_eq!(
+        decode_all(&mut decoder, &bad_channel),
+        [Err(DecodeError::Malformed(0x01))]
+    );
+}
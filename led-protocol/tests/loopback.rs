@@ -0,0 +1,170 @@
+//! Host and a stand-in device talking over a socket pair, the way the PC
+//! software talks to the board over USB CDC.
+
+use led_protocol::{Decoder, Frame, Packet, Stats, encode};
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::thread;
+
+const PIXELS: usize = 40;
+type Channels = [[u8; PIXELS * 3]; 8];
+
+fn send(stream: &mut UnixStream, packet: Packet<'_>) {
+    let mut out = vec![0; packet.frame.encoded_len()];
+    encode(&packet, &mut out).unwrap();
+    stream.write_all(&out).unwrap();
+}
+
+/// Applies pixel data to its own channels, shows them on `Show`, answers
+/// every packet and returns what was shown last once told to stop
+fn device(mut stream: UnixStream) -> (Channels, usize) {
+    let mut decoder = Decoder::<64>::new();
+    let mut pending: Channels = [[0; PIXELS * 3]; 8];
+    let mut shown = pending;
+    let mut rejected = 0;
+    let mut bytes = [0; 64]; // One USB full-speed packet
+
+    loop {
+        let read = stream.read(&mut bytes).unwrap();
+        assert!(read > 0, "host hung up without Stop");
+
+        for &byte in &bytes[..read] {
+            let (seq, reply) = match decoder.push(byte) {
+                None => continue,
+                Some(Err(_)) => {
+                    rejected += 1;
+                    (0, Frame::Nack)
+                }
+                Some(Ok(Packet { seq, frame })) => match frame {
+                    Frame::Pixels {
+                        channel,
+                        offset,
+                        data,
+                    } => {
+                        let start = offset as usize * 3;
+                        pending[channel as usize][start..start + data.len()].copy_from_slice(data);
+                        continue;
+                    }
+                    Frame::Show => {
+                        shown = pending;
+                        (seq, Frame::Ack)
+                    }
+                    Frame::Stop => {
+                        send(
+                            &mut stream,
+                            Packet {
+                                seq,
+                                frame: Frame::Ack,
+                            },
+                        );
+                        return (shown, rejected);
+                    }
+                    Frame::GetStats => (
+                        seq,
+                        Frame::Stats(Stats {
+                            frames_sent: 1,
+                            ..Stats::default()
+                        }),
+                    ),
+                    Frame::Ack | Frame::Nack | Frame::Stats(_) => (seq, Frame::Nack),
+                },
+            };
+            send(&mut stream, Packet { seq, frame: reply });
+        }
+    }
+}
+
+/// Read replies until one arrives
+fn reply(stream: &mut UnixStream, decoder: &mut Decoder<8>) -> Packet<'static> {
+    let mut byte = [0];
+    loop {
+        stream.read_exact(&mut byte).unwrap();
+        if let Some(result) = decoder.push(byte[0]) {
+            let packet = result.unwrap();
+            // Replies carry no payload, so they don't borrow the decoder
+            return Packet {
+                seq: packet.seq,
+                frame: match packet.frame {
+                    Frame::Ack => Frame::Ack,
+                    _ => Frame::Nack,
+                },
+            };
+        }
+    }
+}
+
+#[test]
+fn streams_all_channels_to_the_device() {
+    let (mut host, board) = UnixStream::pair().unwrap();
+    let device = thread::spawn(move || device(board));
+    let mut replies = Decoder::<8>::new();
+
+    let mut frame: Channels = [[0; PIXELS * 3]; 8];
+    for (channel, bytes) in frame.iter_mut().enumerate() {
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = (channel * 31 + i) as u8;
+        }
+    }
+
+    // Pixel data larger than the device's decoder goes out in pieces
+    let mut seq = 0u8;
+    for (channel, bytes) in frame.iter().enumerate() {
+        for (piece, data) in bytes.chunks(16 * 3).enumerate() {
+            send(
+                &mut host,
+                Packet {
+                    seq,
+                    frame: Frame::Pixels {
+                        channel: channel as u8,
+                        offset: (piece * 16) as u16,
+                        data,
+                    },
+                },
+            );
+            seq = seq.wrapping_add(1);
+        }
+    }
+
+    // Line noise between packets is skipped, a damaged packet is refused
+    host.write_all(&[0x00, 0xA5, 0x42]).unwrap();
+    let mut damaged = vec![0; Frame::Show.encoded_len()];
+    encode(
+        &Packet {
+            seq: 99,
+            frame: Frame::Show,
+        },
+        &mut damaged,
+    )
+    .unwrap();
+    damaged[7] ^= 1;
+    host.write_all(&damaged).unwrap();
+    assert_eq!(reply(&mut host, &mut replies).frame, Frame::Nack);
+
+    send(
+        &mut host,
+        Packet {
+            seq,
+            frame: Frame::Show,
+        },
+    );
+    assert_eq!(
+        reply(&mut host, &mut replies),
+        Packet {
+            seq,
+            frame: Frame::Ack
+        }
+    );
+
+    send(
+        &mut host,
+        Packet {
+            seq: seq + 1,
+            frame: Frame::Stop,
+        },
+    );
+    assert_eq!(reply(&mut host, &mut replies).frame, Frame::Ack);
+
+    let (shown, rejected) = device.join().unwrap();
+    assert_eq!(shown, frame);
+    assert_eq!(rejected, 1);
+}